@@ -0,0 +1,57 @@
+//! Procedural macros of the `editorconfig` crate. Use them through its `embed` feature
+//! rather than depending on this crate directly.
+extern crate ordermap;
+extern crate proc_macro;
+
+// A copy of the parser the editorconfig crate reads config files with, so that this crate
+// can be packaged on its own. The tests of editorconfig check that it's kept the same.
+#[allow(dead_code)]
+mod ini;
+
+use std::env;
+use std::fs::File;
+use std::path::Path;
+
+use proc_macro::TokenStream;
+
+/// Parses the config file at the given path, relative to the root of the crate being
+/// built, into an `editorconfig::EmbeddedConfig`.
+///
+/// A missing or malformed file is a compile error.
+#[proc_macro]
+pub fn embed(input: TokenStream) -> TokenStream {
+    match expand(&input.to_string()) {
+        Ok(expanded) => expanded.parse().unwrap(),
+        Err(message) => format!("compile_error!({:?})", message).parse().unwrap(),
+    }
+}
+
+fn expand(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    if input.len() < 2 || !input.starts_with('"') || !input.ends_with('"') || input.contains('\\') {
+        return Err("embed! expects the path of a config file as a plain string literal".to_string());
+    }
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?;
+    let path = Path::new(&manifest_dir).join(&input[1..input.len() - 1]);
+    let ini = File::open(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|mut file| ini::Ini::read_from(&mut file).map_err(|e| e.to_string()))
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut sections = String::new();
+    for (name, properties) in ini.iter() {
+        let name = match *name {
+            Some(ref name) => format!("Some({:?})", name),
+            None => "None".to_string(),
+        };
+        let properties: Vec<_> = properties.iter()
+            .map(|(key, value)| format!("({:?}, {:?})", key, value))
+            .collect();
+        sections.push_str(&format!("({}, &[{}]), ", name, properties.join(", ")));
+    }
+    // Including the file makes cargo rebuild when it changes
+    Ok(format!("{{ const _: &str = include_str!({:?}); \
+                ::editorconfig::EmbeddedConfig {{ sections: &[{}] }} }}",
+               path.display().to_string(),
+               sections))
+}
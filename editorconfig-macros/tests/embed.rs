@@ -0,0 +1,14 @@
+extern crate editorconfig;
+
+use editorconfig::{embed, EmbeddedConfig};
+
+static DEFAULTS: EmbeddedConfig = embed!("tests/defaults.editorconfig");
+
+#[test]
+fn embeds_config_files() {
+    assert_eq!(DEFAULTS.sections.len(), 3);
+    let properties = DEFAULTS.properties_for("src/main.rs");
+    assert_eq!(properties.get("end_of_line").unwrap(), "lf");
+    assert_eq!(properties.get("indent_size").unwrap(), "4");
+    assert!(DEFAULTS.properties_for("README.md").get("indent_size").is_none());
+}
@@ -0,0 +1,83 @@
+//! Records what a lookup read, for checking that only the expected files influenced it.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The config files read by lookups, in the order they were first read, where they stopped
+/// looking for more, and the environment variables they read. Safe to share between threads.
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    files: Mutex<Vec<PathBuf>>,
+    stops: Mutex<Vec<(PathBuf, String)>>,
+    env_vars: Mutex<Vec<String>>,
+}
+
+impl AuditLog {
+    pub fn new() -> AuditLog {
+        AuditLog::default()
+    }
+
+    /// Records that `path` was read, unless it already was.
+    pub fn record_read(&self, path: &Path) {
+        let mut files = self.files.lock().unwrap();
+        if !files.iter().any(|file| file == path) {
+            files.push(path.to_path_buf());
+        }
+    }
+
+    /// Returns the files read so far.
+    pub fn files_read(&self) -> Vec<PathBuf> {
+        self.files.lock().unwrap().clone()
+    }
+
+    /// Records that a lookup didn't look for config files above `dir` because of `reason`,
+    /// unless it already was.
+    pub fn record_stop(&self, dir: &Path, reason: &str) {
+        let mut stops = self.stops.lock().unwrap();
+        if !stops.iter().any(|(d, r)| d == dir && r == reason) {
+            stops.push((dir.to_path_buf(), reason.to_string()));
+        }
+    }
+
+    /// Returns the directories lookups stopped at so far, with the reason.
+    pub fn stops(&self) -> Vec<(PathBuf, String)> {
+        self.stops.lock().unwrap().clone()
+    }
+
+    /// Records that the environment variable `name` was read, unless it already was.
+    pub fn record_env_var(&self, name: &str) {
+        let mut env_vars = self.env_vars.lock().unwrap();
+        if !env_vars.iter().any(|var| var == name) {
+            env_vars.push(name.to_string());
+        }
+    }
+
+    /// Returns the environment variables read so far, in the order they were first read.
+    pub fn env_vars_read(&self) -> Vec<String> {
+        self.env_vars.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_each_file_once() {
+        let log = AuditLog::new();
+        log.record_read(Path::new("/b/.editorconfig"));
+        log.record_read(Path::new("/a/.editorconfig"));
+        log.record_read(Path::new("/b/.editorconfig"));
+        assert_eq!(log.files_read(),
+                   vec![PathBuf::from("/b/.editorconfig"), PathBuf::from("/a/.editorconfig")]);
+    }
+
+    #[test]
+    fn records_each_env_var_once() {
+        let log = AuditLog::new();
+        log.record_env_var("XDG_CONFIG_HOME");
+        log.record_env_var("HOME");
+        log.record_env_var("XDG_CONFIG_HOME");
+        assert_eq!(log.env_vars_read(), ["XDG_CONFIG_HOME", "HOME"]);
+    }
+}
@@ -0,0 +1,8 @@
+extern crate editorconfig;
+
+use std::env;
+use std::process;
+
+fn main() {
+    process::exit(editorconfig::cli::run_core(env::args().collect()));
+}
@@ -0,0 +1,222 @@
+//! Config files parsed by earlier lookups, so the next ones don't read and parse them again,
+//! and the results of the lookups themselves, so looking up a file again doesn't match any
+//! section until a config file it depends on changes.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use ini::Ini;
+use {FileStamp, Properties, Warning};
+
+/// A parsed config file, with the warnings parsing it gave, repeated on every lookup using it.
+#[derive(Debug)]
+pub(crate) struct ParsedConfig {
+    pub ini: Ini,
+    pub warnings: Vec<Warning>,
+    /// The stamp of the file before it was read, it's parsed again when that changes.
+    pub stamp: Option<FileStamp>,
+}
+
+/// The result of a lookup, with the warnings it gave, repeated when it's reused.
+#[derive(Debug)]
+pub(crate) struct ResolvedLookup {
+    pub properties: Properties,
+    pub warnings: Vec<Warning>,
+    /// The stamps of the directories crawled and of the config files read, taken before they
+    /// were: the lookup is done again when any of them changes.
+    pub stamps: Vec<(PathBuf, Option<FileStamp>)>,
+}
+
+/// The lookups kept, by target, and which of them each config file was read for.
+#[derive(Debug, Default)]
+struct ResolvedLookups {
+    lookups: HashMap<PathBuf, Arc<ResolvedLookup>>,
+    dependents: HashMap<PathBuf, HashSet<PathBuf>>,
+}
+
+impl ResolvedLookups {
+    fn remove(&mut self, target: &Path) {
+        if let Some(resolved) = self.lookups.remove(target) {
+            for conf_file in resolved.properties.contributing_configs() {
+                if let Some(dependents) = self.dependents.get_mut(conf_file) {
+                    dependents.remove(target);
+                    if dependents.is_empty() {
+                        self.dependents.remove(conf_file);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Parsed config files by path, the directories found without config files and the
+/// results of lookups. Safe to share between threads, lookups only hold the lock to get or
+/// add an entry, not while parsing or matching.
+#[derive(Debug, Default)]
+pub(crate) struct ConfigCache {
+    parsed: Mutex<HashMap<PathBuf, Arc<ParsedConfig>>>,
+    /// The stamps directories had when they held no config file: creating one changes the
+    /// stamp, so they're only skipped while it's the same.
+    without_config: Mutex<HashMap<PathBuf, FileStamp>>,
+    resolved: Mutex<ResolvedLookups>,
+}
+
+impl ConfigCache {
+    pub fn new() -> ConfigCache {
+        ConfigCache::default()
+    }
+
+    pub fn get(&self, conf_file: &Path) -> Option<Arc<ParsedConfig>> {
+        self.parsed.lock().unwrap().get(conf_file).cloned()
+    }
+
+    pub fn insert(&self, conf_file: &Path, parsed: ParsedConfig) -> Arc<ParsedConfig> {
+        let parsed = Arc::new(parsed);
+        self.parsed.lock().unwrap().insert(conf_file.to_path_buf(), parsed.clone());
+        parsed
+    }
+
+    /// Forgets the config file at `conf_file`, returning whether it was there, and that its
+    /// directory had no config file. Only the lookups it affects are forgotten with it: those
+    /// that read it, and those of the files below its directory, which it may now apply to.
+    pub fn remove(&self, conf_file: &Path) -> bool {
+        let mut resolved = self.resolved.lock().unwrap();
+        let mut affected: Vec<_> =
+            resolved.dependents.get(conf_file).into_iter().flatten().cloned().collect();
+        if let Some(dir) = conf_file.parent() {
+            self.without_config.lock().unwrap().remove(dir);
+            let below = resolved.lookups.keys().filter(|target| target.starts_with(dir));
+            affected.extend(below.cloned());
+        }
+        for target in affected {
+            resolved.remove(&target);
+        }
+        self.parsed.lock().unwrap().remove(conf_file).is_some()
+    }
+
+    pub fn clear(&self) {
+        self.parsed.lock().unwrap().clear();
+        self.without_config.lock().unwrap().clear();
+        *self.resolved.lock().unwrap() = ResolvedLookups::default();
+    }
+
+    /// The lookup of `target` kept by `insert_resolved`, whether or not it's still current.
+    pub fn resolved(&self, target: &Path) -> Option<Arc<ResolvedLookup>> {
+        self.resolved.lock().unwrap().lookups.get(target).cloned()
+    }
+
+    pub fn insert_resolved(&self, target: &Path, resolved: ResolvedLookup) {
+        let mut lookups = self.resolved.lock().unwrap();
+        lookups.remove(target);
+        for conf_file in resolved.properties.contributing_configs() {
+            lookups.dependents
+                .entry(conf_file.clone())
+                .or_default()
+                .insert(target.to_path_buf());
+        }
+        lookups.lookups.insert(target.to_path_buf(), Arc::new(resolved));
+    }
+
+    /// The targets of the lookups held, sorted.
+    pub fn resolved_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<_> = self.resolved.lock().unwrap().lookups.keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    /// Whether `dir` held no config file when it last had `stamp`. Never for directories
+    /// without a stamp, which can't be told to have changed.
+    pub fn known_without_config(&self, dir: &Path, stamp: Option<FileStamp>) -> bool {
+        stamp.is_some() && self.without_config.lock().unwrap().get(dir) == stamp.as_ref()
+    }
+
+    /// Remembers that `dir`, stamped `stamp`, holds no config file.
+    pub fn record_without_config(&self, dir: &Path, stamp: Option<FileStamp>) {
+        if let Some(stamp) = stamp {
+            self.without_config.lock().unwrap().insert(dir.to_path_buf(), stamp);
+        }
+    }
+
+    /// The paths of the config files held, sorted.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<_> = self.parsed.lock().unwrap().keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(contents: &str) -> ParsedConfig {
+        ParsedConfig {
+            ini: Ini::load_from_str(contents).unwrap(),
+            warnings: vec![],
+            stamp: None,
+        }
+    }
+
+    fn resolved(conf_files: &[&str]) -> ResolvedLookup {
+        ResolvedLookup {
+            properties: Properties::new(Default::default(),
+                                        conf_files.iter().map(PathBuf::from).collect()),
+            warnings: vec![],
+            stamps: vec![],
+        }
+    }
+
+    #[test]
+    fn forgets_the_lookups_a_config_file_affects() {
+        let cache = ConfigCache::new();
+        cache.insert_resolved(Path::new("/r/a/x.rs"), resolved(&["/r/a/.editorconfig"]));
+        cache.insert_resolved(Path::new("/r/b/y.rs"), resolved(&["/g/global.ini"]));
+        cache.insert_resolved(Path::new("/r/c/z.rs"), resolved(&["/g/global.ini"]));
+        cache.insert_resolved(Path::new("/s/w.rs"), resolved(&[]));
+
+        // Read for the first lookup only
+        cache.remove(Path::new("/r/a/.editorconfig"));
+        assert_eq!(cache.resolved_paths(),
+                   [PathBuf::from("/r/b/y.rs"),
+                    PathBuf::from("/r/c/z.rs"),
+                    PathBuf::from("/s/w.rs")]);
+        // A new config file applies to the files below it
+        cache.remove(Path::new("/r/c/.editorconfig"));
+        assert_eq!(cache.resolved_paths(), [PathBuf::from("/r/b/y.rs"), PathBuf::from("/s/w.rs")]);
+        // Read for lookups anywhere
+        cache.remove(Path::new("/g/global.ini"));
+        assert_eq!(cache.resolved_paths(), [PathBuf::from("/s/w.rs")]);
+        assert!(cache.resolved.lock().unwrap().dependents.is_empty());
+        assert!(cache.resolved(Path::new("/s/w.rs")).is_some());
+        cache.clear();
+        assert!(cache.resolved_paths().is_empty());
+    }
+
+    #[test]
+    fn forgets_config_files_and_directories_without_them() {
+        let cache = ConfigCache::new();
+        cache.insert(Path::new("/r/b/.editorconfig"), parsed("[*]\nindent_size = 2\n"));
+        cache.insert(Path::new("/r/a/.editorconfig"), parsed("root = true\n"));
+        let held = cache.get(Path::new("/r/b/.editorconfig")).unwrap();
+        assert_eq!(held.ini.get_from(Some("*"), "indent_size").unwrap(), "2");
+        assert_eq!(cache.paths(),
+                   [PathBuf::from("/r/a/.editorconfig"), PathBuf::from("/r/b/.editorconfig")]);
+
+        let stamp = FileStamp { modified: None, len: 3 };
+        cache.record_without_config(Path::new("/r/c"), Some(stamp));
+        cache.record_without_config(Path::new("/r/d"), None);
+        assert!(cache.known_without_config(Path::new("/r/c"), Some(stamp)));
+        let changed = FileStamp { len: 4, ..stamp };
+        assert!(!cache.known_without_config(Path::new("/r/c"), Some(changed)));
+        assert!(!cache.known_without_config(Path::new("/r/d"), None));
+
+        // A config file created in /r/c makes it a directory with a config file again
+        assert!(!cache.remove(Path::new("/r/c/.editorconfig")));
+        assert!(!cache.known_without_config(Path::new("/r/c"), Some(stamp)));
+        assert!(cache.remove(Path::new("/r/b/.editorconfig")));
+        assert!(cache.get(Path::new("/r/b/.editorconfig")).is_none());
+        cache.clear();
+        assert!(cache.paths().is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+//! Telling files people write apart from generated, vendored and binary ones, which checks
+//! shouldn't hold to the same conventions.
+
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use {Error, Glob};
+
+/// What kind of file a path is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileClass {
+    /// Written and maintained by people, what checks are about.
+    Source,
+    /// Settings of tools, like `.editorconfig` or `Cargo.toml`.
+    Config,
+    /// Produced by a tool, like lock files or minified scripts.
+    Generated,
+    /// Copied from another project, like everything below `vendor/`.
+    Vendored,
+    /// Not text.
+    Binary,
+}
+
+impl FileClass {
+    /// Whether people edit files of this class, so checks apply to them.
+    pub fn is_authored(self) -> bool {
+        self == FileClass::Source || self == FileClass::Config
+    }
+}
+
+impl FromStr for FileClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<FileClass, String> {
+        match s {
+            "source" => Ok(FileClass::Source),
+            "config" => Ok(FileClass::Config),
+            "generated" => Ok(FileClass::Generated),
+            "vendored" => Ok(FileClass::Vendored),
+            "binary" => Ok(FileClass::Binary),
+            _ => {
+                Err(format!("unknown file class `{}`, expected source, config, generated, \
+                             vendored or binary",
+                            s))
+            }
+        }
+    }
+}
+
+impl fmt::Display for FileClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            FileClass::Source => "source",
+            FileClass::Config => "config",
+            FileClass::Generated => "generated",
+            FileClass::Vendored => "vendored",
+            FileClass::Binary => "binary",
+        })
+    }
+}
+
+/// Directories whose contents come from other projects.
+const VENDORED_DIRS: &[&str] = &["vendor", "node_modules", "third_party", "bower_components"];
+/// File names, or suffixes of file names, of generated files.
+const GENERATED_SUFFIXES: &[&str] = &["Cargo.lock", "package-lock.json", "yarn.lock", "go.sum",
+                                      ".min.js", ".min.css", ".pb.go", "_pb2.py", ".map"];
+/// Extensions of config files, besides files starting with a dot.
+const CONFIG_EXTENSIONS: &[&str] = &["toml", "ini", "cfg", "conf", "yaml", "yml", "json"];
+/// Extensions of binary files, for files that can't be read.
+const BINARY_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "ico", "pdf", "zip", "gz",
+                                     "tar", "exe", "dll", "so", "dylib", "a", "o", "class",
+                                     "jar", "wasm", "woff", "woff2", "ttf"];
+/// The line marking a file as generated in Go, `// Code generated by X. DO NOT EDIT.`,
+/// which other tools write too.
+const GENERATED_LINE: &str = r"^// Code generated .* DO NOT EDIT\.$";
+/// The tag marking a file as generated anywhere on a line, like `// @generated by bindgen`.
+const GENERATED_TAG: &str = "@generated";
+/// How many lines at the start of a file are searched for generated file markers.
+const GENERATED_MARKER_LINES: usize = 10;
+/// How much of a file is read to tell whether it's binary or generated.
+const SNIFF_LENGTH: u64 = 8000;
+
+struct ClassRule {
+    class: FileClass,
+    glob: Glob,
+}
+
+/// Classifies files with patterns given by the user, then with heuristics on their path and
+/// contents.
+pub struct Classifier {
+    /// The directory the patterns are relative to.
+    root: PathBuf,
+    rules: Vec<ClassRule>,
+}
+
+impl Classifier {
+    /// A classifier of no pattern, whose patterns will be relative to `root`.
+    pub fn new(root: &Path) -> Classifier {
+        Classifier {
+            root: root.to_path_buf(),
+            rules: vec![],
+        }
+    }
+
+    /// Gives `class` to the files matching `pattern`, e.g. `**/gen/**`. Patterns have the
+    /// semantics of section names, and a later pattern wins over an earlier one.
+    pub fn add_pattern(&mut self, class: FileClass, pattern: &str) -> Result<(), Error> {
+        let pattern = if pattern.contains('/') && !pattern.starts_with('/') {
+            format!("/{}", pattern)
+        } else {
+            pattern.to_string()
+        };
+        let glob = Glob::new(&pattern)?;
+        self.rules.push(ClassRule { class, glob });
+        Ok(())
+    }
+
+    /// Returns the class of `path`. Relative paths are taken to be relative to the root.
+    pub fn classify(&self, path: &Path) -> FileClass {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let relative = relative.trim_start_matches('/');
+        for rule in self.rules.iter().rev() {
+            if rule.glob.matches(relative) {
+                return rule.class;
+            }
+        }
+        classify(&self.root.join(path))
+    }
+}
+
+/// Returns the class of `path` according to its name, the directories it's in and, for
+/// files that exist, whether their start contains a NUL byte or a generated file marker.
+pub fn classify(path: &Path) -> FileClass {
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let start = read_start(path);
+    let is_binary = match start {
+        Some(ref start) => start.contains(&0),
+        None => BINARY_EXTENSIONS.contains(&extension.as_str()),
+    };
+    if is_binary {
+        return FileClass::Binary;
+    }
+    let is_generated = start.is_some_and(|start| {
+        String::from_utf8_lossy(&start)
+            .lines()
+            .take(GENERATED_MARKER_LINES)
+            .any(is_generated_marker)
+    }) || GENERATED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix));
+    let in_vendored_dir = path.parent()
+        .map(|dir| {
+            dir.components().any(|component| match component {
+                Component::Normal(dir) => VENDORED_DIRS.iter().any(|&vendored| dir == vendored),
+                _ => false,
+            })
+        })
+        .unwrap_or(false);
+    if in_vendored_dir {
+        FileClass::Vendored
+    } else if is_generated {
+        FileClass::Generated
+    } else if name.starts_with('.') || CONFIG_EXTENSIONS.contains(&extension.as_str()) {
+        FileClass::Config
+    } else {
+        FileClass::Source
+    }
+}
+
+/// Whether `line` says the file it's in is generated.
+fn is_generated_marker(line: &str) -> bool {
+    static GENERATED_LINE_REGEX: OnceLock<Regex> = OnceLock::new();
+    let generated_line = GENERATED_LINE_REGEX.get_or_init(|| Regex::new(GENERATED_LINE).unwrap());
+    line.contains(GENERATED_TAG) || generated_line.is_match(line)
+}
+
+/// Reads the start of a file, `None` if it can't be read.
+fn read_start(path: &Path) -> Option<Vec<u8>> {
+    let mut start = vec![];
+    File::open(path).ok()?.take(SNIFF_LENGTH).read_to_end(&mut start).ok()?;
+    Some(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, process};
+
+    #[test]
+    fn classifies_with_heuristics() {
+        assert_eq!(classify(Path::new("src/lib.rs")), FileClass::Source);
+        assert_eq!(classify(Path::new("Cargo.toml")), FileClass::Config);
+        assert_eq!(classify(Path::new("test_files/lenient/.editorconfig")), FileClass::Config);
+        assert_eq!(classify(Path::new("Cargo.lock")), FileClass::Generated);
+        assert_eq!(classify(Path::new("web/app.min.js")), FileClass::Generated);
+        assert_eq!(classify(Path::new("web/node_modules/x/index.js")), FileClass::Vendored);
+        assert_eq!(classify(Path::new("missing/logo.PNG")), FileClass::Binary);
+        assert!(!FileClass::Vendored.is_authored());
+    }
+
+    #[test]
+    fn recognizes_generated_file_markers() {
+        let dir = env::temp_dir().join(format!("editorconfig-classify-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, contents: &str| {
+            fs::write(dir.join(name), contents).unwrap();
+            classify(&dir.join(name))
+        };
+        assert_eq!(write("a.go", "// Code generated by stringer. DO NOT EDIT.\npackage a\n"),
+                   FileClass::Generated);
+        assert_eq!(write("b.rs", "//! Bindings\n//!\n// @generated by bindgen\n"),
+                   FileClass::Generated);
+        let late_marker = format!("{}// @generated\n", "\n".repeat(GENERATED_MARKER_LINES));
+        assert_eq!(write("c.rs", &late_marker), FileClass::Source);
+        assert_eq!(write("d.bin", "\0\0"), FileClass::Binary);
+        // Only the conventional line, not any mention of it
+        assert_eq!(write("e.go", "// Code generated by hand, DO NOT EDIT.\r\npackage e\n"),
+                   FileClass::Generated);
+        assert_eq!(write("f.go", "// Writes files saying DO NOT EDIT.\npackage f\n"),
+                   FileClass::Source);
+        assert_eq!(write("g.rs", "let header = \"// Code generated by g. DO NOT EDIT.\";\n"),
+                   FileClass::Source);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn user_patterns_win_over_heuristics() {
+        let mut classifier = Classifier::new(Path::new("/repo"));
+        classifier.add_pattern(FileClass::Generated, "**/gen/**").unwrap();
+        classifier.add_pattern(FileClass::Source, "vendor/ours/**").unwrap();
+        assert_eq!(classifier.classify(Path::new("/repo/api/gen/types.rs")),
+                   FileClass::Generated);
+        assert_eq!(classifier.classify(Path::new("gen/types.rs")), FileClass::Generated);
+        assert_eq!(classifier.classify(Path::new("vendor/ours/lib.c")), FileClass::Source);
+        assert_eq!(classifier.classify(Path::new("vendor/theirs/lib.c")), FileClass::Vendored);
+        assert!(classifier.add_pattern(FileClass::Source, "(").is_err());
+        assert_eq!("generated".parse::<FileClass>(), Ok(FileClass::Generated));
+        assert!("authored".parse::<FileClass>().is_err());
+    }
+}
@@ -0,0 +1,2030 @@
+//! The `editorconfig` command line tool, as a library.
+//!
+//! `src/main.rs` only forwards to `run`, so everything the binary does can be done
+//! in-process: call `run_in` with your own output streams, or parse the arguments with
+//! `parse_args` and hand the resulting `Options` to `execute`.
+
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use argparse::{ArgumentParser, Collect, Store, StoreTrue, StoreFalse, List};
+
+use init::{ask_answers, detect_languages, render_config, update_config, TemplateVars};
+use manifest::json_string;
+use parallel::map_ordered;
+use {active_features, diagnose, explain, find_config_files, find_inline_comments, freeze_config,
+     get_config_with, is_permission_denied, lint_config_files, redundant_settings,
+     remove_redundant, simplify_config, verify_idempotent, write_report, AuditLog, Classifier,
+     CodeOwners, Diagnostic, EnvOverrides, Error, FileClass, Fix, GitEolSettings, GroupBy,
+     JsonReporter, JunitReporter, LookupOptions, Manifest, OutputFormat, PathStyle, Phase,
+     RdjsonReporter, ReportOptions, Reporter, SarifReporter, SpecVersion, TextReporter, Timings,
+     ToolConfig, WalkOptions, Warning, WarningAggregator, WarningKind, ENV_OVERRIDE_VARS, RULES};
+
+/// How many entries `--timings` lists per category.
+pub const TIMINGS_REPORT_LENGTH: usize = 10;
+
+/// Everything the command line tool can be asked to do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    /// Name of the config files to look for (`-f`).
+    pub conf_filename: String,
+    /// Names of files overriding the config files in the same directory (`--override`).
+    pub conf_overrides: Vec<String>,
+    /// EditorConfig version to emulate (`-b`).
+    pub version: String,
+    /// Oldest EditorConfig version editors of the project use, empty for none
+    /// (`--min-version`).
+    pub min_version: String,
+    /// Fail on unreadable files instead of skipping them (`--strict-io`).
+    pub strict_io: bool,
+    /// Ignore config files outside of this directory, empty for none (`--trust-root`).
+    pub trust_root: String,
+    /// Don't look for config files above this directory, empty for none (`--stop-dir`).
+    pub stop_dir: String,
+    /// A config file giving the properties no other sets, empty for none (`--global-config`).
+    pub global_config: String,
+    /// Also read the config file of the user after all the others (`--user-config`).
+    pub user_config: bool,
+    /// Ignore config files owned by another user (`--trust-owner`).
+    pub trust_same_owner: bool,
+    /// Compare patterns and paths in NFC (`--normalize-unicode`).
+    pub normalize_unicode: bool,
+    /// Read sizes written with units like `4ch` as plain numbers (`--lenient-values`).
+    pub lenient_values: bool,
+    /// Repair malformed config files instead of failing (`--repair`).
+    pub repair_malformed: bool,
+    /// Fail for targets no config file applies to (`--fail-on-missing-config`).
+    pub require_config: bool,
+    /// Also read config files whose name differs in case (`--conf-any-case`).
+    pub conf_any_case: bool,
+    /// Don't look for config files on another file system (`--one-file-system`).
+    pub one_file_system: bool,
+    /// Descend into symlinked directories when walking a tree (`--follow-symlinks`).
+    pub follow_symlinks: bool,
+    /// Walk directories reachable through several paths once (`--no-dedup-dirs` to unset).
+    pub dedup_visited: bool,
+    /// Leave out files a git sparse checkout excludes when walking a tree (`--sparse`).
+    pub sparse_checkout: bool,
+    /// How to print file paths (`--paths`).
+    pub path_style: PathStyle,
+    /// How to aggregate the problems found by `check` (`--group-by`).
+    pub group_by: GroupBy,
+    /// Show who owns the files with problems according to CODEOWNERS (`--owners`).
+    pub owners: bool,
+    /// What the properties of the targets are written as (`--format`).
+    pub format: OutputFormat,
+    /// Patterns of the files of each class, checked before the heuristics of `classify`.
+    pub classify: Vec<(FileClass, Vec<String>)>,
+    /// Print the effective settings instead of resolving anything (`--dump-config`).
+    pub dump_config: bool,
+    /// Print the version instead of resolving anything (`-v`).
+    pub show_version: bool,
+    /// Print the documentation of a diagnostic code instead, empty for none (`--explain`).
+    pub explain: String,
+    /// Report the slowest targets and config files on stderr (`--timings`), in the modes
+    /// looking files up.
+    pub timings: bool,
+    /// How many targets to look up at once, 0 for one per CPU (`--jobs`). The output keeps
+    /// the order of the targets.
+    pub jobs: usize,
+    /// List everything that influenced the lookups instead of the settings (`audit`).
+    pub audit: bool,
+    /// Look for problems instead of printing the settings (`check`).
+    pub check: bool,
+    /// Check settings outside of config files overriding the targets' (`check --environment`).
+    pub check_environment: bool,
+    /// Check config files restating inherited values (`check --redundant`).
+    pub check_redundant: bool,
+    /// Check config files for comments after values (`check --comments`).
+    pub check_comments: bool,
+    /// Delete what checks found instead of reporting it, where possible (`check --fix`).
+    pub fix: bool,
+    /// With `fix`, apply the fixes twice to copies of the config files instead, failing if the
+    /// second pass changes anything (`--verify-idempotent`).
+    pub verify_idempotent: bool,
+    /// Look for problems in config files instead of printing the settings (`lint`).
+    pub lint: bool,
+    /// Lint every config file below a directory, not only the targets' (`lint --all`).
+    pub lint_all: bool,
+    /// Write a scored report on the config files below a directory instead (`doctor`).
+    pub doctor: bool,
+    /// Write one config file equivalent to the ones applying to a file instead (`export`).
+    pub export: bool,
+    /// The file to export the configuration of (`export --for`).
+    pub export_for: String,
+    /// Write the fingerprint of every file below a directory instead (`manifest`).
+    pub manifest: bool,
+    /// Where `export` and `manifest` write, empty for stdout (`--out`).
+    pub out: String,
+    /// Check generated files too (`check --include-generated`).
+    pub include_generated: bool,
+    /// Write a new config file in the current directory instead (`init`).
+    pub init: bool,
+    /// Ask questions to write the new config file (`init --interactive`).
+    pub interactive: bool,
+    /// Generate the block written by `init` again (`init --update`).
+    pub update: bool,
+    /// The tool config file the defaults were read from, if any.
+    pub tool_config_path: Option<PathBuf>,
+    /// The environment variables the defaults were read from, see `EnvOverrides::from_env`.
+    pub env_vars_read: Vec<String>,
+    /// Files to resolve the configuration for.
+    pub targets: Vec<String>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options::new(&ToolConfig::default())
+    }
+}
+
+impl Options {
+    /// The options used when no flags are given, taking `tool_config` into account.
+    pub fn new(tool_config: &ToolConfig) -> Options {
+        Options {
+            conf_filename: tool_config.conf_filename
+                .clone()
+                .unwrap_or_else(|| ".editorconfig".to_string()),
+            conf_overrides: tool_config.conf_overrides.clone().unwrap_or_default(),
+            version: tool_config.spec_version.clone().unwrap_or_default(),
+            min_version: tool_config.min_spec_version.clone().unwrap_or_default(),
+            strict_io: tool_config.strict_io.unwrap_or(false),
+            trust_root: tool_config.trust_root.clone().unwrap_or_default(),
+            stop_dir: tool_config.stop_dir.clone().unwrap_or_default(),
+            global_config: tool_config.global_config.clone().unwrap_or_default(),
+            user_config: tool_config.user_config.unwrap_or(false),
+            trust_same_owner: tool_config.trust_same_owner.unwrap_or(false),
+            normalize_unicode: tool_config.normalize_unicode
+                .unwrap_or_else(|| LookupOptions::default().normalize_unicode),
+            lenient_values: tool_config.lenient_values.unwrap_or(false),
+            repair_malformed: tool_config.repair_malformed.unwrap_or(false),
+            require_config: tool_config.require_config.unwrap_or(false),
+            conf_any_case: tool_config.conf_any_case.unwrap_or(false),
+            one_file_system: tool_config.one_file_system.unwrap_or(false),
+            follow_symlinks: tool_config.follow_symlinks.unwrap_or(false),
+            dedup_visited: tool_config.dedup_visited
+                .unwrap_or_else(|| WalkOptions::default().dedup_visited),
+            sparse_checkout: tool_config.sparse_checkout.unwrap_or(false),
+            path_style: tool_config.paths.unwrap_or_default(),
+            group_by: tool_config.group_by.unwrap_or_default(),
+            owners: tool_config.owners.unwrap_or(false),
+            format: tool_config.format.unwrap_or_default(),
+            classify: tool_config.classify.clone().unwrap_or_default(),
+            dump_config: false,
+            show_version: false,
+            explain: String::new(),
+            timings: false,
+            jobs: 0,
+            audit: false,
+            check: false,
+            check_environment: false,
+            check_redundant: false,
+            check_comments: false,
+            fix: false,
+            verify_idempotent: false,
+            lint: false,
+            lint_all: false,
+            doctor: false,
+            export: false,
+            export_for: String::new(),
+            manifest: false,
+            out: String::new(),
+            include_generated: false,
+            init: false,
+            interactive: false,
+            update: false,
+            tool_config_path: None,
+            env_vars_read: vec![],
+            targets: vec![],
+        }
+    }
+
+    /// The library options matching these CLI options.
+    pub fn lookup_options(&self) -> LookupOptions {
+        LookupOptions {
+            conffile: self.conf_filename.clone(),
+            conffile_overrides: self.conf_overrides.clone(),
+            skip_unreadable: !self.strict_io,
+            timings: None,
+            audit: None,
+            observer: None,
+            trust_root: if self.trust_root.is_empty() {
+                None
+            } else {
+                Some(PathBuf::from(&self.trust_root))
+            },
+            stop_dir: if self.stop_dir.is_empty() {
+                None
+            } else {
+                Some(PathBuf::from(&self.stop_dir))
+            },
+            global_config: if self.global_config.is_empty() {
+                None
+            } else {
+                Some(PathBuf::from(&self.global_config))
+            },
+            user_config: self.user_config,
+            trust_same_owner: self.trust_same_owner,
+            normalize_unicode: self.normalize_unicode,
+            lenient_values: self.lenient_values,
+            repair_malformed: self.repair_malformed,
+            require_config: self.require_config,
+            conffile_any_case: self.conf_any_case,
+            one_file_system: self.one_file_system,
+            spec_version: if self.version.is_empty() {
+                None
+            } else {
+                Some(self.version.clone())
+            },
+            min_spec_version: if self.min_version.is_empty() {
+                None
+            } else {
+                Some(self.min_version.clone())
+            },
+            ..LookupOptions::default()
+        }
+    }
+
+    /// `lookup_options`, recording where lookups spend their time in `timings` with
+    /// `--timings`.
+    fn timed_lookup_options(&self, timings: &Arc<Timings>) -> LookupOptions {
+        let mut lookup = self.lookup_options();
+        if self.timings {
+            lookup.timings = Some(timings.clone());
+        }
+        lookup
+    }
+
+    /// The library options matching these CLI options, for the modes walking a tree.
+    pub fn walk_options(&self) -> WalkOptions {
+        WalkOptions {
+            follow_symlinks: self.follow_symlinks,
+            dedup_visited: self.dedup_visited,
+            skip_unreadable: !self.strict_io,
+            sparse_checkout: self.sparse_checkout,
+        }
+    }
+}
+
+/// Parses command line arguments (including the program name) on top of `defaults`.
+///
+/// A leading `audit`, `check`, `doctor`, `export`, `init`, `lint` or `manifest` argument
+/// selects that mode, the remaining arguments are parsed as usual. Targets with these names
+/// can still be given as e.g. `./audit`.
+///
+/// On `--help` or invalid arguments, the help or error is written out and the exit code
+/// the process should end with is returned as the error.
+pub fn parse_args(args: Vec<String>,
+                  defaults: Options,
+                  stdout: &mut dyn Write,
+                  stderr: &mut dyn Write)
+                  -> Result<Options, i32> {
+    let mut options = defaults;
+    let mut args = args;
+    match args.get(1).map(String::as_str) {
+        Some("audit") => options.audit = true,
+        Some("check") => options.check = true,
+        Some("doctor") => options.doctor = true,
+        Some("export") => options.export = true,
+        Some("init") => options.init = true,
+        Some("lint") => options.lint = true,
+        Some("manifest") => options.manifest = true,
+        _ => {}
+    }
+    if options.audit || options.check || options.doctor || options.export || options.init ||
+       options.lint || options.manifest {
+        args.remove(1);
+    }
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Parse .editorconfig files. Start with `audit` to list the files, \
+                            environment variables and features affecting each target instead, \
+                            with `check --environment` to find settings overriding them, \
+                            with `doctor DIR` to score the config files of DIR, \
+                            with `lint --all DIR` to find problems in every config file of DIR, \
+                            with `export --for FILE` to flatten its config files into one, \
+                            with `init --interactive` to write a new config file, \
+                            `init --update` to refresh one, or with `manifest DIR` to list \
+                            the properties of every file in DIR as JSON.");
+        ap.refer(&mut options.conf_filename)
+            .add_option(&["-f"], Store, "Conf filename");
+        ap.refer(&mut options.conf_overrides)
+            .add_option(&["--override"], Collect,
+                        "Also read files with this name, overriding the conf files in the same \
+                         directory (e.g. .editorconfig.user)");
+        ap.refer(&mut options.version)
+            .add_option(&["-b"], Store, "editorconfig version");
+        ap.refer(&mut options.min_version)
+            .add_option(&["--min-version"], Store,
+                        "With lint, report properties and values editors on an older \
+                         EditorConfig version than this one ignore");
+        ap.refer(&mut options.strict_io)
+            .add_option(&["--strict-io"], StoreTrue,
+                        "Fail on unreadable files instead of skipping them with a warning")
+            .add_option(&["--no-strict-io"], StoreFalse,
+                        "Skip unreadable files with a warning (default)");
+        ap.refer(&mut options.trust_root)
+            .add_option(&["--trust-root"], Store,
+                        "Ignore config files outside of this directory");
+        ap.refer(&mut options.stop_dir)
+            .add_option(&["--stop-dir"], Store,
+                        "Don't look for config files above this directory, like the workspace \
+                         root, even without root = true");
+        ap.refer(&mut options.global_config)
+            .add_option(&["--global-config"], Store,
+                        "Conf file giving the properties no other conf file sets, read after \
+                         all of them");
+        ap.refer(&mut options.user_config)
+            .add_option(&["--user-config"], StoreTrue,
+                        "Also read $XDG_CONFIG_HOME/editorconfig/config or ~/.editorconfig after \
+                         all the conf files, for the properties none of them sets");
+        ap.refer(&mut options.trust_same_owner)
+            .add_option(&["--trust-owner"], StoreTrue,
+                        "Ignore config files owned by another user than the workspace");
+        ap.refer(&mut options.normalize_unicode)
+            .add_option(&["--normalize-unicode"], StoreTrue,
+                        "Match composed patterns against decomposed file names and vice versa \
+                         (default on macOS)")
+            .add_option(&["--no-normalize-unicode"], StoreFalse,
+                        "Match patterns and file names as they're written");
+        ap.refer(&mut options.lenient_values)
+            .add_option(&["--lenient-values"], StoreTrue,
+                        "Read sizes written with a unit, like 4ch, as plain numbers, with a warning");
+        ap.refer(&mut options.repair_malformed)
+            .add_option(&["--repair"], StoreTrue,
+                        "Read malformed config files as best as possible, with a warning per \
+                         repaired line");
+        ap.refer(&mut options.require_config)
+            .add_option(&["--fail-on-missing-config"], StoreTrue,
+                        "Fail for files no config file applies to, listing them on stderr");
+        ap.refer(&mut options.conf_any_case)
+            .add_option(&["--conf-any-case"], StoreTrue,
+                        "Also read conf files whose name differs in case, like .EditorConfig, \
+                         with a warning suggesting to rename them");
+        ap.refer(&mut options.one_file_system)
+            .add_option(&["--one-file-system"], StoreTrue,
+                        "Don't look for conf files on another file system than the target's, \
+                         like a host above a network mount");
+        ap.refer(&mut options.follow_symlinks)
+            .add_option(&["--follow-symlinks"], StoreTrue,
+                        "With doctor, lint --all and manifest, walk into symlinked directories")
+            .add_option(&["--no-follow-symlinks"], StoreFalse,
+                        "Don't walk into symlinked directories (default)");
+        ap.refer(&mut options.dedup_visited)
+            .add_option(&["--dedup-dirs"], StoreTrue,
+                        "Walk directories reachable through several paths, like bind mounts or \
+                         symlinks, only once (default)")
+            .add_option(&["--no-dedup-dirs"], StoreFalse,
+                        "Walk directories again through every path reaching them");
+        ap.refer(&mut options.sparse_checkout)
+            .add_option(&["--sparse"], StoreTrue,
+                        "When walking a git sparse checkout, skip the files its patterns exclude \
+                         even if they're on disk")
+            .add_option(&["--no-sparse"], StoreFalse,
+                        "Walk every file on disk (default)");
+        ap.refer(&mut options.path_style)
+            .add_option(&["--paths"], Store,
+                        "How to show file paths: relative, absolute or as-given (default)");
+        ap.refer(&mut options.group_by)
+            .add_option(&["--group-by"], Store,
+                        "With check, summarize problems per rule, file or dir instead of listing \
+                         them (none, the default)");
+        ap.refer(&mut options.owners)
+            .add_option(&["--owners"], StoreTrue,
+                        "With check, show who owns the files with problems according to CODEOWNERS");
+        ap.refer(&mut options.format)
+            .add_option(&["--format"], Store,
+                        "Write the properties of the targets, or the problems check and lint \
+                         found, as text (default) or json, which holds the error of each target \
+                         that failed, or json-map, an object of the properties keyed by target. \
+                         Problems can also be written as sarif, junit or rdjson");
+        ap.refer(&mut options.dump_config)
+            .add_option(&["--dump-config"], StoreTrue,
+                        "Print the effective tool settings in the format of editorconfig-tool.ini \
+                         and exit");
+        ap.refer(&mut options.timings)
+            .add_option(&["--timings"], StoreTrue,
+                        "Report the slowest targets and config files on stderr");
+        ap.refer(&mut options.jobs)
+            .add_option(&["-j", "--jobs"], Store,
+                        "Look up this many targets at once when resolving or auditing, 0 for one \
+                         per CPU (default). The output keeps the order of the targets");
+        ap.refer(&mut options.check_environment)
+            .add_option(&["--environment"], StoreTrue,
+                        "With check, warn about git settings changing line endings at checkout");
+        ap.refer(&mut options.check_redundant)
+            .add_option(&["--redundant"], StoreTrue,
+                        "With check, find properties restating the value of a parent config file");
+        ap.refer(&mut options.check_comments)
+            .add_option(&["--comments"], StoreTrue,
+                        "With check, find comments after values, which EditorConfig 0.15.0 and \
+                         later read as part of the value");
+        ap.refer(&mut options.fix)
+            .add_option(&["--fix"], StoreTrue,
+                        "With check --redundant, delete the redundant properties, with lint, \
+                         simplify the patterns of sections");
+        ap.refer(&mut options.verify_idempotent)
+            .add_option(&["--verify-idempotent"], StoreTrue,
+                        "With --fix, apply the fixes twice to copies of the config files instead \
+                         of changing them, and fail if the second pass changes anything");
+        ap.refer(&mut options.lint_all)
+            .add_option(&["--all"], StoreTrue,
+                        "With lint, lint every conf file below the directory given instead of \
+                         the ones the targets' lookups read");
+        ap.refer(&mut options.include_generated)
+            .add_option(&["--include-generated"], StoreTrue,
+                        "With check, don't skip files marked as generated");
+        ap.refer(&mut options.export_for)
+            .add_option(&["--for"], Store, "With export, the file whose config files to flatten");
+        ap.refer(&mut options.out)
+            .add_option(&["--out"], Store,
+                        "With export or manifest, write there instead of stdout");
+        ap.refer(&mut options.interactive)
+            .add_option(&["--interactive"], StoreTrue,
+                        "With init, ask questions to write the new config file");
+        ap.refer(&mut options.update)
+            .add_option(&["--update"], StoreTrue,
+                        "With init, generate the settings written by init again");
+        ap.refer(&mut options.show_version)
+            .add_option(&["-v", "--version"], StoreTrue, "Show version");
+        ap.refer(&mut options.explain)
+            .add_option(&["--explain"], Store,
+                        "Print the documentation of a diagnostic code, like EC101, and exit");
+        ap.refer(&mut options.targets)
+            .add_argument("arguments", List, "Files to check");
+        ap.parse(args, stdout, stderr)?;
+    }
+    Ok(options)
+}
+
+/// Runs the tool like the `editorconfig` binary does and returns its exit code.
+pub fn run(args: Vec<String>) -> i32 {
+    let cwd = env::current_dir().unwrap_or_default();
+    let stdout = io::stdout();
+    let stderr = io::stderr();
+    run_in(args, &cwd, &mut stdout.lock(), &mut stderr.lock())
+}
+
+/// Runs the tool like the `editorconfig-core` binary does, see `run_core_in`.
+pub fn run_core(args: Vec<String>) -> i32 {
+    let cwd = env::current_dir().unwrap_or_default();
+    let stdout = io::stdout();
+    let stderr = io::stderr();
+    run_core_in(args, &cwd, &mut stdout.lock(), &mut stderr.lock())
+}
+
+/// Runs the tool with only the flags (`-f`, `-b`, `-v`) and output of the reference
+/// EditorConfig core command line, for scripts written against it. There are no
+/// subcommands and `editorconfig-tool.ini` isn't read.
+pub fn run_core_in(args: Vec<String>,
+                   cwd: &Path,
+                   stdout: &mut dyn Write,
+                   stderr: &mut dyn Write)
+                   -> i32 {
+    let mut options = with_env_overrides(Options::default(), &EnvOverrides::from_env());
+    {
+        let mut ap = ArgumentParser::new();
+        ap.set_description("Parse .editorconfig files.");
+        ap.refer(&mut options.conf_filename)
+            .add_option(&["-f"], Store, "Conf filename");
+        ap.refer(&mut options.version)
+            .add_option(&["-b"], Store, "editorconfig version");
+        ap.refer(&mut options.show_version)
+            .add_option(&["-v", "--version"], StoreTrue, "Show version");
+        ap.refer(&mut options.targets)
+            .add_argument("FILEPATH", List, "Files to find the configuration for");
+        if let Err(code) = ap.parse(args, stdout, stderr) {
+            return code;
+        }
+    }
+    execute(&options, cwd, stdout, stderr)
+}
+
+/// Runs the tool as if started in `cwd`, writing to the given streams.
+pub fn run_in(args: Vec<String>,
+              cwd: &Path,
+              stdout: &mut dyn Write,
+              stderr: &mut dyn Write)
+              -> i32 {
+    let tool_config_path = ToolConfig::find(cwd);
+    let tool_config = match tool_config_path {
+        Some(ref path) => {
+            match ToolConfig::load(path) {
+                Ok(tool_config) => tool_config,
+                Err(err) => {
+                    let _ = writeln!(stderr, "editorconfig: {}", err);
+                    return 1;
+                }
+            }
+        }
+        None => ToolConfig::default(),
+    };
+    // Settings from the tool config file are only defaults, environment variables and then
+    // flags override them
+    let defaults = Options {
+        tool_config_path,
+        env_vars_read: ENV_OVERRIDE_VARS.iter().map(|var| var.to_string()).collect(),
+        ..Options::new(&tool_config)
+    };
+    let defaults = with_env_overrides(defaults, &EnvOverrides::from_env());
+    match parse_args(args, defaults, stdout, stderr) {
+        Ok(options) => execute(&options, cwd, stdout, stderr),
+        Err(code) => code,
+    }
+}
+
+/// `options` with what the environment variables in `overrides` set.
+fn with_env_overrides(mut options: Options, overrides: &EnvOverrides) -> Options {
+    if let Some(ref name) = overrides.conf_filename {
+        options.conf_filename = name.clone();
+    }
+    if let Some(ref path) = overrides.global_config {
+        options.global_config = path.to_string_lossy().into_owned();
+    }
+    options
+}
+
+/// Does what `options` ask for and returns the exit code.
+pub fn execute(options: &Options, cwd: &Path, stdout: &mut dyn Write, stderr: &mut dyn Write) -> i32 {
+    for version in [&options.version, &options.min_version] {
+        if version.is_empty() {
+            continue;
+        }
+        if let Err(err) = version.parse::<SpecVersion>() {
+            let _ = writeln!(stderr, "editorconfig: {}", err);
+            return 2;
+        }
+    }
+    if options.format.is_diagnostics_only() && !options.check && !options.lint {
+        let _ = writeln!(stderr, "editorconfig: --format {} needs check or lint", options.format);
+        return 2;
+    }
+    if options.format == OutputFormat::JsonMap && (options.check || options.lint) {
+        let _ = writeln!(stderr, "editorconfig: --format json-map only applies to lookups");
+        return 2;
+    }
+    if options.verify_idempotent && !options.fix {
+        let _ = writeln!(stderr, "editorconfig: --verify-idempotent needs --fix");
+        return 2;
+    }
+    if options.timings && (options.dump_config || !options.explain.is_empty() || options.init) {
+        let _ = writeln!(stderr, "editorconfig: --timings only applies to modes looking files up");
+        return 2;
+    }
+    // Filled by the lookups of every mode, reported once it's done
+    let timings = Arc::new(Timings::new());
+    let result = if options.show_version {
+        writeln!(stdout, "EditorConfig Rust Core Version {}", env!("CARGO_PKG_VERSION")).map(|_| 0)
+    } else if !options.explain.is_empty() {
+        explain_code(&options.explain, stdout, stderr)
+    } else if options.dump_config {
+        dump_config(options, stdout).map(|_| 0)
+    } else if options.doctor {
+        doctor_report(options, &timings, cwd, stdout, stderr)
+    } else if options.export {
+        export_config(options, &timings, cwd, stdout, stderr)
+    } else if options.manifest {
+        write_manifest(options, &timings, cwd, stdout, stderr)
+    } else if options.init {
+        let stdin = io::stdin();
+        init_config(options, cwd, &mut stdin.lock(), stdout, stderr)
+    } else if options.check {
+        check_targets(options, &timings, cwd, stdout, stderr)
+    } else if options.lint {
+        lint_configs(options, &timings, cwd, stdout, stderr)
+    } else if options.audit {
+        audit_targets(options, &timings, cwd, stdout, stderr)
+    } else {
+        resolve_targets(options, &timings, cwd, stdout, stderr)
+    };
+    if options.timings && result.is_ok() {
+        let _ = write_timings(&timings, options.path_style, cwd, stderr);
+    }
+    result.unwrap_or_else(|err| {
+        let _ = writeln!(stderr, "editorconfig: {}", err);
+        1
+    })
+}
+
+/// Writes the effective tool settings in the format of `editorconfig-tool.ini`, like
+/// `--dump-config`.
+pub fn dump_config(options: &Options, out: &mut dyn Write) -> io::Result<()> {
+    writeln!(out, "[tool]")?;
+    writeln!(out, "version = {}", quoted(env!("CARGO_PKG_VERSION")))?;
+    writeln!(out)?;
+    writeln!(out, "[lookup]")?;
+    writeln!(out, "conf_filename = {}", quoted(&options.conf_filename))?;
+    writeln!(out,
+             "conf_overrides = [{}]",
+             options.conf_overrides.iter().map(|name| quoted(name)).collect::<Vec<_>>().join(", "))?;
+    writeln!(out, "spec_version = {}", quoted(&options.version))?;
+    writeln!(out, "min_spec_version = {}", quoted(&options.min_version))?;
+    writeln!(out, "strict_io = {}", options.strict_io)?;
+    writeln!(out, "trust_root = {}", quoted(&options.trust_root))?;
+    writeln!(out, "stop_dir = {}", quoted(&options.stop_dir))?;
+    writeln!(out, "global_config = {}", quoted(&options.global_config))?;
+    writeln!(out, "user_config = {}", options.user_config)?;
+    writeln!(out, "trust_same_owner = {}", options.trust_same_owner)?;
+    writeln!(out, "normalize_unicode = {}", options.normalize_unicode)?;
+    writeln!(out, "lenient_values = {}", options.lenient_values)?;
+    writeln!(out, "repair_malformed = {}", options.repair_malformed)?;
+    writeln!(out, "require_config = {}", options.require_config)?;
+    writeln!(out, "conf_any_case = {}", options.conf_any_case)?;
+    writeln!(out, "one_file_system = {}", options.one_file_system)?;
+    writeln!(out)?;
+    writeln!(out, "[walk]")?;
+    writeln!(out, "follow_symlinks = {}", options.follow_symlinks)?;
+    writeln!(out, "dedup_visited = {}", options.dedup_visited)?;
+    writeln!(out, "sparse_checkout = {}", options.sparse_checkout)?;
+    writeln!(out)?;
+    writeln!(out, "[output]")?;
+    writeln!(out, "paths = {}", quoted(&options.path_style.to_string()))?;
+    writeln!(out, "group_by = {}", quoted(&options.group_by.to_string()))?;
+    writeln!(out, "owners = {}", options.owners)?;
+    writeln!(out, "format = {}", quoted(&options.format.to_string()))?;
+    writeln!(out)?;
+    writeln!(out, "[classify]")?;
+    for &(class, ref patterns) in &options.classify {
+        let patterns: Vec<_> = patterns.iter().map(|pattern| quoted(pattern)).collect();
+        writeln!(out, "{} = [{}]", class, patterns.join(", "))?;
+    }
+    writeln!(out)?;
+    // The limits the lookups use, which only the library can change
+    let lookup = options.lookup_options();
+    writeln!(out, "[limits]")?;
+    writeln!(out, "max_section_name_length = {}", lookup.max_section_name_length)?;
+    writeln!(out, "max_key_length = {}", lookup.max_key_length)?;
+    writeln!(out, "max_value_length = {}", lookup.max_value_length)?;
+    writeln!(out)?;
+    writeln!(out, "[rules]")?;
+    let codes: Vec<_> = RULES.iter().map(|rule| quoted(rule.code)).collect();
+    writeln!(out, "enabled = [{}]", codes.join(", "))?;
+    writeln!(out)?;
+    writeln!(out, "[features]")?;
+    let features: Vec<_> = active_features().into_iter().map(quoted).collect();
+    writeln!(out, "compiled_in = [{}]", features.join(", "))
+}
+
+/// Resolves every target, going on with the next ones when one fails, and returns 1 if any
+/// failed or, with `--fail-on-missing-config`, got no config file.
+fn resolve_targets(options: &Options,
+                   timings: &Arc<Timings>,
+                   cwd: &Path,
+                   stdout: &mut dyn Write,
+                   stderr: &mut dyn Write)
+                   -> io::Result<i32> {
+    let lookup = options.timed_lookup_options(timings);
+    let path_style = options.path_style;
+    let json = options.format == OutputFormat::Json;
+    // `--format json-map`: the properties keyed by target, written at the end
+    let mut map = if options.format == OutputFormat::JsonMap { Some(vec![]) } else { None };
+    let multiple_targets = options.targets.len() > 1;
+    let warnings = WarningAggregator::new();
+    // Targets no config file applies to, reported together at the end
+    let mut uncovered = vec![];
+    let mut failed = false;
+
+    let lookups = map_ordered(&options.targets, options.jobs, |t| {
+        let mut target_warnings = vec![];
+        let started = Instant::now();
+        let result = get_config_with(&cwd.join(t), &lookup, &mut target_warnings);
+        timings.record(Path::new(t), Phase::Resolve, started.elapsed());
+        (result, target_warnings)
+    });
+
+    if json {
+        write!(stdout, "[")?;
+    }
+    for (i, (t, (result, mut target_warnings))) in options.targets.iter().zip(lookups).enumerate() {
+        let shown = path_style.display(Path::new(t), cwd);
+        if json {
+            let separator = if i == 0 { "" } else { "," };
+            write!(stdout, "{}\n  {{\"path\": {}, ", separator, json_string(&shown))?;
+        } else if multiple_targets && map.is_none() {
+            writeln!(stdout, "[{}]", shown)?;
+        }
+
+        let result = match result {
+            Err(ref err) if !options.strict_io && is_permission_denied(err) => {
+                target_warnings.push(Warning::new(t.clone(),
+                                                  WarningKind::Unreadable,
+                                                  format!("skipped, {}", err)));
+                Ok(Default::default())
+            }
+            result => result,
+        };
+        match result {
+            Ok(res) => {
+                if json {
+                    let properties: Vec<_> = res.iter()
+                        .map(|(k, v)| format!("{}: {}", json_string(k), json_string(v)))
+                        .collect();
+                    write!(stdout, "\"properties\": {{{}}}}}", properties.join(", "))?;
+                } else if let Some(ref mut map) = map {
+                    let properties: Vec<_> = res.iter()
+                        .map(|(k, v)| format!("{}: {}", json_string(k), json_string(v)))
+                        .collect();
+                    map.push((shown.to_string(), format!("{{{}}}", properties.join(", "))));
+                } else {
+                    for (k, v) in res.iter() {
+                        writeln!(stdout, "{}={}", *k, *v)?;
+                    }
+                }
+            }
+            Err(err) => {
+                if json {
+                    write!(stdout,
+                           "\"error\": {{\"kind\": {}, \"message\": {}}}}}",
+                           json_string(error_kind(&err)),
+                           json_string(&err.to_string()))?;
+                }
+                match err {
+                    Error::MissingConfig(_) => {
+                        if let Some(ref mut map) = map {
+                            map.push((shown.to_string(), "{}".to_string()));
+                        }
+                        uncovered.push(t)
+                    }
+                    err => {
+                        writeln!(stderr, "editorconfig: {}: {}", shown, err)?;
+                        failed = true;
+                    }
+                }
+            }
+        }
+        for warning in target_warnings {
+            warnings.add(Path::new(t), warning);
+        }
+    }
+    if json {
+        writeln!(stdout, "{}]", if options.targets.is_empty() { "" } else { "\n" })?;
+    }
+    if let Some(mut map) = map {
+        // A target given twice is written once, failed targets only show on stderr
+        let mut seen = HashSet::new();
+        map.retain(|(path, _)| seen.insert(path.clone()));
+        let entries: Vec<_> = map.iter()
+            .map(|(path, properties)| format!("{}: {}", json_string(path), properties))
+            .collect();
+        if entries.is_empty() {
+            writeln!(stdout, "{{}}")?;
+        } else {
+            writeln!(stdout, "{{\n  {}\n}}", entries.join(",\n  "))?;
+        }
+    }
+    write_warnings(warnings, path_style, cwd, stderr)?;
+    // One line per path, for scripts
+    for t in &uncovered {
+        writeln!(stderr, "editorconfig: uncovered: {}", path_style.display(Path::new(t), cwd))?;
+    }
+    Ok(if failed || !uncovered.is_empty() { 1 } else { 0 })
+}
+
+/// Names the variant of `err`, for the `kind` of errors in JSON output.
+fn error_kind(err: &Error) -> &'static str {
+    match *err {
+        Error::Io(_) => "io",
+        Error::Parse { .. } => "parse",
+        Error::InvalidGlob { .. } => "invalid-glob",
+        Error::PathPrefix(_) => "path-prefix",
+        Error::UnmappedUri(_) => "unmapped-uri",
+        Error::MissingConfig(_) => "missing-config",
+        Error::InvalidSetting { .. } => "invalid-setting",
+    }
+}
+
+/// Prints the documentation of the diagnostic `code`, like `--explain`.
+fn explain_code(code: &str, stdout: &mut dyn Write, stderr: &mut dyn Write) -> io::Result<i32> {
+    match explain(code) {
+        Some(rule) => {
+            writeln!(stdout, "{} ({}), {} severity\n", rule.code, rule.name, rule.severity)?;
+            write!(stdout, "{}", rule.explanation)?;
+            Ok(0)
+        }
+        None => {
+            let codes: Vec<_> = RULES.iter().map(|rule| rule.code).collect();
+            writeln!(stderr, "editorconfig: unknown diagnostic code {}, known codes are {}",
+                     code,
+                     codes.join(", "))?;
+            Ok(1)
+        }
+    }
+}
+
+/// Writes the health report of the target directory, most severe findings first, like `doctor`.
+fn doctor_report(options: &Options,
+                 timings: &Arc<Timings>,
+                 cwd: &Path,
+                 stdout: &mut dyn Write,
+                 stderr: &mut dyn Write)
+                 -> io::Result<i32> {
+    let path_style = options.path_style;
+    let root = cwd.join(options.targets.first().map(String::as_str).unwrap_or("."));
+    let report = match diagnose(&root, &options.timed_lookup_options(timings), &options.walk_options()) {
+        Ok(report) => report,
+        Err(err) => {
+            writeln!(stderr, "editorconfig: {}: {}", path_style.display(&root, cwd), err)?;
+            return Ok(1);
+        }
+    };
+    writeln!(stdout,
+             "Health score: {}/100 ({} files, {} config files)",
+             report.score(),
+             report.files,
+             report.config_files.len())?;
+    for (i, finding) in report.findings.iter().enumerate() {
+        writeln!(stdout,
+                 "{}. [{}] {}: {}: {}",
+                 i + 1,
+                 finding.severity,
+                 finding.category,
+                 path_style.display(&finding.file, cwd),
+                 finding.message)?;
+        writeln!(stdout, "   {}", finding.recommendation)?;
+    }
+    Ok(0)
+}
+
+/// Writes one config file equivalent to the ones applying to `--for`, like `export`.
+fn export_config(options: &Options,
+                 timings: &Arc<Timings>,
+                 cwd: &Path,
+                 stdout: &mut dyn Write,
+                 stderr: &mut dyn Write)
+                 -> io::Result<i32> {
+    if options.export_for.is_empty() {
+        writeln!(stderr, "editorconfig: export needs --for")?;
+        return Ok(2);
+    }
+    let path_style = options.path_style;
+    let target = cwd.join(&options.export_for);
+    let mut warnings = vec![];
+    let result = freeze_config(&target, &options.timed_lookup_options(timings), &mut warnings);
+    let aggregator = WarningAggregator::new();
+    for warning in warnings {
+        aggregator.add(&target, warning);
+    }
+    write_warnings(aggregator, path_style, cwd, stderr)?;
+    let frozen = match result {
+        Ok(frozen) => frozen,
+        Err(err) => {
+            writeln!(stderr, "editorconfig: {}: {}", path_style.display(&target, cwd), err)?;
+            return Ok(1);
+        }
+    };
+    write_output(options, cwd, &frozen, stdout)?;
+    Ok(0)
+}
+
+/// Writes the fingerprint of every file below the target directory, like `manifest`, returning
+/// 1 if the properties of any couldn't be resolved.
+fn write_manifest(options: &Options,
+                  timings: &Arc<Timings>,
+                  cwd: &Path,
+                  stdout: &mut dyn Write,
+                  stderr: &mut dyn Write)
+                  -> io::Result<i32> {
+    let path_style = options.path_style;
+    let root = cwd.join(options.targets.first().map(String::as_str).unwrap_or("."));
+    let mut warnings = vec![];
+    let result = Manifest::build(&root,
+                                 &options.timed_lookup_options(timings),
+                                 &options.walk_options(),
+                                 &mut warnings);
+    // Manifest::build doesn't say which files the warnings were noticed for
+    let aggregator = WarningAggregator::new();
+    for warning in warnings {
+        aggregator.add(&root, warning);
+    }
+    write_warnings(aggregator, path_style, cwd, stderr)?;
+    match result {
+        Ok(manifest) => {
+            for (path, err) in &manifest.errors {
+                writeln!(stderr, "editorconfig: {}: {}", path_style.display(&root.join(path), cwd), err)?;
+            }
+            write_output(options, cwd, &manifest.to_json(), stdout)?;
+            Ok(if manifest.errors.is_empty() { 0 } else { 1 })
+        }
+        Err(err) => {
+            writeln!(stderr, "editorconfig: {}: {}", path_style.display(&root, cwd), err)?;
+            Ok(1)
+        }
+    }
+}
+
+/// Writes `contents` to `--out`, or to `stdout` without it.
+fn write_output(options: &Options,
+                cwd: &Path,
+                contents: &str,
+                stdout: &mut dyn Write)
+                -> io::Result<()> {
+    if options.out.is_empty() {
+        return write!(stdout, "{}", contents);
+    }
+    let out = cwd.join(&options.out);
+    File::create(&out)?.write_all(contents.as_bytes())?;
+    writeln!(stdout, "Wrote {}", options.path_style.display(&out, cwd))
+}
+
+/// Writes a new config file in `cwd`, like `init`, asking questions on `stdout` and
+/// reading the answers from `stdin`. With `--update`, generates the settings of an
+/// existing config file again instead.
+pub fn init_config(options: &Options,
+                   cwd: &Path,
+                   stdin: &mut dyn BufRead,
+                   stdout: &mut dyn Write,
+                   stderr: &mut dyn Write)
+                   -> io::Result<i32> {
+    let path = cwd.join(&options.conf_filename);
+    let shown = options.path_style.display(&path, cwd);
+    let vars = TemplateVars::for_dir(cwd);
+    if options.update {
+        let existing = match fs::read_to_string(&path) {
+            Ok(existing) => existing,
+            Err(e) => {
+                writeln!(stderr, "editorconfig: can't read {}: {}", shown, e)?;
+                return Ok(1);
+            }
+        };
+        let updated = match update_config(&existing, &vars) {
+            Ok(updated) => updated,
+            Err(e) => {
+                writeln!(stderr, "editorconfig: can't update {}: {}", shown, e)?;
+                return Ok(1);
+            }
+        };
+        if updated == existing {
+            writeln!(stdout, "{} is up to date", shown)?;
+        } else {
+            File::create(&path)?.write_all(updated.as_bytes())?;
+            writeln!(stdout, "Updated {}", shown)?;
+        }
+        return Ok(0);
+    }
+    if !options.interactive {
+        writeln!(stderr, "editorconfig: init needs --interactive or --update")?;
+        return Ok(2);
+    }
+    if path.exists() {
+        writeln!(stderr, "editorconfig: {} already exists", shown)?;
+        return Ok(1);
+    }
+    let answers = ask_answers(stdin, stdout, &detect_languages(cwd))?;
+    File::create(&path)?.write_all(render_config(&answers, &vars).as_bytes())?;
+    writeln!(stdout, "Wrote {}", shown)?;
+    Ok(0)
+}
+
+fn check_targets(options: &Options,
+                 timings: &Arc<Timings>,
+                 cwd: &Path,
+                 stdout: &mut dyn Write,
+                 stderr: &mut dyn Write)
+                 -> io::Result<i32> {
+    if !options.check_environment && !options.check_redundant && !options.check_comments {
+        writeln!(stderr, "editorconfig: check needs --environment, --redundant or --comments")?;
+        return Ok(2);
+    }
+    let path_style = options.path_style;
+    let mut lookup = options.timed_lookup_options(timings);
+    // The config files to check are the ones the lookups of the targets read
+    let audit = Arc::new(AuditLog::new());
+    lookup.audit = Some(audit.clone());
+    let mut classifier = Classifier::new(cwd);
+    for &(class, ref patterns) in &options.classify {
+        for pattern in patterns {
+            if let Err(err) = classifier.add_pattern(class, pattern) {
+                writeln!(stderr, "editorconfig: classify: {}", err)?;
+                return Ok(2);
+            }
+        }
+    }
+    let mut diagnostics = vec![];
+    let mut skipped = 0;
+    // Targets and config files failing are reported, the others still checked
+    let mut failed = false;
+    // Read once per directory, as every read runs git
+    let mut git_settings: HashMap<PathBuf, Option<GitEolSettings>> = HashMap::new();
+    for t in &options.targets {
+        let target = cwd.join(t);
+        // Generated, vendored and binary files follow the conventions of whatever made them
+        let class = classifier.classify(Path::new(t));
+        let checked = class.is_authored() ||
+                      (class == FileClass::Generated && options.include_generated);
+        if !checked {
+            skipped += 1;
+            continue;
+        }
+        let started = Instant::now();
+        let result = get_config_with(&target, &lookup, &mut vec![]);
+        timings.record(Path::new(t), Phase::Resolve, started.elapsed());
+        let properties = match result {
+            Ok(properties) => properties,
+            Err(err) => {
+                writeln!(stderr, "editorconfig: {}: {}", path_style.display(Path::new(t), cwd), err)?;
+                failed = true;
+                continue;
+            }
+        };
+        if !options.check_environment {
+            continue;
+        }
+        let end_of_line = match properties.get("end_of_line") {
+            Some(end_of_line) => end_of_line,
+            None => continue,
+        };
+        let dir = target.parent().filter(|dir| dir.is_dir()).unwrap_or(cwd);
+        if !git_settings.contains_key(dir) {
+            let git = GitEolSettings::read(dir);
+            if git.is_none() && !git_settings.values().any(Option::is_none) {
+                writeln!(stderr, "editorconfig: warning: can't run git, skipping environment checks")?;
+            }
+            git_settings.insert(dir.to_path_buf(), git);
+        }
+        // Other checks go on without git
+        let git = match git_settings[dir] {
+            Some(ref git) => git,
+            None => continue,
+        };
+        if let Some((checkout_eol, setting)) = git.conflict_with(end_of_line) {
+            diagnostics.push(Diagnostic {
+                file: PathBuf::from(t),
+                rule: "git-line-endings",
+                message_id: "git-line-endings.conflict",
+                args: vec![("end_of_line", end_of_line.to_string()),
+                           ("checkout_eol", checkout_eol.to_string()),
+                           ("setting", setting.to_string())],
+            });
+        }
+    }
+    if skipped > 0 {
+        writeln!(stderr, "editorconfig: skipped {} generated, vendored or binary files", skipped)?;
+    }
+    if options.check_redundant && options.verify_idempotent {
+        let conf_files = audit.files_read();
+        return verify_fixes(Fix::RemoveRedundant, &conf_files, &lookup, path_style, cwd, stdout,
+                            stderr);
+    }
+    if options.check_redundant {
+        for conf_file in audit.files_read() {
+            let shown = path_style.display(&conf_file, cwd);
+            let redundant = match redundant_settings(&conf_file, &lookup) {
+                Ok(redundant) => redundant,
+                Err(err) => {
+                    writeln!(stderr, "editorconfig: {}: {}", shown, err)?;
+                    failed = true;
+                    continue;
+                }
+            };
+            if redundant.is_empty() {
+                continue;
+            }
+            let contents = fs::read_to_string(&conf_file)?;
+            if options.fix {
+                fs::write(&conf_file, remove_redundant(&contents, &redundant))?;
+                writeln!(stdout, "Removed {} redundant settings from {}", redundant.len(), shown)?;
+                continue;
+            }
+            for setting in redundant {
+                let line = contents[..setting.span.start].matches('\n').count() + 1;
+                diagnostics.push(Diagnostic {
+                    file: conf_file.clone(),
+                    rule: "redundant-setting",
+                    message_id: "redundant-setting.inherited",
+                    args: vec![("section", setting.section),
+                               ("key", setting.key),
+                               ("value", setting.value),
+                               ("line", line.to_string()),
+                               ("inherited_from",
+                                path_style.display(&setting.inherited_from, cwd).to_string())],
+                });
+            }
+        }
+    }
+    if options.check_comments {
+        for conf_file in audit.files_read() {
+            let contents = fs::read_to_string(&conf_file)?;
+            let comments = match find_inline_comments(&contents) {
+                Ok(comments) => comments,
+                Err(err) => {
+                    let shown = path_style.display(&conf_file, cwd);
+                    writeln!(stderr, "editorconfig: {}: {}", shown, err)?;
+                    failed = true;
+                    continue;
+                }
+            };
+            for comment in comments {
+                diagnostics.push(Diagnostic {
+                    file: conf_file.clone(),
+                    rule: "inline-comment",
+                    message_id: "inline-comment.part-of-value",
+                    args: vec![("key", comment.key),
+                               ("value", comment.stripped),
+                               ("line", comment.line.to_string()),
+                               ("kept", comment.kept)],
+                });
+            }
+        }
+    }
+    let code = report_diagnostics(options, &diagnostics, cwd, stdout, stderr)?;
+    Ok(if failed { code.max(1) } else { code })
+}
+
+/// Lints the config files the lookups of the targets read or, with `--all`, every config
+/// file below the directory given, like `lint`.
+fn lint_configs(options: &Options,
+                timings: &Arc<Timings>,
+                cwd: &Path,
+                stdout: &mut dyn Write,
+                stderr: &mut dyn Write)
+                -> io::Result<i32> {
+    let path_style = options.path_style;
+    let mut lookup = options.timed_lookup_options(timings);
+    let conf_files = if options.lint_all {
+        let root = cwd.join(options.targets.first().map(String::as_str).unwrap_or("."));
+        let mut warnings = vec![];
+        let result = find_config_files(&root, &lookup, &options.walk_options(), &mut warnings);
+        let aggregator = WarningAggregator::new();
+        for warning in warnings {
+            aggregator.add(&root, warning);
+        }
+        write_warnings(aggregator, path_style, cwd, stderr)?;
+        match result {
+            Ok(conf_files) => conf_files,
+            Err(err) => {
+                writeln!(stderr, "editorconfig: {}: {}", path_style.display(&root, cwd), err)?;
+                return Ok(1);
+            }
+        }
+    } else {
+        let audit = Arc::new(AuditLog::new());
+        lookup.audit = Some(audit.clone());
+        for t in &options.targets {
+            // Config files that can't be parsed are read all the same, and reported below
+            let started = Instant::now();
+            let _ = get_config_with(&cwd.join(t), &lookup, &mut vec![]);
+            timings.record(Path::new(t), Phase::Resolve, started.elapsed());
+        }
+        audit.files_read()
+    };
+    if options.verify_idempotent {
+        return verify_fixes(Fix::Simplify, &conf_files, &lookup, path_style, cwd, stdout, stderr);
+    }
+    if options.fix {
+        for conf_file in &conf_files {
+            let contents = match fs::read_to_string(conf_file) {
+                Ok(contents) => contents,
+                // Reported as broken below
+                Err(_) => continue,
+            };
+            if let Ok((simplified, simplifications)) = simplify_config(&contents) {
+                if !simplifications.is_empty() {
+                    fs::write(conf_file, simplified)?;
+                    writeln!(stdout,
+                             "Simplified {} sections of {}",
+                             simplifications.len(),
+                             path_style.display(conf_file, cwd))?;
+                }
+            }
+        }
+    }
+    let mut diagnostics = lint_config_files(&conf_files, &lookup);
+    for diagnostic in &mut diagnostics {
+        for &mut (name, ref mut value) in &mut diagnostic.args {
+            if name == "inherited_from" {
+                *value = path_style.display(Path::new(value.as_str()), cwd).to_string();
+            }
+        }
+    }
+    writeln!(stderr, "editorconfig: linted {} config files", conf_files.len())?;
+    report_diagnostics(options, &diagnostics, cwd, stdout, stderr)
+}
+
+/// Applies `fix` twice to copies of `conf_files`, like `--verify-idempotent`, returning 1 if
+/// the second pass changed any of them.
+fn verify_fixes(fix: Fix,
+                conf_files: &[PathBuf],
+                lookup: &LookupOptions,
+                path_style: PathStyle,
+                cwd: &Path,
+                stdout: &mut dyn Write,
+                stderr: &mut dyn Write)
+                -> io::Result<i32> {
+    let unsettled = match verify_idempotent(fix, conf_files, lookup) {
+        Ok(unsettled) => unsettled,
+        Err(err) => {
+            writeln!(stderr, "editorconfig: {}", err)?;
+            return Ok(1);
+        }
+    };
+    for file in &unsettled {
+        writeln!(stdout,
+                 "{}: fixing it again changes it, from:\n{}to:\n{}",
+                 path_style.display(&file.conf_file, cwd),
+                 file.once,
+                 file.twice)?;
+    }
+    writeln!(stderr,
+             "editorconfig: fixed {} config files twice, {} changed again",
+             conf_files.len(),
+             unsettled.len())?;
+    Ok(if unsettled.is_empty() { 0 } else { 1 })
+}
+
+/// Writes `diagnostics` as `check` and `lint` show them, returning 1 if there are any.
+fn report_diagnostics(options: &Options,
+                      diagnostics: &[Diagnostic],
+                      cwd: &Path,
+                      stdout: &mut dyn Write,
+                      stderr: &mut dyn Write)
+                      -> io::Result<i32> {
+    let owners = if options.owners {
+        match CodeOwners::find(cwd) {
+            Ok(Some(owners)) => Some(owners),
+            Ok(None) => {
+                writeln!(stderr, "editorconfig: warning: no CODEOWNERS file found")?;
+                None
+            }
+            Err(err) => {
+                writeln!(stderr, "editorconfig: CODEOWNERS: {}", err)?;
+                return Ok(1);
+            }
+        }
+    } else {
+        None
+    };
+    let report_options = ReportOptions::new(cwd).path_style(options.path_style);
+    let mut reporter: Box<dyn Reporter> = match options.format {
+        OutputFormat::Text => {
+            let reporter = TextReporter::new(stdout, report_options);
+            match owners {
+                Some(ref owners) => Box::new(reporter.owners(owners)),
+                None => Box::new(reporter),
+            }
+        }
+        // json-map is rejected for check and lint
+        OutputFormat::Json | OutputFormat::JsonMap => {
+            Box::new(JsonReporter::new(stdout, report_options))
+        }
+        OutputFormat::Sarif => Box::new(SarifReporter::new(stdout, report_options)),
+        OutputFormat::Junit => Box::new(JunitReporter::new(stdout, report_options)),
+        OutputFormat::Rdjson => Box::new(RdjsonReporter::new(stdout, report_options)),
+    };
+    write_report(&mut *reporter, diagnostics, options.group_by)?;
+    Ok(if diagnostics.is_empty() { 0 } else { 1 })
+}
+
+/// Lists everything that influenced the lookup of each target, like `audit`, going on with
+/// the next targets when one fails and returning 1 if any did.
+fn audit_targets(options: &Options,
+                 timings: &Arc<Timings>,
+                 cwd: &Path,
+                 stdout: &mut dyn Write,
+                 stderr: &mut dyn Write)
+                 -> io::Result<i32> {
+    let path_style = options.path_style;
+    let mut failed = false;
+    // Every target gets its own log, so the lookups can run at once
+    let lookups = map_ordered(&options.targets, options.jobs, |t| {
+        let mut lookup = options.timed_lookup_options(timings);
+        let audit = Arc::new(AuditLog::new());
+        lookup.audit = Some(audit.clone());
+        let mut warnings = vec![];
+        let started = Instant::now();
+        let result = get_config_with(&cwd.join(t), &lookup, &mut warnings);
+        timings.record(Path::new(t), Phase::Resolve, started.elapsed());
+        (result, audit, warnings)
+    });
+    for (t, (result, audit, warnings)) in options.targets.iter().zip(lookups) {
+        // What the lookup read until it failed is listed all the same
+        if let Err(err) = result {
+            writeln!(stderr, "editorconfig: {}: {}", path_style.display(Path::new(t), cwd), err)?;
+            failed = true;
+        }
+
+        writeln!(stdout, "[{}]", path_style.display(Path::new(t), cwd))?;
+        writeln!(stdout, "files read:")?;
+        if let Some(ref path) = options.tool_config_path {
+            writeln!(stdout, "  {} (tool config)", path_style.display(path, cwd))?;
+        }
+        for path in audit.files_read() {
+            writeln!(stdout, "  {}", path_style.display(&path, cwd))?;
+        }
+        writeln!(stdout, "files ignored:")?;
+        let ignored = warnings.iter().filter(|warning| {
+            warning.kind == WarningKind::Unreadable || warning.kind == WarningKind::Untrusted
+        });
+        for warning in ignored {
+            writeln!(stdout, "  {}: {}", path_style.display(&warning.file, cwd), warning.message)?;
+        }
+        writeln!(stdout, "stopped looking above:")?;
+        for (dir, reason) in audit.stops() {
+            writeln!(stdout, "  {}: {}", path_style.display(&dir, cwd), reason)?;
+        }
+        writeln!(stdout, "environment variables consulted:")?;
+        for var in options.env_vars_read.iter().chain(&audit.env_vars_read()) {
+            writeln!(stdout, "  {}", var)?;
+        }
+        writeln!(stdout, "active features:")?;
+        for feature in active_settings(options) {
+            writeln!(stdout, "  {}", feature)?;
+        }
+        writeln!(stdout, "compiled-in features:")?;
+        for feature in active_features() {
+            writeln!(stdout, "  {}", feature)?;
+        }
+    }
+    Ok(if failed { 1 } else { 0 })
+}
+
+/// Describes every setting that changes how lookups behave compared to the defaults.
+fn active_settings(options: &Options) -> Vec<String> {
+    let mut features = vec![];
+    if options.conf_filename != ".editorconfig" {
+        features.push(format!("config files named {}", options.conf_filename));
+    }
+    for name in &options.conf_overrides {
+        features.push(format!("overridden by files named {}", name));
+    }
+    if !options.version.is_empty() {
+        features.push(format!("emulating EditorConfig {}", options.version));
+    }
+    if options.strict_io {
+        features.push("strict io".to_string());
+    }
+    if !options.trust_root.is_empty() {
+        features.push(format!("trust root {}", options.trust_root));
+    }
+    if !options.stop_dir.is_empty() {
+        features.push(format!("stop directory {}", options.stop_dir));
+    }
+    if !options.global_config.is_empty() {
+        features.push(format!("global config {}", options.global_config));
+    }
+    if options.user_config {
+        features.push("user config".to_string());
+    }
+    if options.trust_same_owner {
+        features.push("trust same owner only".to_string());
+    }
+    if options.normalize_unicode {
+        features.push("unicode normalization".to_string());
+    }
+    if options.lenient_values {
+        features.push("lenient values".to_string());
+    }
+    if options.repair_malformed {
+        features.push("repair malformed configs".to_string());
+    }
+    if options.require_config {
+        features.push("config required".to_string());
+    }
+    if options.conf_any_case {
+        features.push("conf file names in any case".to_string());
+    }
+    if options.one_file_system {
+        features.push("one file system".to_string());
+    }
+    features
+}
+
+fn write_timings(timings: &Timings,
+                 path_style: PathStyle,
+                 cwd: &Path,
+                 stderr: &mut dyn Write)
+                 -> io::Result<()> {
+    let sections = [(Phase::Resolve, "slowest targets"),
+                    (Phase::Parse, "slowest config files to parse"),
+                    (Phase::Match, "slowest config files to match")];
+    for &(phase, title) in &sections {
+        let slowest = timings.slowest(phase, TIMINGS_REPORT_LENGTH);
+        if slowest.is_empty() {
+            continue;
+        }
+        writeln!(stderr, "editorconfig: timings: {}", title)?;
+        for entry in slowest {
+            write!(stderr,
+                   "  {:>10}  {}",
+                   format_duration(entry.total),
+                   path_style.display(&entry.path, cwd))?;
+            if entry.count > 1 {
+                write!(stderr, " ({} times)", entry.count)?;
+            }
+            writeln!(stderr)?;
+        }
+    }
+
+    let mut reported_patterns = false;
+    for entry in timings.slowest(Phase::Parse, usize::MAX) {
+        for cost in timings.expensive_patterns(&entry.path) {
+            if !reported_patterns {
+                writeln!(stderr, "editorconfig: timings: expensive patterns")?;
+                reported_patterns = true;
+            }
+            writeln!(stderr, "  {}: [{}]", path_style.display(&entry.path, cwd), cost.pattern)?;
+            for issue in cost.issues {
+                writeln!(stderr, "    {}; {}", issue.message, issue.suggestion)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}ms", duration.as_secs() as f64 * 1e3 + duration.subsec_nanos() as f64 / 1e6)
+}
+
+fn write_warnings(warnings: WarningAggregator,
+                  path_style: PathStyle,
+                  cwd: &Path,
+                  stderr: &mut dyn Write)
+                  -> io::Result<()> {
+    for (warning, count) in warnings.into_counts() {
+        write!(stderr,
+               "editorconfig: warning: {}: {}",
+               path_style.display(&warning.file, cwd),
+               warning.message)?;
+        if count > 1 {
+            write!(stderr, " (affects {} files)", count)?;
+        }
+        writeln!(stderr)?;
+    }
+    Ok(())
+}
+
+/// Quotes `s` as a string of `editorconfig-tool.ini`, which reads it back as is.
+fn quoted(s: &str) -> String {
+    let mut written = String::with_capacity(s.len() + 2);
+    written.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => written.push_str("\\\""),
+            '\\' => written.push_str("\\\\"),
+            '\n' => written.push_str("\\n"),
+            '\t' => written.push_str("\\t"),
+            '\r' => written.push_str("\\r"),
+            c if c.is_control() => written.push_str(&format!("\\x{:04x}", c as u32)),
+            c => written.push(c),
+        }
+    }
+    written.push('"');
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+
+    fn run_args(args: &[&str]) -> (i32, String, String) {
+        let mut argv = vec!["editorconfig".to_string()];
+        argv.extend(args.iter().map(|a| a.to_string()));
+        let cwd = Path::new(".").canonicalize().unwrap();
+        let mut stdout = vec![];
+        let mut stderr = vec![];
+        let code = run_in(argv, &cwd, &mut stdout, &mut stderr);
+        (code, String::from_utf8(stdout).unwrap(), String::from_utf8(stderr).unwrap())
+    }
+
+    fn fixture(path: &str) -> String {
+        Path::new(path).canonicalize().unwrap().to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn resolves_a_single_target() {
+        let (code, out, _) = run_args(&[&fixture("test_files/simple/file.txt")]);
+        assert_eq!(code, 0);
+        assert_eq!(out, "end_of_line=lf\ninsert_final_newline=true\n");
+    }
+
+    #[test]
+    fn reports_why_the_output_failed() {
+        struct Closed;
+        impl Write for Closed {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "pipe closed"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let argv = vec!["editorconfig".to_string(), fixture("test_files/simple/file.txt")];
+        let cwd = Path::new(".").canonicalize().unwrap();
+        let mut stderr = vec![];
+        assert_eq!(run_in(argv, &cwd, &mut Closed, &mut stderr), 1);
+        assert_eq!(String::from_utf8(stderr).unwrap(), "editorconfig: pipe closed\n");
+    }
+
+    #[test]
+    fn resolves_relative_targets_from_the_given_directory() {
+        let cwd = Path::new("test_files/simple").canonicalize().unwrap();
+        let run = |args: &[&str]| {
+            let mut argv = vec!["editorconfig".to_string()];
+            argv.extend(args.iter().map(|a| a.to_string()));
+            let (mut out, mut err) = (vec![], vec![]);
+            let code = run_in(argv, &cwd, &mut out, &mut err);
+            (code, String::from_utf8(out).unwrap())
+        };
+        assert_eq!(run(&["file.txt"]),
+                   (0, "end_of_line=lf\ninsert_final_newline=true\n".to_string()));
+        let (code, out) = run(&["audit", "file.txt"]);
+        assert_eq!(code, 0);
+        let read = format!("files read:\n  {}\n", cwd.join(".editorconfig").display());
+        assert!(out.contains(&read), "{}", out);
+    }
+
+    #[test]
+    fn prints_headers_for_multiple_targets() {
+        let a = fixture("test_files/simple/file.txt");
+        let b = fixture("test_files/multi_level/foo/bar/file.txt");
+        let (code, out, _) = run_args(&["--paths", "relative", &a, &b]);
+        assert_eq!(code, 0);
+        assert_eq!(out,
+                   "[test_files/simple/file.txt]\nend_of_line=lf\ninsert_final_newline=true\n\
+                    [test_files/multi_level/foo/bar/file.txt]\nend_of_line=lf\n\
+                    insert_final_newline=true\n");
+    }
+
+    #[test]
+    fn reports_every_invalid_section_of_a_config_file() {
+        let target = fixture("test_files/invalid_patterns") + "/file.txt";
+        let (code, _, err) = run_args(&["--paths", "relative", &target]);
+        assert_eq!(code, 0);
+        let warnings: Vec<_> = err.lines().filter(|line| line.contains("can't be translated")).collect();
+        assert_eq!(warnings.len(), 4, "{}", err);
+        assert!(warnings[0].contains("section [(]"), "{}", err);
+        assert!(warnings[3].contains("section [{1..3}(]"), "{}", err);
+        // A single target was resolved
+        assert!(!err.contains("affects"), "{}", err);
+    }
+
+    #[test]
+    fn keeps_the_order_of_the_targets_when_resolving_at_once() {
+        let targets: Vec<_> = ["test_files/simple/file.txt",
+                               "test_files/multi_level/foo/bar/file.txt",
+                               "test_files/lenient/file.txt",
+                               "test_files/malformed/file.md",
+                               "test_files/overrides/foo/file.txt"]
+            .iter()
+            .cycle()
+            .take(40)
+            .map(|path| fixture(path))
+            .collect();
+        let run = |jobs: &str| {
+            let mut args = vec!["--jobs", jobs, "--format", "json"];
+            args.extend(targets.iter().map(String::as_str));
+            run_args(&args)
+        };
+        let sequential = run("1");
+        assert!(sequential.1.contains("\"error\""));
+        assert_eq!(run("8"), sequential);
+        assert_eq!(run("0"), sequential);
+    }
+
+    #[test]
+    fn prints_version() {
+        let (code, out, _) = run_args(&["-v"]);
+        assert_eq!(code, 0);
+        assert_eq!(out, format!("EditorConfig Rust Core Version {}\n", env!("CARGO_PKG_VERSION")));
+    }
+
+    #[test]
+    fn lets_environment_variables_override_the_tool_config() {
+        let tool_config = ToolConfig {
+            conf_filename: Some(".ec".to_string()),
+            global_config: Some("/etc/editorconfig".to_string()),
+            ..ToolConfig::default()
+        };
+        let overrides = EnvOverrides {
+            conf_filename: Some(".editorconfig.try".to_string()),
+            global_config: None,
+        };
+        let options = with_env_overrides(Options::new(&tool_config), &overrides);
+        assert_eq!(options.conf_filename, ".editorconfig.try");
+        assert_eq!(options.lookup_options().global_config,
+                   Some(PathBuf::from("/etc/editorconfig")));
+        let options = parse_args(vec!["editorconfig".to_string(), "-f".to_string(),
+                                      ".ec".to_string()],
+                                 options,
+                                 &mut vec![],
+                                 &mut vec![])
+            .unwrap();
+        assert_eq!(options.conf_filename, ".ec");
+    }
+
+    #[test]
+    fn dumps_config() {
+        let stop_dir = "a \"b\"\t\\c";
+        let (code, out, _) = run_args(&["--dump-config", "-f", ".ec", "--strict-io", "--stop-dir",
+                                        stop_dir]);
+        assert_eq!(code, 0);
+        assert!(out.contains("conf_filename = \".ec\"\n"));
+        assert!(out.contains("strict_io = true\n"));
+        assert!(out.contains("[limits]\nmax_section_name_length = 4096\n"));
+        assert!(out.contains("[rules]\nenabled = [\"EC101\", "));
+        assert!(out.contains("[features]\ncompiled_in = ["));
+        // The dump reads back as a tool config file
+        assert_eq!(ToolConfig::parse(&out).unwrap().stop_dir.as_deref(), Some(stop_dir));
+    }
+
+    #[test]
+    fn reports_timings() {
+        let target = fixture("test_files/simple/file.txt");
+        let (code, _, err) = run_args(&["--timings", &target]);
+        assert_eq!(code, 0);
+        assert!(err.contains("editorconfig: timings: slowest targets\n"));
+        assert!(err.contains("editorconfig: timings: slowest config files to parse\n"));
+        assert!(err.contains(&fixture("test_files/simple/.editorconfig")));
+    }
+
+    #[test]
+    fn reports_timings_in_every_mode_looking_files_up() {
+        let target = fixture("test_files/simple/file.txt");
+        let dir = fixture("test_files/simple");
+        for args in &[&["lint", "--timings", &target][..],
+                      &["lint", "--all", "--timings", &dir],
+                      &["check", "--comments", "--timings", &target],
+                      &["audit", "--timings", &target],
+                      &["manifest", "--timings", &dir],
+                      &["doctor", "--timings", &dir]] {
+            let (_, _, err) = run_args(args);
+            assert!(err.contains("editorconfig: timings: slowest config files to parse\n"),
+                    "{:?}: {}",
+                    args,
+                    err);
+        }
+        let (code, _, err) = run_args(&["--dump-config", "--timings"]);
+        assert_eq!(code, 2);
+        assert_eq!(err, "editorconfig: --timings only applies to modes looking files up\n");
+    }
+
+    #[test]
+    fn warns_about_untrusted_config_files() {
+        let target = fixture("test_files/non_root_editorconfig/foo/file.txt");
+        let root = fixture("test_files/non_root_editorconfig/foo");
+        let (code, out, err) = run_args(&["--trust-root", &root, &target]);
+        assert_eq!(code, 0);
+        assert_eq!(out, "end_of_line=lf\ninsert_final_newline=false\n");
+        assert!(err.contains("outside the trusted root"));
+    }
+
+    #[test]
+    fn stops_looking_at_the_stop_dir() {
+        let target = fixture("test_files/non_root_editorconfig/foo/file.txt");
+        let stop = fixture("test_files/non_root_editorconfig/foo");
+        let (code, out, err) = run_args(&["--stop-dir", &stop, &target]);
+        assert_eq!(code, 0);
+        assert_eq!(out, "end_of_line=lf\ninsert_final_newline=false\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn audits_a_lookup() {
+        let target = fixture("test_files/non_root_editorconfig/foo/file.txt");
+        let root = fixture("test_files/non_root_editorconfig/foo");
+        let (code, out, _) = run_args(&["audit", "--trust-root", &root, &target]);
+        assert_eq!(code, 0);
+        let read = format!("files read:\n  {}\nfiles ignored:\n  {}: ",
+                           fixture("test_files/non_root_editorconfig/foo/.editorconfig"),
+                           fixture("test_files/non_root_editorconfig/.editorconfig"));
+        assert!(out.starts_with(&format!("[{}]\n{}", target, read)));
+        assert!(out.contains(&format!("active features:\n  trust root {}\ncompiled-in features:\n",
+                                      root)));
+        // Only the variables of EnvOverrides, as the user config isn't read
+        assert!(out.contains("consulted:\n  EDITORCONFIG_CONF_FILENAME\n  \
+                              EDITORCONFIG_GLOBAL_CONFIG\nactive features:\n"));
+
+        let (_, out, _) = run_args(&["audit", "--user-config", &target]);
+        assert!(out.contains("  EDITORCONFIG_GLOBAL_CONFIG\n  XDG_CONFIG_HOME\n"), "{}", out);
+    }
+
+    #[test]
+    fn audits_the_targets_after_a_failing_one() {
+        let broken = fixture("test_files/malformed/file.md");
+        let target = fixture("test_files/simple/file.txt");
+        let (code, out, err) = run_args(&["audit", &broken, &target]);
+        assert_eq!(code, 1);
+        assert!(err.contains(&broken), "{}", err);
+        assert!(out.contains(&format!("[{}]\nfiles read:\n  {}\n",
+                                      target,
+                                      fixture("test_files/simple/.editorconfig"))),
+                "{}",
+                out);
+    }
+
+    #[test]
+    fn takes_the_walk_options_from_flags() {
+        let parse = |args: &[&str]| {
+            let mut argv = vec!["editorconfig".to_string(), "manifest".to_string()];
+            argv.extend(args.iter().map(|a| a.to_string()));
+            parse_args(argv, Options::default(), &mut vec![], &mut vec![]).unwrap().walk_options()
+        };
+        let walk = parse(&[]);
+        assert!(!walk.follow_symlinks && walk.dedup_visited && walk.skip_unreadable);
+        assert!(!walk.sparse_checkout);
+        let walk = parse(&["--follow-symlinks", "--no-dedup-dirs", "--strict-io", "--sparse"]);
+        assert!(walk.follow_symlinks && !walk.dedup_visited && !walk.skip_unreadable);
+        assert!(walk.sparse_checkout);
+    }
+
+    #[test]
+    fn writes_config_files_interactively() {
+        let dir = env::temp_dir().join(format!("editorconfig-init-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let options = parse_args(vec!["editorconfig".to_string(),
+                                      "init".to_string(),
+                                      "--interactive".to_string()],
+                                 Options::default(),
+                                 &mut vec![],
+                                 &mut vec![])
+            .unwrap();
+        let mut answers = "go\ntabs\n\n\n\n".as_bytes();
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(init_config(&options, &dir, &mut answers, &mut out, &mut err).unwrap(), 0);
+        let config = fs::read_to_string(dir.join(".editorconfig")).unwrap();
+        assert!(config.contains("\n[*.go]\nindent_style = tab\n"));
+        // Existing files are never overwritten
+        let mut answers = "\n\n\n\n\n".as_bytes();
+        assert_eq!(init_config(&options, &dir, &mut answers, &mut out, &mut err).unwrap(), 1);
+        // But their generated settings can be refreshed, keeping manual additions
+        fs::write(dir.join("main.py"), "").unwrap();
+        let edited = config.replace("languages=go", "languages=go,python") +
+                     "[*.txt]\ncharset = latin1\n";
+        fs::write(dir.join(".editorconfig"), &edited).unwrap();
+        let update = Options { update: true, ..options.clone() };
+        let mut out = vec![];
+        assert_eq!(init_config(&update, &dir, &mut "".as_bytes(), &mut out, &mut err).unwrap(), 0);
+        assert!(String::from_utf8(out).unwrap().starts_with("Updated "));
+        let config = fs::read_to_string(dir.join(".editorconfig")).unwrap();
+        assert!(config.contains("\n[*.py]\n"));
+        assert!(config.ends_with("[*.txt]\ncharset = latin1\n"));
+        let mut out = vec![];
+        assert_eq!(init_config(&update, &dir, &mut "".as_bytes(), &mut out, &mut err).unwrap(), 0);
+        assert!(String::from_utf8(out).unwrap().ends_with(" is up to date\n"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn checks_git_line_ending_conversion() {
+        let dir = env::temp_dir().join(format!("editorconfig-check-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(&dir)
+                .args(args)
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        if !git(&["init", "-q"]) || !git(&["config", "core.autocrlf", "true"]) {
+            // No git to check against
+            fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+        fs::write(dir.join(".editorconfig"), "root = true\n[*]\nend_of_line = lf\n").unwrap();
+        let options = parse_args(vec!["editorconfig".to_string(),
+                                      "check".to_string(),
+                                      "--environment".to_string(),
+                                      "file.txt".to_string()],
+                                 Options::default(),
+                                 &mut vec![],
+                                 &mut vec![])
+            .unwrap();
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 1);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "file.txt: [EC101] end_of_line = lf but git checks files out with CRLF because \
+                    of core.autocrlf = true\n");
+        let options = Options { group_by: GroupBy::Rule, ..options };
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 1);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "1 git-line-endings violation across 1 file\n");
+        let json = Options { format: OutputFormat::Json, ..options.clone() };
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&json, &dir, &mut out, &mut err), 1);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "[\n  {\"rule\": \"git-line-endings\", \"count\": 1, \"files\": 1}\n]\n");
+        fs::write(dir.join("CODEOWNERS"), "*.txt @docs-team\n").unwrap();
+        let options = Options { group_by: GroupBy::File, owners: true, ..options };
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 1);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "file.txt: 1 violation (owned by @docs-team)\n");
+        // Files nobody writes by hand are left alone
+        let options = Options {
+            targets: vec!["file.txt".to_string(),
+                          "vendor/x.txt".to_string(),
+                          "gen/y.txt".to_string()],
+            classify: vec![(FileClass::Generated, vec!["gen/**".to_string()])],
+            group_by: GroupBy::Rule,
+            owners: false,
+            ..options
+        };
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 1);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "1 git-line-endings violation across 1 file\n");
+        assert_eq!(String::from_utf8(err).unwrap(),
+                   "editorconfig: skipped 2 generated, vendored or binary files\n");
+        fs::write(dir.join("marked.txt"), "# @generated by hand\n").unwrap();
+        let options = Options { targets: vec!["marked.txt".to_string()], ..options };
+        assert_eq!(execute(&options, &dir, &mut vec![], &mut vec![]), 0);
+        let options = Options { include_generated: true, ..options };
+        assert_eq!(execute(&options, &dir, &mut vec![], &mut vec![]), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn checks_and_fixes_redundant_settings() {
+        let dir = env::temp_dir().join(format!("editorconfig-redundant-{}", std::process::id()));
+        fs::create_dir_all(dir.join("pkg")).unwrap();
+        fs::write(dir.join(".editorconfig"), "root = true\n[*]\nindent_size = 2\n").unwrap();
+        fs::write(dir.join("pkg/.editorconfig"), "[*]\nindent_size = 2\ncharset = utf-8\n")
+            .unwrap();
+        let options = Options {
+            check: true,
+            check_redundant: true,
+            targets: vec!["pkg/file.txt".to_string()],
+            path_style: PathStyle::Relative,
+            ..Options::default()
+        };
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 1);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "pkg/.editorconfig: [EC201] [*] indent_size = 2 on line 2 restates the value \
+                    inherited from .editorconfig\n");
+        let options = Options { fix: true, ..options };
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 0);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "Removed 1 redundant settings from pkg/.editorconfig\n");
+        assert_eq!(fs::read_to_string(dir.join("pkg/.editorconfig")).unwrap(),
+                   "[*]\ncharset = utf-8\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn lints_and_simplifies_patterns() {
+        let dir = env::temp_dir().join(format!("editorconfig-simplify-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(".editorconfig"),
+                  "root = true\n[*.{md,md}]\nindent_size = 2\n[*.md]\ncharset = utf-8\n")
+            .unwrap();
+        let options = Options {
+            lint: true,
+            targets: vec!["a.md".to_string()],
+            path_style: PathStyle::Relative,
+            ..Options::default()
+        };
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 1);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   ".editorconfig: [EC205] [*.{md,md}] on line 2 matches the same files as \
+                    [*.md]\n.editorconfig: [EC205] [*.md] on line 4 repeats the section right \
+                    before it\n");
+        let verify = Options { verify_idempotent: true, ..options.clone() };
+        assert_eq!(execute(&verify, &dir, &mut vec![], &mut vec![]), 2);
+        let verify = Options { fix: true, ..verify };
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&verify, &dir, &mut out, &mut err), 0);
+        assert_eq!(String::from_utf8(err).unwrap(),
+                   "editorconfig: fixed 1 config files twice, 0 changed again\n");
+        assert!(fs::read_to_string(dir.join(".editorconfig")).unwrap().contains("{md,md}"));
+        let options = Options { fix: true, ..options };
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 0);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "Simplified 2 sections of .editorconfig\n");
+        assert_eq!(fs::read_to_string(dir.join(".editorconfig")).unwrap(),
+                   "root = true\n[*.md]\nindent_size = 2\ncharset = utf-8\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn checks_comments_after_values() {
+        let options = Options {
+            check: true,
+            check_comments: true,
+            targets: vec!["file.txt".to_string()],
+            path_style: PathStyle::Relative,
+            ..Options::default()
+        };
+        let dir = Path::new("test_files/inline_comments").canonicalize().unwrap();
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 1);
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   ".editorconfig: [EC202] indent_size = 4 on line 4 is followed by a comment, \
+                    EditorConfig 0.15.0 and later read the value as `4 ; like rustfmt`\n");
+
+        // A target failing doesn't stop the others from being checked
+        let options = Options {
+            targets: vec!["malformed/file.md".to_string(), "inline_comments/file.txt".to_string()],
+            ..options
+        };
+        let dir = Path::new("test_files").canonicalize().unwrap();
+        let (mut out, mut err) = (vec![], vec![]);
+        assert_eq!(execute(&options, &dir, &mut out, &mut err), 1);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("inline_comments/.editorconfig: [EC202] "), "{}", out);
+        let err = String::from_utf8(err).unwrap();
+        assert!(err.starts_with("editorconfig: malformed/file.md: "), "{}", err);
+    }
+
+    #[test]
+    fn fails_for_targets_without_config() {
+        let dir = env::temp_dir().join(format!("editorconfig-uncovered-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let uncovered = dir.join("file.txt").to_str().unwrap().to_string();
+        let covered = fixture("test_files/simple/file.txt");
+        let (code, out, err) = run_args(&["--fail-on-missing-config", &uncovered, &covered]);
+        assert_eq!(code, 1);
+        assert!(out.ends_with("end_of_line=lf\ninsert_final_newline=true\n"));
+        assert_eq!(err, format!("editorconfig: uncovered: {}\n", uncovered));
+        assert_eq!(run_args(&[&uncovered]).0, 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn exports_the_config_of_a_file() {
+        let (code, out, _) = run_args(&["export", "--for", "test_files/multi_level/foo/file.txt"]);
+        assert_eq!(code, 0);
+        assert!(out.starts_with("# Frozen by `editorconfig export` for "));
+        assert!(out.contains("\nroot = true\n\n[*]\n"));
+        let (code, _, err) = run_args(&["export"]);
+        assert_eq!(code, 2);
+        assert_eq!(err, "editorconfig: export needs --for\n");
+    }
+
+    #[test]
+    fn writes_a_manifest_of_a_directory() {
+        let (code, out, _) = run_args(&["manifest", "test_files/lenient"]);
+        assert_eq!(code, 0);
+        assert!(out.starts_with("{\n  \"version\": 1,\n  \"files\": {\n"));
+        assert!(out.contains("\n    \".editorconfig\": \""));
+        assert!(out.contains("\n    \"file.txt\": \""));
+    }
+
+    #[test]
+    fn reports_the_health_of_a_directory() {
+        let (code, out, _) = run_args(&["doctor", "--paths", "relative", "test_files/doctor"]);
+        assert_eq!(code, 0);
+        assert!(out.starts_with("Health score: 93/100 (2 files, 2 config files)\n\
+                                 1. [medium] coverage: test_files/doctor: 1 of 2 files get no \
+                                 properties\n"));
+        assert!(out.contains("\n3. [low] redundancy: test_files/doctor/src/.editorconfig: "));
+    }
+
+    #[test]
+    fn mirrors_the_core_command_line() {
+        let target = fixture("test_files/lenient/file.txt");
+        let core = |args: &[&str]| {
+            let mut argv = vec!["editorconfig-core".to_string()];
+            argv.extend(args.iter().map(|a| a.to_string()));
+            let (mut out, mut err) = (vec![], vec![]);
+            let code = run_core_in(argv, Path::new("/"), &mut out, &mut err);
+            (code, String::from_utf8(out).unwrap())
+        };
+        let (code, out, _) = run_args(&[&target]);
+        assert_eq!(core(&[&target]), (code, out));
+        assert_eq!(core(&["-b", "0.8.0", &target]).1, run_args(&["-b", "0.8.0", &target]).1);
+        assert_eq!(core(&["-b", "0.x", &target]).0, 2);
+        assert!(core(&["-v"]).1.starts_with("EditorConfig Rust Core Version "));
+        // Flags and subcommands of the richer command line aren't accepted
+        assert_eq!(core(&["--strict-io", &target]).0, 2);
+        assert!(core(&["audit", &target]).1.starts_with("[audit]\n"));
+    }
+
+    #[test]
+    fn explains_diagnostic_codes() {
+        let (mut out, mut err) = (vec![], vec![]);
+        let args = vec!["editorconfig".to_string(), "--explain".to_string(), "EC101".to_string()];
+        assert_eq!(run_in(args, Path::new("/"), &mut out, &mut err), 0);
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .starts_with("EC101 (git-line-endings), medium severity\n\n"));
+        let (mut out, mut err) = (vec![], vec![]);
+        let args = vec!["editorconfig".to_string(), "--explain".to_string(), "EC999".to_string()];
+        assert_eq!(run_in(args, Path::new("/"), &mut out, &mut err), 1);
+        assert!(String::from_utf8(err).unwrap().contains("known codes are EC101"));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        let (code, _, err) = run_args(&["--paths", "sideways"]);
+        assert_eq!(code, 2);
+        assert!(err.contains("sideways"));
+        let (code, _, err) = run_args(&["--format", "sarif", "file.txt"]);
+        assert_eq!(code, 2);
+        assert_eq!(err, "editorconfig: --format sarif needs check or lint\n");
+        let (code, _, err) = run_args(&["lint", "--format", "json-map", "file.txt"]);
+        assert_eq!(code, 2);
+        assert_eq!(err, "editorconfig: --format json-map only applies to lookups\n");
+    }
+}
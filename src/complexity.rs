@@ -0,0 +1,201 @@
+//! Estimates how expensive a section pattern is to match, to point users at the ones
+//! worth simplifying.
+
+use std::path::Path;
+
+use ini::Ini;
+use {load_ini, Error};
+
+/// Patterns with more `**` than this are reported.
+pub const MAX_DOUBLE_STARS: usize = 2;
+/// Patterns whose alternations are nested deeper than this are reported.
+pub const MAX_ALTERNATION_DEPTH: usize = 2;
+/// Patterns expanding to more alternatives than this are reported.
+pub const MAX_EXPANSIONS: usize = 64;
+
+/// Something that makes a pattern expensive, with a way to avoid it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternIssue {
+    /// What makes the pattern expensive.
+    pub message: String,
+    /// How to write it in a cheaper way.
+    pub suggestion: String,
+}
+
+/// The estimated matching cost of a section pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternCost {
+    pub pattern: String,
+    /// Number of `**` wildcards.
+    pub double_stars: usize,
+    /// How deep `{...}` alternations are nested.
+    pub alternation_depth: usize,
+    /// Number of distinct literal patterns the alternations expand to.
+    pub expansions: usize,
+    /// Number of `{n..m}` numeric ranges.
+    pub numeric_ranges: usize,
+    /// Why the pattern is considered expensive, empty if it isn't.
+    pub issues: Vec<PatternIssue>,
+}
+
+impl PatternCost {
+    pub fn is_expensive(&self) -> bool {
+        !self.issues.is_empty()
+    }
+}
+
+/// Estimates the cost of matching `pattern`.
+pub fn pattern_cost(pattern: &str) -> PatternCost {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut parser = BraceParser {
+        chars: &chars,
+        pos: 0,
+        max_depth: 0,
+        numeric_ranges: 0,
+    };
+    let expansions = parser.sequence(0);
+    let mut cost = PatternCost {
+        pattern: pattern.to_string(),
+        double_stars: pattern.matches("**").count(),
+        alternation_depth: parser.max_depth,
+        expansions,
+        numeric_ranges: parser.numeric_ranges,
+        issues: vec![],
+    };
+
+    if cost.double_stars > MAX_DOUBLE_STARS {
+        cost.issues.push(PatternIssue {
+            message: format!("{} `**` wildcards, each of which can span any number of directories",
+                             cost.double_stars),
+            suggestion: "use a single `**` and match the rest with `*`".to_string(),
+        });
+    }
+    if cost.alternation_depth > MAX_ALTERNATION_DEPTH {
+        cost.issues.push(PatternIssue {
+            message: format!("alternations nested {} levels deep", cost.alternation_depth),
+            suggestion: "flatten the nested `{...}` into a single list".to_string(),
+        });
+    }
+    if cost.expansions > MAX_EXPANSIONS {
+        cost.issues.push(PatternIssue {
+            message: format!("alternations expand to {} patterns", cost.expansions),
+            suggestion: "split the section or use wildcards instead of listing names".to_string(),
+        });
+    }
+    if cost.numeric_ranges > 0 && cost.double_stars > 0 {
+        cost.issues.push(PatternIssue {
+            message: "numeric ranges next to `**` need a slower capturing match".to_string(),
+            suggestion: "anchor the pattern to a directory instead of using `**`".to_string(),
+        });
+    }
+    cost
+}
+
+/// Returns the cost of every expensive section pattern in the config file at `path`.
+pub fn expensive_patterns(path: &Path) -> Result<Vec<PatternCost>, Error> {
+    Ok(expensive_sections(&load_ini(path)?))
+}
+
+/// Returns the cost of every expensive section pattern of an already parsed config file.
+pub(crate) fn expensive_sections(ini: &Ini) -> Vec<PatternCost> {
+    ini.sections()
+        .flatten()
+        .map(|section| pattern_cost(section))
+        .filter(|cost| cost.is_expensive())
+        .collect()
+}
+
+struct BraceParser<'a> {
+    chars: &'a [char],
+    pos: usize,
+    max_depth: usize,
+    numeric_ranges: usize,
+}
+
+impl<'a> BraceParser<'a> {
+    /// Parses until an unmatched `,` or `}` at `depth`, returning the number of expansions.
+    fn sequence(&mut self, depth: usize) -> usize {
+        let mut expansions = 1usize;
+        while self.pos < self.chars.len() {
+            match self.chars[self.pos] {
+                '\\' => self.pos += 2,
+                '{' => {
+                    self.pos += 1;
+                    expansions = expansions.saturating_mul(self.group(depth + 1));
+                }
+                ',' | '}' if depth > 0 => return expansions,
+                _ => self.pos += 1,
+            }
+        }
+        expansions
+    }
+
+    /// Parses the inside of a `{...}` group, the opening brace already consumed.
+    fn group(&mut self, depth: usize) -> usize {
+        if self.is_numeric_range() {
+            self.numeric_ranges += 1;
+            return 1;
+        }
+        self.max_depth = self.max_depth.max(depth);
+        let mut expansions = 0usize;
+        loop {
+            expansions = expansions.saturating_add(self.sequence(depth));
+            match self.chars.get(self.pos) {
+                Some(&',') => self.pos += 1,
+                Some(&'}') => {
+                    self.pos += 1;
+                    return expansions;
+                }
+                // Unterminated, matched literally
+                _ => return 1,
+            }
+        }
+    }
+
+    fn is_numeric_range(&mut self) -> bool {
+        let rest: String = self.chars[self.pos..].iter().take_while(|&&c| c != '}').collect();
+        if self.pos + rest.chars().count() >= self.chars.len() {
+            return false;
+        }
+        let mut ends = rest.splitn(2, "..");
+        let is_number = |s: Option<&str>| {
+            s.map(|s| s.trim_start_matches('-'))
+                .is_some_and(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()))
+        };
+        if is_number(ends.next()) && is_number(ends.next()) {
+            self.pos += rest.chars().count() + 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_patterns_are_cheap() {
+        for pattern in &["*", "*.rs", "{Makefile,*.mk}", "lib/**.js", "file{1..3}.txt"] {
+            assert!(!pattern_cost(pattern).is_expensive(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn counts_expansions_and_depth() {
+        let cost = pattern_cost("{a,b{c,d,e}}/{x,y}");
+        assert_eq!(cost.expansions, 8);
+        assert_eq!(cost.alternation_depth, 2);
+        assert_eq!(pattern_cost("{1..3}").numeric_ranges, 1);
+        assert_eq!(pattern_cost("\\{a,b}").expansions, 1);
+    }
+
+    #[test]
+    fn flags_expensive_patterns() {
+        assert!(pattern_cost("**/a/**/b/**/c").is_expensive());
+        assert!(pattern_cost("{a,{b,{c,{d,e}}}}").is_expensive());
+        assert!(pattern_cost("{a,b,c,d}{a,b,c,d}{a,b,c,d}{a,b}").is_expensive());
+        assert!(pattern_cost("**/file{1..10}").is_expensive());
+    }
+}
@@ -0,0 +1,253 @@
+//! Properties with known meanings, parsed into types.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ordermap::OrderMap;
+
+use derived::{effective_indent_size, effective_tab_width};
+
+/// `indent_style`.
+///
+/// Values the specification doesn't define yet are kept as `Other`, like in the other
+/// property enums, so that matching on them keeps compiling when new values are added.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IndentStyle {
+    Tab,
+    Space,
+    Other(String),
+}
+
+/// `end_of_line`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EndOfLine {
+    Lf,
+    CrLf,
+    Cr,
+    Other(String),
+}
+
+/// `charset`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Charset {
+    Latin1,
+    Utf8,
+    /// UTF-8 with a byte order mark.
+    Utf8Bom,
+    Utf16Be,
+    Utf16Le,
+    Other(String),
+}
+
+macro_rules! keyword_enum {
+    ($name:ident, $property:expr, $($variant:ident => $keyword:expr),+) => {
+        impl FromStr for $name {
+            type Err = String;
+
+            /// Reads unknown values as `Other`, only an empty value is an error.
+            fn from_str(s: &str) -> Result<$name, String> {
+                match s {
+                    $($keyword => Ok($name::$variant),)+
+                    "" => Err(format!("empty {}", $property)),
+                    _ => Ok($name::Other(s.to_string())),
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(match *self {
+                    $($name::$variant => $keyword,)+
+                    $name::Other(ref value) => value,
+                })
+            }
+        }
+    }
+}
+
+keyword_enum!(IndentStyle, "indent_style", Tab => "tab", Space => "space");
+keyword_enum!(EndOfLine, "end_of_line", Lf => "lf", CrLf => "crlf", Cr => "cr");
+keyword_enum!(Charset, "charset",
+              Latin1 => "latin1", Utf8 => "utf-8", Utf8Bom => "utf-8-bom",
+              Utf16Be => "utf-16be", Utf16Le => "utf-16le");
+
+impl EndOfLine {
+    /// The characters ending lines, e.g. to configure a writer or a text buffer, `None`
+    /// for `Other`.
+    pub fn line_ending(&self) -> Option<&'static str> {
+        match *self {
+            EndOfLine::Lf => Some("\n"),
+            EndOfLine::CrLf => Some("\r\n"),
+            EndOfLine::Cr => Some("\r"),
+            EndOfLine::Other(_) => None,
+        }
+    }
+
+    /// The value for lines ending with `ending`, like `\r\n`.
+    pub fn from_line_ending(ending: &str) -> Option<EndOfLine> {
+        match ending {
+            "\n" => Some(EndOfLine::Lf),
+            "\r\n" => Some(EndOfLine::CrLf),
+            "\r" => Some(EndOfLine::Cr),
+            _ => None,
+        }
+    }
+}
+
+impl Charset {
+    /// The WHATWG Encoding Standard label of the charset, which encoding libraries look
+    /// encodings up by, `None` for `Other`. UTF-8 with or without a byte order mark share
+    /// the label, see `has_bom`.
+    pub fn encoding_label(&self) -> Option<&'static str> {
+        match *self {
+            Charset::Latin1 => Some("iso-8859-1"),
+            Charset::Utf8 | Charset::Utf8Bom => Some("utf-8"),
+            Charset::Utf16Be => Some("utf-16be"),
+            Charset::Utf16Le => Some("utf-16le"),
+            Charset::Other(_) => None,
+        }
+    }
+
+    /// Whether files start with a byte order mark. UTF-16 files only have one when the
+    /// tool writing them adds it, as the byte order is already in the charset.
+    pub fn has_bom(&self) -> bool {
+        *self == Charset::Utf8Bom
+    }
+}
+
+impl IndentStyle {
+    /// The character indentation is made of, `None` for `Other`.
+    pub fn indent_char(&self) -> Option<char> {
+        match *self {
+            IndentStyle::Tab => Some('\t'),
+            IndentStyle::Space => Some(' '),
+            IndentStyle::Other(_) => None,
+        }
+    }
+}
+
+/// The properties of a file, with the known ones parsed. Known properties that are unset
+/// or have an invalid value are `None`, the invalid values being kept in `extras`, but for
+/// values of `indent_style`, `end_of_line` and `charset`, which are kept as `Other`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    pub indent_style: Option<IndentStyle>,
+    /// Columns of an indentation level, the tab width for `indent_size = tab`.
+    pub indent_size: Option<u32>,
+    /// Columns a tab is shown as, the indent size when `tab_width` isn't set.
+    pub tab_width: Option<u32>,
+    pub end_of_line: Option<EndOfLine>,
+    pub charset: Option<Charset>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+    /// `None` for `off` too.
+    pub max_line_length: Option<u32>,
+    /// Unknown properties, and known ones whose value couldn't be parsed.
+    pub extras: OrderMap<String, String>,
+}
+
+impl Config {
+    /// The text of an indentation level, like `effective_indent_unit`.
+    pub fn indent_unit(&self) -> Option<String> {
+        match *self.indent_style.as_ref()? {
+            IndentStyle::Tab => Some("\t".to_string()),
+            IndentStyle::Space => self.indent_size.map(|size| " ".repeat(size as usize)),
+            IndentStyle::Other(_) => None,
+        }
+    }
+}
+
+impl<'a> From<&'a OrderMap<String, String>> for Config {
+    fn from(properties: &'a OrderMap<String, String>) -> Config {
+        let mut config = Config {
+            indent_size: effective_indent_size(properties),
+            tab_width: effective_tab_width(properties),
+            ..Config::default()
+        };
+        for (key, value) in properties {
+            let parsed = match key.as_str() {
+                _ if value == "unset" => true,
+                "indent_style" => value.parse().map(|v| config.indent_style = Some(v)).is_ok(),
+                "end_of_line" => value.parse().map(|v| config.end_of_line = Some(v)).is_ok(),
+                "charset" => value.parse().map(|v| config.charset = Some(v)).is_ok(),
+                "trim_trailing_whitespace" => {
+                    parse_bool(value).map(|v| config.trim_trailing_whitespace = Some(v)).is_some()
+                }
+                "insert_final_newline" => {
+                    parse_bool(value).map(|v| config.insert_final_newline = Some(v)).is_some()
+                }
+                "max_line_length" if value == "off" => true,
+                "max_line_length" => {
+                    value.parse().map(|v| config.max_line_length = Some(v)).is_ok()
+                }
+                // Sizes of 0 columns aren't read, see `effective_indent_size`
+                "indent_size" => value == "tab" || value.parse::<u32>().is_ok_and(|n| n > 0),
+                "tab_width" => value.parse::<u32>().is_ok_and(|n| n > 0),
+                _ => false,
+            };
+            if !parsed {
+                config.extras.insert(key.clone(), value.clone());
+            }
+        }
+        config
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_properties() {
+        let properties: OrderMap<_, _> = [("indent_style", "space"),
+                                          ("indent_size", "2"),
+                                          ("end_of_line", "crlf"),
+                                          ("charset", "utf-8-bom"),
+                                          ("insert_final_newline", "true"),
+                                          ("trim_trailing_whitespace", "maybe"),
+                                          ("max_line_length", "off"),
+                                          ("quote_type", "single")]
+            .iter()
+            .map(|&(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let config = Config::from(&properties);
+        assert_eq!(config.indent_style, Some(IndentStyle::Space));
+        assert_eq!((config.indent_size, config.tab_width), (Some(2), Some(2)));
+        assert_eq!(config.end_of_line, Some(EndOfLine::CrLf));
+        assert_eq!(config.charset, Some(Charset::Utf8Bom));
+        assert_eq!(config.insert_final_newline, Some(true));
+        assert_eq!(config.trim_trailing_whitespace, None);
+        assert_eq!(config.max_line_length, None);
+        assert_eq!(config.indent_unit(), Some("  ".to_string()));
+        let extras: Vec<_> = config.extras.keys().map(String::as_str).collect();
+        assert_eq!(extras, ["trim_trailing_whitespace", "quote_type"]);
+        assert_eq!(Charset::Utf16Le.to_string(), "utf-16le");
+
+        let future: Charset = "utf-32".parse().unwrap();
+        assert_eq!(future, Charset::Other("utf-32".to_string()));
+        assert_eq!((future.to_string(), future.encoding_label()), ("utf-32".to_string(), None));
+        assert!("".parse::<EndOfLine>().is_err());
+        assert_eq!(EndOfLine::from_line_ending("\r\n").and_then(|eol| eol.line_ending()),
+                   Some("\r\n"));
+        assert_eq!(IndentStyle::Tab.indent_char(), Some('\t'));
+    }
+
+    #[test]
+    fn keeps_sizes_of_zero_in_extras() {
+        let properties: OrderMap<_, _> = [("indent_size", "0"), ("tab_width", "0")]
+            .iter()
+            .map(|&(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        let config = Config::from(&properties);
+        assert_eq!((config.indent_size, config.tab_width), (None, None));
+        assert_eq!(config.extras, properties);
+    }
+}
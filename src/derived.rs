@@ -0,0 +1,65 @@
+//! Values following from several properties, like the width of a tab when only
+//! `indent_size` is set, so consumers don't each work them out differently.
+
+use ordermap::OrderMap;
+
+fn number(properties: &OrderMap<String, String>, key: &str) -> Option<u32> {
+    properties.get(key).and_then(|value| value.parse().ok()).filter(|&n| n > 0)
+}
+
+/// Columns a tab is shown as: `tab_width`, or else `indent_size` when it's a number.
+pub fn effective_tab_width(properties: &OrderMap<String, String>) -> Option<u32> {
+    number(properties, "tab_width").or_else(|| number(properties, "indent_size"))
+}
+
+/// Columns of an indentation level: `indent_size`, or the tab width when it's `tab`.
+pub fn effective_indent_size(properties: &OrderMap<String, String>) -> Option<u32> {
+    match properties.get("indent_size").map(String::as_str) {
+        Some("tab") => number(properties, "tab_width"),
+        Some(_) => number(properties, "indent_size"),
+        // Like `indent_size = tab`, which is the default of tab indentation
+        None if properties.get("indent_style").map(String::as_str) == Some("tab") => {
+            number(properties, "tab_width")
+        }
+        None => None,
+    }
+}
+
+/// The text of an indentation level: a tab, or as many spaces as the indent size. `None`
+/// when `indent_style` isn't set, or is `space` without a size.
+pub fn effective_indent_unit(properties: &OrderMap<String, String>) -> Option<String> {
+    match properties.get("indent_style").map(String::as_str) {
+        Some("tab") => Some("\t".to_string()),
+        Some("space") => effective_indent_size(properties).map(|size| " ".repeat(size as usize)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(pairs: &[(&str, &str)]) -> OrderMap<String, String> {
+        pairs.iter().map(|&(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn derives_widths_and_units() {
+        let spaces = properties(&[("indent_style", "space"), ("indent_size", "2")]);
+        assert_eq!(effective_tab_width(&spaces), Some(2));
+        assert_eq!(effective_indent_unit(&spaces), Some("  ".to_string()));
+
+        let tabs = properties(&[("indent_style", "tab"), ("tab_width", "8")]);
+        assert_eq!(effective_indent_size(&tabs), Some(8));
+        assert_eq!(effective_indent_unit(&tabs), Some("\t".to_string()));
+
+        let mixed = properties(&[("indent_style", "space"), ("indent_size", "tab"),
+                                 ("tab_width", "3")]);
+        assert_eq!(effective_indent_unit(&mixed), Some("   ".to_string()));
+
+        let unset = properties(&[("indent_size", "4"), ("tab_width", "unset")]);
+        assert_eq!(effective_tab_width(&unset), Some(4));
+        assert_eq!(effective_indent_unit(&unset), None);
+        assert_eq!(effective_indent_size(&properties(&[("indent_size", "0")])), None);
+    }
+}
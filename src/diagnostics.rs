@@ -0,0 +1,373 @@
+//! Problems found by `editorconfig check`, and summaries of many of them.
+
+use std::cmp::Reverse;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ordermap::OrderMap;
+
+use doctor::Severity;
+use messages::{MessageArgs, MessageCatalog};
+
+/// A problem found by a check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file the problem is about.
+    pub file: PathBuf,
+    /// Identifies the check which found the problem, e.g. `git-line-endings`.
+    pub rule: &'static str,
+    /// Identifies the text describing the problem in message catalogs, stable across
+    /// releases, e.g. `git-line-endings.conflict`.
+    pub message_id: &'static str,
+    /// Values of the placeholders in the text.
+    pub args: MessageArgs,
+}
+
+impl Diagnostic {
+    /// The stable code of the diagnostic's rule, or `None` for unknown rules.
+    pub fn code(&self) -> Option<&'static str> {
+        RULES.iter().find(|rule| rule.name == self.rule).map(|rule| rule.code)
+    }
+
+    /// How serious the problem is, that of its rule. `Severity::Medium` for unknown rules.
+    pub fn severity(&self) -> Severity {
+        RULES.iter()
+            .find(|rule| rule.name == self.rule)
+            .map_or(Severity::Medium, |rule| rule.severity)
+    }
+
+    /// Describes the problem in the language of `catalog`.
+    pub fn message(&self, catalog: &MessageCatalog) -> String {
+        catalog.format(self.message_id, &self.args)
+    }
+}
+
+/// A check, and the documentation `--explain` prints for it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuleInfo {
+    /// Stable code of the rule, e.g. `EC101`. Codes are never reused for another rule.
+    pub code: &'static str,
+    /// Name of the rule, as in `Diagnostic::rule`.
+    pub name: &'static str,
+    /// How serious the problems the rule finds are, e.g. for CI to only fail on the
+    /// `Severity::High` ones.
+    pub severity: Severity,
+    /// What the rule detects, why it matters and how to fix it.
+    pub explanation: &'static str,
+}
+
+/// Every rule checks can report. Codes starting with EC1 are about settings outside of
+/// config files, codes starting with EC2 about config files themselves.
+pub const RULES: &[RuleInfo] = &[RuleInfo {
+                                     code: "EC101",
+                                     name: "git-line-endings",
+                                     severity: Severity::Medium,
+                                     explanation: "\
+A file's `end_of_line` differs from the line endings git gives it at checkout.
+
+Git converts line endings when checking files out if `core.autocrlf` is `true` (to CRLF) or
+`input` (keeping LF), or else according to `core.eol`. Editors then find line endings the
+config file doesn't ask for, and either keep them, breaking the convention, or convert
+every line they touch, making diffs noisy.
+
+To fix it, make the git setting agree with `end_of_line`, or mark the files with `eol=` in
+`.gitattributes`, which takes precedence over `core.autocrlf` and `core.eol`.
+",
+                                 },
+                                 RuleInfo {
+                                     code: "EC201",
+                                     name: "redundant-setting",
+                                     severity: Severity::Low,
+                                     explanation: "\
+A config file sets a property to the value files already inherit from a config file
+further up.
+
+Restated values make configs longer than they need to be, and when the parent's value
+changes the child silently keeps the old one, which is rarely what was meant.
+
+To fix it, delete the property, or run `check --redundant --fix`. Only properties whose
+section matches the same files in both config files are reported.
+",
+                                 },
+                                 RuleInfo {
+                                     code: "EC202",
+                                     name: "inline-comment",
+                                     severity: Severity::Medium,
+                                     explanation: "\
+A `;` or `#` comment follows a value on the same line.
+
+Since version 0.15.0 of the spec, comments only start at the beginning of a line: cores
+following it read `indent_size = 4 ; like rustfmt` as `4 ; like rustfmt`, an invalid size,
+while older cores and lookups emulating them read `4`. The same config file then means
+different things to different editors.
+
+To fix it, move the comment to a line of its own, or escape the character as `\\;` or `\\#`
+if it's meant to be part of the value.
+",
+                                 },
+                                 RuleInfo {
+                                     code: "EC203",
+                                     name: "broken-config",
+                                     severity: Severity::High,
+                                     explanation: "\
+A config file can't be read or parsed.
+
+Lookups fail for every file below it, so editors fall back to their own settings, or skip
+the config file with a warning when reading it is merely not permitted.
+
+To fix it, correct the line the error points at, or run with `--repair` to see what a
+lenient reading would make of it.
+",
+                                 },
+                                 RuleInfo {
+                                     code: "EC204",
+                                     name: "invalid-pattern",
+                                     severity: Severity::Medium,
+                                     explanation: "\
+A section pattern can't be translated to a regex.
+
+The section then only applies to files whose path is the pattern itself, which is rarely
+what was meant, and other EditorConfig cores may reject the whole file instead.
+
+To fix it, balance the brackets and braces of the pattern, or escape the special
+characters meant literally with `\\`.
+",
+                                 },
+                                 RuleInfo {
+                                     code: "EC205",
+                                     name: "simplifiable-pattern",
+                                     severity: Severity::Low,
+                                     explanation: "\
+A section could be written more simply, giving the same properties to the same files.
+
+Its pattern repeats options of braces, like `[*.{md,md}]` for `[*.md]`, or it has the same
+pattern as the section right before it, which could hold the properties of both. Only
+rewrites proven to match the same paths are suggested.
+
+To fix it, run `lint --fix`, which rewrites the patterns and merges the sections.
+",
+                                 },
+                                 RuleInfo {
+                                     code: "EC206",
+                                     name: "invalid-value",
+                                     severity: Severity::High,
+                                     explanation: "\
+A known property has a value the specification doesn't allow, like `indent_style = spaces`,
+`tab_width = -4` or `charset = utf-32`.
+
+Editors ignore invalid values, so the files the section matches get their own settings
+instead, without any sign that the config file asked for something else.
+
+To fix it, use one of the values the message lists, or `unset` to remove the property.
+",
+                                 },
+                                 RuleInfo {
+                                     code: "EC207",
+                                     name: "misspelled-property",
+                                     severity: Severity::Medium,
+                                     explanation: "\
+A key isn't a known property but is close to one, like `indent_stile` for `indent_style`
+or `insert_final_newlines` for `insert_final_newline`.
+
+Editors ignore keys they don't know, so the files the section matches silently go without
+the setting. Only keys one or two edits away from a known property are reported, since
+tools are free to define their own properties.
+
+To fix it, rename the key to the property the message suggests. Properties of your own
+can be made known with `ResolverBuilder::property`.
+",
+                                 },
+                                 RuleInfo {
+                                     code: "EC208",
+                                     name: "expensive-pattern",
+                                     severity: Severity::Low,
+                                     explanation: "\
+A section pattern is likely to be slow to match, like `[**/a/**/b/**]` or
+`[{a,{b,{c,{d,e}}}}]`.
+
+Patterns are translated to regexes, and every `**`, nested alternation or long list of
+names makes the regex larger or forces it to backtrack. Each lookup matches every section
+of every config file above the target, so one such pattern slows down all of them.
+
+To fix it, use a single `**`, flatten nested `{...}` into one list, or split a section
+listing many names into several. `--timings` reports these patterns along with the config
+files that take the longest to parse and match.
+",
+                                 },
+                                 RuleInfo {
+                                     code: "EC209",
+                                     name: "newer-property",
+                                     severity: Severity::Low,
+                                     explanation: "\
+A property, or a value of it, was added by a later version of the specification than the
+oldest EditorConfig core the project supports, like `spelling_language`, added by 0.16.0.
+
+Editors with an older core ignore it without a word, so their files go without the setting.
+The rule only runs once the oldest core is declared, with `--min-version` or
+`min_spec_version` in the `[lookup]` section of `editorconfig-tool.ini`.
+
+To fix it, raise the minimum version if every editor supports the property by now, or
+accept that older editors won't apply it.
+",
+                                 }];
+
+/// Returns the rule with `code`, ignoring case, or with that name.
+pub fn explain(code: &str) -> Option<&'static RuleInfo> {
+    RULES.iter().find(|rule| rule.code.eq_ignore_ascii_case(code) || rule.name == code)
+}
+
+/// How diagnostics are aggregated in reports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Every diagnostic is reported on its own.
+    #[default]
+    Ungrouped,
+    /// One entry per rule.
+    Rule,
+    /// One entry per file.
+    File,
+    /// One entry per directory, counting the files directly in it.
+    Dir,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<GroupBy, String> {
+        match s {
+            "none" => Ok(GroupBy::Ungrouped),
+            "rule" => Ok(GroupBy::Rule),
+            "file" => Ok(GroupBy::File),
+            "dir" => Ok(GroupBy::Dir),
+            _ => Err(format!("unknown grouping `{}`, expected rule, file, dir or none", s)),
+        }
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            GroupBy::Ungrouped => "none",
+            GroupBy::Rule => "rule",
+            GroupBy::File => "file",
+            GroupBy::Dir => "dir",
+        })
+    }
+}
+
+/// Diagnostics sharing a rule, file or directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticGroup {
+    /// The rule of every diagnostic, unless grouping by file or directory.
+    pub rule: Option<&'static str>,
+    /// The file or directory of every diagnostic, when grouping by file or directory.
+    pub path: Option<PathBuf>,
+    /// Number of diagnostics.
+    pub count: usize,
+    /// Number of distinct files they are about.
+    pub files: usize,
+}
+
+type GroupKey = (Option<&'static str>, Option<PathBuf>);
+
+/// Aggregates `diagnostics` according to `by`, largest groups first. Returns one group
+/// per diagnostic for `GroupBy::Ungrouped`.
+pub fn group_diagnostics(diagnostics: &[Diagnostic], by: GroupBy) -> Vec<DiagnosticGroup> {
+    if by == GroupBy::Ungrouped {
+        return diagnostics.iter()
+            .map(|diagnostic| {
+                DiagnosticGroup {
+                    rule: Some(diagnostic.rule),
+                    path: Some(diagnostic.file.clone()),
+                    count: 1,
+                    files: 1,
+                }
+            })
+            .collect();
+    }
+    // (rule, path) of a group to its count and files
+    let mut groups: OrderMap<GroupKey, (usize, Vec<&Path>)> = OrderMap::new();
+    for diagnostic in diagnostics {
+        let key = match by {
+            GroupBy::Ungrouped | GroupBy::Rule => (Some(diagnostic.rule), None),
+            GroupBy::File => (None, Some(diagnostic.file.clone())),
+            GroupBy::Dir => {
+                (None, Some(diagnostic.file.parent().unwrap_or(Path::new("")).to_path_buf()))
+            }
+        };
+        let group = groups.entry(key).or_insert((0, vec![]));
+        group.0 += 1;
+        if !group.1.contains(&diagnostic.file.as_path()) {
+            group.1.push(&diagnostic.file);
+        }
+    }
+    let mut result: Vec<_> = groups.into_iter()
+        .map(|((rule, path), (count, files))| {
+            DiagnosticGroup {
+                rule,
+                path,
+                count,
+                files: files.len(),
+            }
+        })
+        .collect();
+    // Stable, so groups of the same size stay in the order they were first seen
+    result.sort_by_key(|group| Reverse(group.count));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(file: &str, rule: &'static str) -> Diagnostic {
+        Diagnostic {
+            file: PathBuf::from(file),
+            rule,
+            message_id: "test",
+            args: vec![],
+        }
+    }
+
+    #[test]
+    fn groups_by_rule_file_and_dir() {
+        let diagnostics = vec![diagnostic("src/a.rs", "trailing-whitespace"),
+                               diagnostic("src/a.rs", "trailing-whitespace"),
+                               diagnostic("src/b.rs", "final-newline"),
+                               diagnostic("src/legacy/c.rs", "trailing-whitespace"),
+                               diagnostic("src/legacy/c.rs", "final-newline"),
+                               diagnostic("src/legacy/c.rs", "final-newline")];
+
+        let by_rule = group_diagnostics(&diagnostics, GroupBy::Rule);
+        assert_eq!(by_rule.len(), 2);
+        assert_eq!((by_rule[0].rule, by_rule[0].count, by_rule[0].files),
+                   (Some("trailing-whitespace"), 3, 2));
+        assert_eq!((by_rule[1].rule, by_rule[1].count, by_rule[1].files),
+                   (Some("final-newline"), 3, 2));
+
+        let by_file = group_diagnostics(&diagnostics, GroupBy::File);
+        assert_eq!(by_file[0].path, Some(PathBuf::from("src/legacy/c.rs")));
+        assert_eq!(by_file[0].count, 3);
+
+        let by_dir = group_diagnostics(&diagnostics, GroupBy::Dir);
+        assert_eq!(by_dir.len(), 2);
+        assert_eq!((by_dir[0].path.clone(), by_dir[0].count, by_dir[0].files),
+                   (Some(PathBuf::from("src")), 3, 2));
+
+        assert_eq!(group_diagnostics(&diagnostics, GroupBy::Ungrouped).len(), 6);
+    }
+
+    #[test]
+    fn rules_have_unique_codes() {
+        for (i, rule) in RULES.iter().enumerate() {
+            assert!(rule.code.starts_with("EC") && rule.code.len() == 5);
+            let earlier = &RULES[..i];
+            assert!(earlier.iter().all(|other| other.code != rule.code && other.name != rule.name));
+        }
+        assert_eq!(explain("ec101").map(|rule| rule.name), Some("git-line-endings"));
+        assert_eq!(explain("git-line-endings").map(|rule| rule.code), Some("EC101"));
+        assert_eq!(explain("EC999"), None);
+        assert_eq!(diagnostic("a.txt", "git-line-endings").code(), Some("EC101"));
+    }
+}
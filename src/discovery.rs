@@ -0,0 +1,163 @@
+//! Finding every config file governing a directory tree, for the features looking at config
+//! files rather than at the files they apply to, like `lint --all` and `Watcher::watch_tree`.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use cache::ConfigCache;
+use {crawl_dir_paths, lookup_path, parsed_config, stop_dir, walk_files, Error, LookupOptions,
+     WalkOptions, Warning};
+
+/// A config file found by `find_all_configs`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigFileInfo {
+    pub path: PathBuf,
+    /// Whether it's in a parent directory of the tree, applying to all of it.
+    pub inherited: bool,
+    /// Whether it says `root = true`.
+    pub is_root: bool,
+    /// The number of sections, not counting the properties before the first one.
+    pub sections: usize,
+    /// Why it can't be read or parsed, `None` if it can. It has no sections then.
+    pub error: Option<String>,
+}
+
+/// Returns the config files governing the files below `root`, see `find_all_configs_with`.
+pub fn find_all_configs(root: &Path) -> Result<Vec<ConfigFileInfo>, Error> {
+    find_all_configs_with(root, &LookupOptions::default(), &WalkOptions::default(), &mut vec![])
+}
+
+/// Returns the config files named like `lookup` looks for them governing the files below
+/// `root`: first those of parent directories a lookup of `root` reads, farthest first, then
+/// those below `root` in the order `walk_files` lists them. A relative `root` is taken from
+/// the current directory, and the paths returned are canonical when `lookup` canonicalizes.
+///
+/// Only failing to list the files is an error, config files that can't be parsed are
+/// returned with their `error`. Directories skipped as `walk` allows are appended to
+/// `warnings`.
+pub fn find_all_configs_with(root: &Path,
+                             lookup: &LookupOptions,
+                             walk: &WalkOptions,
+                             warnings: &mut Vec<Warning>)
+                             -> Result<Vec<ConfigFileInfo>, Error> {
+    // In the form of the paths crawled, to tell which config files are in `root` itself and
+    // which are outside of `stop_dir`
+    let root = if root.is_absolute() {
+        root.to_path_buf()
+    } else {
+        env::current_dir()?.join(root)
+    };
+    let root = lookup_path(&root, lookup)?;
+    let root = root.as_path();
+    let cache = ConfigCache::new();
+    let info = |path: PathBuf, inherited: bool| {
+        let (is_root, sections, error) = match parsed_config(&path, lookup, &mut vec![], &cache) {
+            Ok(parsed) => {
+                let is_root = parsed.ini
+                    .get_from::<String>(None, "root")
+                    .is_some_and(|root| root.eq_ignore_ascii_case("true"));
+                (is_root, parsed.ini.sections().flatten().count(), None)
+            }
+            Err(err) => (false, 0, Some(err.to_string())),
+        };
+        ConfigFileInfo {
+            path,
+            inherited,
+            is_root,
+            sections,
+            error,
+        }
+    };
+
+    let mut inherited = vec![];
+    // Looking up a file in `root` reads the config files of `root` and of its parents
+    let mut root_dir = None;
+    let stop_dir = stop_dir(lookup);
+    for path in crawl_dir_paths(root, lookup)? {
+        let outside_stop_dir = stop_dir.as_ref().is_some_and(|dir| !path.starts_with(dir));
+        if outside_stop_dir || root_dir.is_some() && path.parent() != root_dir.as_deref() {
+            break;
+        }
+        let found = info(path, true);
+        if found.is_root {
+            root_dir = found.path.parent().map(Path::to_path_buf);
+        }
+        if found.path.parent() != Some(root) {
+            inherited.push(found);
+        }
+    }
+    inherited.reverse();
+    let below = find_config_files(root, lookup, walk, warnings)?
+        .into_iter().map(|path| info(path, false));
+    Ok(inherited.into_iter().chain(below).collect())
+}
+
+/// Returns the config files below `root`, named like `lookup` looks for them, sorted.
+/// Directories skipped as `walk` allows are appended to `warnings`.
+pub fn find_config_files(root: &Path,
+                         lookup: &LookupOptions,
+                         walk: &WalkOptions,
+                         warnings: &mut Vec<Warning>)
+                         -> Result<Vec<PathBuf>, Error> {
+    Ok(walk_files(root, walk, warnings)?
+        .into_iter()
+        .filter(|file| {
+            let name = file.file_name().and_then(|name| name.to_str()).unwrap_or("");
+            name == lookup.conffile || lookup.conffile_overrides.iter().any(|n| n == name)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::FixtureBuilder;
+
+    #[test]
+    fn finds_the_config_files_governing_a_tree() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig", "root = true\n[*]\nindent_size = 2\n")
+            .config("repo/.editorconfig", "[*]\ncharset = utf-8\n[*.md]\nindent_size = 4\n")
+            .config("repo/src/.editorconfig", "root = true\n[*.rs]\nindent_size = 4\n")
+            .config("repo/docs/.editorconfig", "[*]\n= 2\n")
+            .build()
+            .unwrap();
+        let lookup = fixture.lookup_options();
+        let walk = WalkOptions::default();
+        let configs = find_all_configs_with(&fixture.path("repo"), &lookup, &walk, &mut vec![]).unwrap();
+        assert!(find_all_configs_with(&fixture.path("repo/src"), &lookup, &walk, &mut vec![])
+            .unwrap()
+            .iter()
+            .all(|info| !info.inherited));
+        let found: Vec<_> = configs.iter()
+            .map(|info| (info.path.clone(), info.inherited, info.is_root, info.sections))
+            .collect();
+        assert_eq!(found,
+                   [(fixture.path(".editorconfig"), true, true, 1),
+                    (fixture.path("repo/.editorconfig"), false, false, 2),
+                    (fixture.path("repo/docs/.editorconfig"), false, false, 0),
+                    (fixture.path("repo/src/.editorconfig"), false, true, 1)]);
+        assert!(configs[2].error.is_some());
+        assert!(configs.iter().filter(|info| info.path != configs[2].path).all(|info| {
+            info.error.is_none()
+        }));
+    }
+
+    #[test]
+    fn takes_a_relative_root_from_the_current_directory() {
+        let lookup = LookupOptions {
+            stop_dir: Some(PathBuf::from("test_files/overrides")),
+            ..LookupOptions::default()
+        };
+        let configs = find_all_configs_with(Path::new("test_files/overrides/foo"),
+                                            &lookup,
+                                            &WalkOptions::default(),
+                                            &mut vec![])
+            .unwrap();
+        let dir = Path::new("test_files/overrides").canonicalize().unwrap();
+        let found: Vec<_> = configs.iter().map(|info| (info.path.clone(), info.inherited)).collect();
+        // test_files/.editorconfig is outside of the stop directory
+        assert_eq!(found,
+                   [(dir.join(".editorconfig"), true), (dir.join("foo/.editorconfig"), false)]);
+    }
+}
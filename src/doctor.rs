@@ -0,0 +1,265 @@
+//! A scored report on the config files of a whole tree, combining the other checks so
+//! maintainers see what to improve first.
+
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use {classify, expensive_patterns, get_config_with, load_ini, redundant_settings, walk_files,
+     AuditLog, Error, GitEolSettings, LookupOptions, WalkOptions};
+
+/// How much a finding lowers the score, and how soon it should be dealt with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Worth cleaning up, nothing is wrong.
+    Low,
+    /// Some files may not get the settings they should.
+    Medium,
+    /// Files get wrong settings, or none at all.
+    High,
+}
+
+impl Severity {
+    /// The points a finding of this severity takes off the score.
+    pub fn penalty(self) -> u32 {
+        match self {
+            Severity::Low => 1,
+            Severity::Medium => 5,
+            Severity::High => 20,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        })
+    }
+}
+
+/// A problem found by `diagnose`, with what to do about it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    /// The check which found it: `lint`, `coverage`, `redundancy` or `environment`.
+    pub category: &'static str,
+    /// The config file or directory it's about.
+    pub file: PathBuf,
+    pub message: String,
+    pub recommendation: String,
+}
+
+/// The findings of `diagnose`, most severe first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Number of files people write below the directory, which the coverage is about.
+    pub files: usize,
+    /// The config files applying to them.
+    pub config_files: Vec<PathBuf>,
+    pub findings: Vec<Finding>,
+}
+
+impl HealthReport {
+    /// 100 minus the penalty of every finding, and never below 0.
+    pub fn score(&self) -> u32 {
+        let penalty: u32 = self.findings.iter().map(|finding| finding.severity.penalty()).sum();
+        100u32.saturating_sub(penalty)
+    }
+}
+
+/// Checks the config files applying to the files below `root`:
+///
+/// - lint: config files that can't be parsed, problems found while looking files up and
+///   expensive section patterns,
+/// - coverage: files getting no properties at all, and the lack of a `root = true`,
+/// - redundancy: properties restating an inherited value, see `redundant_settings`,
+/// - environment: git settings converting line endings to something else than
+///   `end_of_line`.
+///
+/// Generated, vendored and binary files are left out, as they follow the conventions of
+/// whatever made them. Only failing to list the files is an error, directories skipped as
+/// `walk` allows are coverage findings.
+pub fn diagnose(root: &Path, lookup: &LookupOptions, walk: &WalkOptions) -> Result<HealthReport, Error> {
+    let mut lookup = lookup.clone();
+    let audit = Arc::new(AuditLog::new());
+    lookup.audit = Some(audit.clone());
+    let git = GitEolSettings::read(root);
+
+    let mut report = HealthReport::default();
+    let mut uncovered = 0;
+    // Reported once, however many files they affect
+    let mut seen = BTreeSet::new();
+    let mut add = |finding: Finding, findings: &mut Vec<Finding>| {
+        if seen.insert((finding.file.clone(), finding.message.clone())) {
+            findings.push(finding);
+        }
+    };
+    let mut skipped = vec![];
+    let files = walk_files(root, walk, &mut skipped)?;
+    for warning in skipped {
+        add(Finding {
+                severity: Severity::Medium,
+                category: "coverage",
+                file: warning.file,
+                message: format!("directory {}", warning.message),
+                recommendation: "make the directory readable so its files can be checked"
+                    .to_string(),
+            },
+            &mut report.findings);
+    }
+    for file in files {
+        let name = file.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        if name == lookup.conffile || lookup.conffile_overrides.iter().any(|n| n == name) ||
+           !classify(&file).is_authored() {
+            continue;
+        }
+        report.files += 1;
+        let mut warnings = vec![];
+        let properties = match get_config_with(&file, &lookup, &mut warnings) {
+            Ok(properties) => properties,
+            Err(err) => {
+                let (conf_file, message) = match err {
+                    Error::Parse { file: Some(ref conf_file), .. } => {
+                        (conf_file.clone(), "can't be parsed".to_string())
+                    }
+                    _ => (file.clone(), "can't be looked up".to_string()),
+                };
+                add(Finding {
+                        severity: Severity::High,
+                        category: "lint",
+                        file: conf_file,
+                        message: format!("{}: {}", message, err),
+                        recommendation: "fix the config file, or read it with --repair"
+                            .to_string(),
+                    },
+                    &mut report.findings);
+                continue;
+            }
+        };
+        for warning in warnings {
+            add(Finding {
+                    severity: Severity::Medium,
+                    category: "lint",
+                    file: warning.file,
+                    message: warning.message,
+                    recommendation: "fix the config file so no warning is needed".to_string(),
+                },
+                &mut report.findings);
+        }
+        if properties.is_empty() {
+            uncovered += 1;
+        }
+        let git_conflict = match (git.as_ref(), properties.get("end_of_line")) {
+            (Some(git), Some(end_of_line)) => {
+                git.conflict_with(end_of_line).map(|conflict| (end_of_line.clone(), conflict))
+            }
+            _ => None,
+        };
+        if let Some((end_of_line, (checkout_eol, setting))) = git_conflict {
+            add(Finding {
+                    severity: Severity::High,
+                    category: "environment",
+                    file: root.to_path_buf(),
+                    message: format!("end_of_line = {} but git checks files out with {} \
+                                      because of {}",
+                                     end_of_line,
+                                     checkout_eol,
+                                     setting),
+                    recommendation: "unset the git setting, or add `* text eol=...` to \
+                                     .gitattributes"
+                        .to_string(),
+                },
+                &mut report.findings);
+        }
+    }
+    report.config_files = audit.files_read();
+
+    if uncovered > 0 {
+        let severity = if uncovered * 2 > report.files {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+        report.findings.push(Finding {
+            severity,
+            category: "coverage",
+            file: root.to_path_buf(),
+            message: format!("{} of {} files get no properties", uncovered, report.files),
+            recommendation: "add sections matching them, e.g. `[*]`".to_string(),
+        });
+    }
+    let has_root = report.config_files
+        .iter()
+        .filter_map(|conf_file| load_ini(conf_file).ok())
+        .any(|ini| {
+            ini.get_from::<String>(None, "root").is_some_and(|root| root.eq_ignore_ascii_case("true"))
+        });
+    if !has_root && !report.config_files.is_empty() {
+        report.findings.push(Finding {
+            severity: Severity::Medium,
+            category: "coverage",
+            file: root.to_path_buf(),
+            message: "no config file sets root = true".to_string(),
+            recommendation: "add `root = true` to the top config file, so settings of parent \
+                             directories don't apply"
+                .to_string(),
+        });
+    }
+    for conf_file in &report.config_files {
+        for cost in expensive_patterns(conf_file).unwrap_or_default() {
+            report.findings.push(Finding {
+                severity: Severity::Low,
+                category: "lint",
+                file: conf_file.clone(),
+                message: format!("[{}] is expensive to match", cost.pattern),
+                recommendation: cost.issues
+                    .iter()
+                    .map(|issue| issue.suggestion.clone())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            });
+        }
+        for setting in redundant_settings(conf_file, &lookup).unwrap_or_default() {
+            report.findings.push(Finding {
+                severity: Severity::Low,
+                category: "redundancy",
+                file: conf_file.clone(),
+                message: format!("[{}] {} = {} restates the value inherited from {}",
+                                 setting.section,
+                                 setting.key,
+                                 setting.value,
+                                 setting.inherited_from.display()),
+                recommendation: "delete it, e.g. with check --redundant --fix".to_string(),
+            });
+        }
+    }
+    report.findings.sort_by_key(|finding| Reverse(finding.severity));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_the_config_files_of_a_tree() {
+        let root = Path::new("test_files/doctor").canonicalize().unwrap();
+        let report = diagnose(&root, &LookupOptions::default(), &WalkOptions::default()).unwrap();
+        assert_eq!(report.files, 2);
+        assert_eq!(report.config_files.len(), 2);
+        let found: Vec<_> = report.findings
+            .iter()
+            .map(|finding| (finding.severity, finding.category))
+            .collect();
+        assert_eq!(found,
+                   [(Severity::Medium, "coverage"), (Severity::Low, "lint"),
+                    (Severity::Low, "redundancy")]);
+        assert_eq!(report.findings[0].message, "1 of 2 files get no properties");
+        assert_eq!(report.score(), 93);
+    }
+}
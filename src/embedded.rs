@@ -0,0 +1,61 @@
+//! Config files built into the binary, see the `embed!` macro of the `embed` feature.
+
+use ordermap::OrderMap;
+
+use ini::Ini;
+use {clean_properties, match_sections, LookupOptions};
+
+/// A section name, `None` for the preamble before the first section, and its properties.
+pub type EmbeddedSection = (Option<&'static str>, &'static [(&'static str, &'static str)]);
+
+/// The sections of a config file, parsed at compile time by `embed!`.
+///
+/// ```ignore
+/// extern crate editorconfig;
+///
+/// use editorconfig::{embed, EmbeddedConfig};
+///
+/// static DEFAULTS: EmbeddedConfig = embed!("defaults.editorconfig");
+///
+/// let properties = DEFAULTS.properties_for("src/main.rs");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmbeddedConfig {
+    pub sections: &'static [EmbeddedSection],
+}
+
+impl EmbeddedConfig {
+    /// Returns the properties this config gives to `relative_path`, like `match_file`.
+    pub fn properties_for(&self, relative_path: &str) -> OrderMap<String, String> {
+        let mut ini = Ini::new();
+        for &(name, properties) in self.sections {
+            for &(key, value) in properties {
+                ini.set_to(name, key.to_string(), value.to_string());
+            }
+        }
+        let properties = match_sections(&ini,
+                                        relative_path,
+                                        &LookupOptions::default(),
+                                        &mut |_| {},
+                                        &mut |_, _| {});
+        clean_properties(&properties, &LookupOptions::default(), &mut |_| {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_embedded_sections() {
+        let config = EmbeddedConfig {
+            sections: &[(None, &[("root", "true")]),
+                        (Some("*"), &[("indent_style", "Space"), ("indent_size", "2")]),
+                        (Some("*.rs"), &[("indent_size", "4")])],
+        };
+        let properties = config.properties_for("src/main.rs");
+        assert_eq!(properties.get("indent_style").unwrap(), "space");
+        assert_eq!(properties.get("indent_size").unwrap(), "4");
+        assert!(properties.get("root").is_none());
+    }
+}
@@ -0,0 +1,83 @@
+//! Environment variables pointing lookups at other config files, so users can try a config
+//! without touching the repository. Lookups only read them when asked to, see `EnvOverrides`.
+
+use std::env;
+use std::path::PathBuf;
+
+use LookupOptions;
+
+/// Name of the config files to look for instead of `.editorconfig`, like `-f`.
+pub const CONF_FILENAME_VAR: &str = "EDITORCONFIG_CONF_FILENAME";
+/// Path of a config file to read after all the others, see `LookupOptions::global_config`.
+pub const GLOBAL_CONFIG_VAR: &str = "EDITORCONFIG_GLOBAL_CONFIG";
+/// The variables `EnvOverrides::from_env` reads.
+pub const ENV_OVERRIDE_VARS: &[&str] = &[CONF_FILENAME_VAR, GLOBAL_CONFIG_VAR];
+
+/// What the environment variables ask for, empty variables being unset.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvOverrides {
+    pub conf_filename: Option<String>,
+    pub global_config: Option<PathBuf>,
+}
+
+impl EnvOverrides {
+    /// Reads the variables of the process.
+    pub fn from_env() -> EnvOverrides {
+        EnvOverrides::from_vars(|name| env::var(name).ok())
+    }
+
+    /// Reads the variables from `var`, which returns the value of a variable by name.
+    pub fn from_vars<F: Fn(&str) -> Option<String>>(var: F) -> EnvOverrides {
+        let var = |name| var(name).filter(|value| !value.is_empty());
+        EnvOverrides {
+            conf_filename: var(CONF_FILENAME_VAR),
+            global_config: var(GLOBAL_CONFIG_VAR).map(PathBuf::from),
+        }
+    }
+
+    /// Sets the options of `lookup` the variables override.
+    pub fn apply(&self, lookup: &mut LookupOptions) {
+        if let Some(ref name) = self.conf_filename {
+            lookup.conffile = name.clone();
+        }
+        if let Some(ref path) = self.global_config {
+            lookup.global_config = Some(path.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::FixtureBuilder;
+    use get_config_with;
+
+    #[test]
+    fn points_lookups_at_other_config_files() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig", "root = true\n[*]\nindent_size = 2\n")
+            .config(".editorconfig.try", "root = true\n[*]\nindent_size = 8\n")
+            .config("global/editorconfig", "[*.rs]\nindent_size = 4\ncharset = utf-8\n")
+            .file("src/lib.rs")
+            .build()
+            .unwrap();
+        let global = fixture.path("global/editorconfig");
+        let vars = |name: &str| match name {
+            CONF_FILENAME_VAR => Some(".editorconfig.try".to_string()),
+            GLOBAL_CONFIG_VAR => Some(global.to_str().unwrap().to_string()),
+            _ => None,
+        };
+        let overrides = EnvOverrides::from_vars(vars);
+        let mut lookup = fixture.lookup_options();
+        overrides.apply(&mut lookup);
+        let properties = get_config_with(&fixture.path("src/lib.rs"), &lookup, &mut vec![])
+            .unwrap();
+        assert_eq!(properties["indent_size"], "8");
+        assert_eq!(properties["charset"], "utf-8");
+
+        let unset = EnvOverrides::from_vars(|name| {
+            if name == CONF_FILENAME_VAR { Some(String::new()) } else { None }
+        });
+        assert_eq!(unset, EnvOverrides::default());
+    }
+}
@@ -0,0 +1,185 @@
+//! Settings outside of config files that silently change what editors see, like git
+//! converting line endings at checkout, and which files a git sparse checkout leaves out.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The git settings converting line endings of checked out files.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GitEolSettings {
+    /// `core.autocrlf`: `true`, `input` or `false`.
+    pub autocrlf: Option<String>,
+    /// `core.eol`: `lf`, `crlf` or `native`.
+    pub eol: Option<String>,
+}
+
+impl GitEolSettings {
+    /// Reads the settings applying in `dir` by running `git config`. Returns `None` if git
+    /// can't be run.
+    pub fn read(dir: &Path) -> Option<GitEolSettings> {
+        let get = |key: &str| -> Option<Option<String>> {
+            let output = Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(["config", "--get", key])
+                .output()
+                .ok()?;
+            // Exit code 1 means the key isn't set
+            if !output.status.success() {
+                return Some(None);
+            }
+            Some(Some(String::from_utf8_lossy(&output.stdout).trim().to_lowercase()))
+        };
+        Some(GitEolSettings {
+            autocrlf: get("core.autocrlf")?,
+            eol: get("core.eol")?,
+        })
+    }
+
+    /// Returns the line ending of checked out files and the setting causing it, if these
+    /// settings convert line endings to something else than `end_of_line`.
+    pub fn conflict_with(&self, end_of_line: &str) -> Option<(&'static str, &'static str)> {
+        let checkout_eol = match self.autocrlf.as_deref() {
+            Some("true") => Some(("CRLF", "core.autocrlf = true")),
+            Some("input") => Some(("LF", "core.autocrlf = input")),
+            _ => {
+                match self.eol.as_deref() {
+                    Some("crlf") => Some(("CRLF", "core.eol = crlf")),
+                    Some("lf") => Some(("LF", "core.eol = lf")),
+                    _ => None,
+                }
+            }
+        };
+        checkout_eol.filter(|&(eol, _)| !eol.eq_ignore_ascii_case(end_of_line))
+    }
+}
+
+/// The files a git sparse checkout leaves out, although they may be on disk, like files
+/// restored by hand or written by builds in directories outside of its patterns. See
+/// `WalkOptions::sparse_checkout`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GitSparseCheckout {
+    /// The directory read.
+    pub dir: PathBuf,
+    /// The path of `dir` from the top of the work tree, ending with `/` unless it's empty.
+    pub prefix: String,
+    /// The files of the index marked skip-worktree, below `dir`.
+    pub skipped: HashSet<PathBuf>,
+    /// In cone mode, the directories checked out, from the top of the work tree. Files
+    /// directly in them, in their parents or at the top are checked out too.
+    pub cone: Option<Vec<String>>,
+}
+
+impl GitSparseCheckout {
+    /// Reads the sparse checkout of the repository `dir` is in by running `git`. Returns
+    /// `None` if git can't be run, `dir` isn't in a work tree or `core.sparseCheckout` is
+    /// off, as skip-worktree then only hides local changes of files which are there.
+    ///
+    /// Only cone mode patterns are matched against paths, the others are left to git, which
+    /// marks the files of the index they exclude as skip-worktree.
+    pub fn read(dir: &Path) -> Option<GitSparseCheckout> {
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(args)
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| output.stdout)
+        };
+        let enabled = |key: &str| {
+            git(&["config", "--get", "--type=bool", key])
+                .is_some_and(|value| String::from_utf8_lossy(&value).trim() == "true")
+        };
+        if !enabled("core.sparseCheckout") {
+            return None;
+        }
+        let prefix = git(&["rev-parse", "--show-prefix"])?;
+        let cone = if enabled("core.sparseCheckoutCone") {
+            let list = git(&["sparse-checkout", "list"])?;
+            Some(String::from_utf8_lossy(&list).lines().map(str::to_string).collect())
+        } else {
+            None
+        };
+        Some(GitSparseCheckout {
+            dir: dir.to_path_buf(),
+            prefix: String::from_utf8_lossy(&prefix).trim_end_matches('\n').to_string(),
+            skipped: skipped_files(dir, &git(&["ls-files", "-t", "-z"])?),
+            cone,
+        })
+    }
+
+    /// Whether `file`, a path below `dir`, is left out of the checkout.
+    pub fn excludes(&self, file: &Path) -> bool {
+        if self.skipped.contains(file) {
+            return true;
+        }
+        let (cone, relative) = match (self.cone.as_ref(), file.strip_prefix(&self.dir)) {
+            (Some(cone), Ok(relative)) => (cone, relative),
+            _ => return false,
+        };
+        let components: Vec<_> =
+            relative.components().map(|part| part.as_os_str().to_string_lossy()).collect();
+        let path = format!("{}{}", self.prefix, components.join("/"));
+        let parent = path.rfind('/').map_or("", |end| &path[..end]);
+        let checked_out = |dir: &String| {
+            path.starts_with(&format!("{}/", dir)) || dir.starts_with(&format!("{}/", parent))
+        };
+        !parent.is_empty() && !cone.iter().any(checked_out)
+    }
+}
+
+/// The files of the output of `git ls-files -t -z` run in `dir` marked skip-worktree. Its
+/// entries are a tag, a space and a path relative to `dir`, `S` tagging those files.
+fn skipped_files(dir: &Path, listing: &[u8]) -> HashSet<PathBuf> {
+    listing.split(|&byte| byte == 0)
+        .filter_map(|entry| entry.strip_prefix(b"S "))
+        .map(|path| dir.join(&*String::from_utf8_lossy(path)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(autocrlf: Option<&str>, eol: Option<&str>) -> GitEolSettings {
+        GitEolSettings {
+            autocrlf: autocrlf.map(str::to_string),
+            eol: eol.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn detects_conflicting_line_endings() {
+        assert!(settings(Some("true"), None).conflict_with("lf").is_some());
+        assert!(settings(Some("true"), None).conflict_with("crlf").is_none());
+        assert!(settings(Some("input"), None).conflict_with("crlf").is_some());
+        // core.eol only matters without core.autocrlf
+        assert!(settings(Some("true"), Some("lf")).conflict_with("crlf").is_none());
+        assert!(settings(Some("false"), Some("crlf")).conflict_with("lf").is_some());
+        assert!(settings(None, Some("native")).conflict_with("lf").is_none());
+        assert!(settings(None, None).conflict_with("cr").is_none());
+    }
+
+    #[test]
+    fn leaves_out_skipped_files_and_files_outside_of_the_cone() {
+        let dir = Path::new("/repo/src");
+        let sparse = GitSparseCheckout {
+            dir: dir.to_path_buf(),
+            prefix: "src/".to_string(),
+            skipped: skipped_files(dir, b"H a/x.txt\0S b/y.txt\0S b/c d.txt\0"),
+            cone: None,
+        };
+        assert!(sparse.excludes(&dir.join("b/y.txt")) && sparse.excludes(&dir.join("b/c d.txt")));
+        assert!(!sparse.excludes(&dir.join("a/x.txt")) && !sparse.excludes(&dir.join("e/z.txt")));
+        let sparse = GitSparseCheckout { cone: Some(vec!["src/a/b".to_string()]), ..sparse };
+        for kept in &["a/b/c/x.txt", "a/x.txt", "x.txt"] {
+            assert!(!sparse.excludes(&dir.join(kept)), "{}", kept);
+        }
+        for left_out in &["e/z.txt", "a/bc/x.txt", "b/y.txt"] {
+            assert!(sparse.excludes(&dir.join(left_out)), "{}", left_out);
+        }
+    }
+}
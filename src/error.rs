@@ -0,0 +1,152 @@
+//! What can go wrong when looking up a configuration.
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::{PathBuf, StripPrefixError};
+
+use ini;
+
+/// The error of every fallible function of the crate. New variants may be added in minor
+/// versions, so matches outside of the crate need a wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A file or directory couldn't be read.
+    Io(io::Error),
+    /// A config file isn't valid INI.
+    Parse {
+        /// The config file, `None` when parsing a string.
+        file: Option<PathBuf>,
+        line: usize,
+        col: usize,
+        message: String,
+    },
+    /// A pattern can't be used to match paths.
+    InvalidGlob { pattern: String, message: String },
+    /// The path looked up isn't below the directory of a config file applying to it.
+    PathPrefix(StripPrefixError),
+    /// No path is mapped for a URI, see `UriMap`.
+    UnmappedUri(String),
+    /// No config file applies to a path, with `LookupOptions::require_config`.
+    MissingConfig(PathBuf),
+    /// A setting of `editorconfig-tool.ini` is unknown or has an invalid value.
+    InvalidSetting {
+        /// The tool config file, `None` when parsing a string.
+        file: Option<PathBuf>,
+        message: String,
+    },
+}
+
+impl Error {
+    /// Records which file a parse error or an invalid setting comes from.
+    pub(crate) fn in_file<P: Into<PathBuf>>(self, path: P) -> Error {
+        match self {
+            Error::Parse { line, col, message, .. } => {
+                Error::Parse {
+                    file: Some(path.into()),
+                    line,
+                    col,
+                    message,
+                }
+            }
+            Error::InvalidSetting { message, .. } => {
+                Error::InvalidSetting {
+                    file: Some(path.into()),
+                    message,
+                }
+            }
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => err.fmt(f),
+            Error::Parse { ref file, line, col, ref message } => {
+                if let Some(ref file) = *file {
+                    write!(f, "{}:", file.display())?;
+                }
+                write!(f, "{}:{} {}", line, col, message)
+            }
+            Error::InvalidGlob { ref pattern, ref message } => {
+                write!(f, "invalid pattern `{}`: {}", pattern, message)
+            }
+            Error::PathPrefix(ref err) => err.fmt(f),
+            Error::UnmappedUri(ref uri) => write!(f, "no path is mapped for {}", uri),
+            Error::MissingConfig(ref path) => {
+                write!(f, "no config file applies to {}", path.display())
+            }
+            Error::InvalidSetting { ref file, ref message } => {
+                if let Some(ref file) = *file {
+                    write!(f, "{}: ", file.display())?;
+                }
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::PathPrefix(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<StripPrefixError> for Error {
+    fn from(err: StripPrefixError) -> Error {
+        Error::PathPrefix(err)
+    }
+}
+
+impl From<ini::Error> for Error {
+    fn from(err: ini::Error) -> Error {
+        Error::Parse {
+            file: None,
+            line: err.line,
+            col: err.col,
+            message: err.msg,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as StdError;
+    use std::path::Path;
+
+    use load_ini;
+
+    #[test]
+    fn keeps_the_cause_of_errors() {
+        let err = Error::from(ini::Ini::load_from_str("= 1\n").unwrap_err());
+        let err = err.in_file("a/.editorconfig");
+        match err {
+            Error::Parse { ref file, ref message, .. } => {
+                assert_eq!(file.as_ref().map(PathBuf::as_path),
+                           Some(Path::new("a/.editorconfig")));
+                assert_eq!(message, "Missing key");
+            }
+            ref err => panic!("expected a parse error, got {:?}", err),
+        }
+        assert!(err.to_string().starts_with("a/.editorconfig:"));
+        assert!(err.source().is_none());
+
+        let err = load_ini(Path::new("test_files/missing/.editorconfig")).unwrap_err();
+        let source = err.source().and_then(|source| source.downcast_ref::<io::Error>());
+        assert_eq!(source.map(io::Error::kind), Some(io::ErrorKind::NotFound));
+    }
+}
@@ -0,0 +1,289 @@
+//! The file access lookups go through, for hosts whose files aren't on the local file system,
+//! like language servers with unsaved buffers or WASM hosts.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+/// Reads the files lookups need. `RealFiles` reads them from the local file system.
+pub trait FileProvider: Send + Sync {
+    /// Whether there's a file or directory at `path`.
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Reads the whole file at `path`.
+    fn read(&self, path: &Path) -> io::Result<String>;
+
+    /// Makes `path` absolute, resolving `..` and symbolic links.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// Lists the names of the entries of the directory `dir`, only needed to find config
+    /// files named in another case. Fails with `Unsupported` by default.
+    fn file_names(&self, _dir: &Path) -> io::Result<Vec<String>> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "listing directories isn't supported"))
+    }
+
+    /// What tells whether the file at `path` changed since it was read, used to parse cached
+    /// config files again. `None` by default, for files that don't change or can't be told.
+    fn stamp(&self, _path: &Path) -> Option<FileStamp> {
+        None
+    }
+
+    /// The ID of the file system holding `path`, to stop lookups at mount points. `None` by
+    /// default, for single file systems or where it can't be told.
+    fn device(&self, _path: &Path) -> Option<u64> {
+        None
+    }
+
+    /// The user ID owning the file or directory at `path`, for
+    /// `LookupOptions::trust_same_owner`. `None` by default, where files have no owner, so
+    /// that no config file is rejected for its owner.
+    fn owner(&self, _path: &Path) -> Option<u32> {
+        None
+    }
+}
+
+/// The modification time and size of a file: when either differs, the file changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileStamp {
+    pub modified: Option<SystemTime>,
+    pub len: u64,
+}
+
+impl fmt::Debug for dyn FileProvider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("FileProvider")
+    }
+}
+
+/// The local file system, what lookups use by default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RealFiles;
+
+impl FileProvider for RealFiles {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+
+    fn file_names(&self, dir: &Path) -> io::Result<Vec<String>> {
+        let mut names = vec![];
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                names.push(name.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    fn stamp(&self, path: &Path) -> Option<FileStamp> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(FileStamp {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+
+    #[cfg(unix)]
+    fn device(&self, path: &Path) -> Option<u64> {
+        use std::os::unix::fs::MetadataExt;
+        fs::metadata(path).ok().map(|metadata| metadata.dev())
+    }
+
+    #[cfg(unix)]
+    fn owner(&self, path: &Path) -> Option<u32> {
+        use std::os::unix::fs::MetadataExt;
+        fs::metadata(path).ok().map(|metadata| metadata.uid())
+    }
+}
+
+/// Files held in memory, for tests and hosts with their own storage. Directories exist
+/// when a file below them does, and paths are already canonical.
+///
+/// ```
+/// use std::path::Path;
+/// use std::sync::Arc;
+/// use editorconfig::{get_config_with, LookupOptions, MemoryFiles};
+///
+/// let mut files = MemoryFiles::new();
+/// files.insert("/repo/.editorconfig", "root = true\n[*.rs]\nindent_size = 4\n");
+/// let lookup = LookupOptions { files: Arc::new(files), ..LookupOptions::default() };
+/// let properties = get_config_with(Path::new("/repo/src/lib.rs"), &lookup, &mut vec![]).unwrap();
+/// assert_eq!(properties.get("indent_size").unwrap(), "4");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemoryFiles {
+    files: HashMap<PathBuf, String>,
+}
+
+impl MemoryFiles {
+    pub fn new() -> MemoryFiles {
+        MemoryFiles::default()
+    }
+
+    /// Adds the file at `path`, replacing the one that was there.
+    pub fn insert<P: Into<PathBuf>, S: Into<String>>(&mut self, path: P, contents: S) {
+        self.files.insert(path.into(), contents.into());
+    }
+
+    /// Removes the file at `path`, returning its contents.
+    pub fn remove(&mut self, path: &Path) -> Option<String> {
+        self.files.remove(path)
+    }
+}
+
+impl FileProvider for MemoryFiles {
+    fn exists(&self, path: &Path) -> bool {
+        self.files.keys().any(|file| file.starts_with(path))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<String> {
+        self.files.get(path).cloned().ok_or_else(|| not_found(path))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if self.exists(path) {
+            Ok(path.to_path_buf())
+        } else {
+            Err(not_found(path))
+        }
+    }
+
+    fn file_names(&self, dir: &Path) -> io::Result<Vec<String>> {
+        Ok(self.files
+            .keys()
+            .filter(|file| file.parent() == Some(dir))
+            .filter_map(|file| file.file_name()?.to_str().map(str::to_string))
+            .collect())
+    }
+}
+
+/// Files held in memory shadowing the ones of another provider, like the unsaved buffers of
+/// an editor shadow the files on disk. See `Resolver::set_overlay`.
+///
+/// Overlays can be changed while lookups use them, and clones share them.
+#[derive(Clone, Debug)]
+pub struct OverlayFiles {
+    base: Arc<dyn FileProvider>,
+    overlays: Arc<RwLock<HashMap<PathBuf, String>>>,
+}
+
+impl OverlayFiles {
+    /// Shadows the files of `base`, with no overlay yet.
+    pub fn new(base: Arc<dyn FileProvider>) -> OverlayFiles {
+        OverlayFiles {
+            base,
+            overlays: Arc::default(),
+        }
+    }
+
+    /// Reads `contents` instead of the file at `path`, whether or not there is one.
+    pub fn insert<P: Into<PathBuf>, S: Into<String>>(&self, path: P, contents: S) {
+        self.overlays.write().unwrap().insert(path.into(), contents.into());
+    }
+
+    /// Reads the file at `path` from the base provider again, returning the overlay.
+    pub fn remove(&self, path: &Path) -> Option<String> {
+        self.overlays.write().unwrap().remove(path)
+    }
+
+    /// Reads every file from the base provider again.
+    pub fn clear(&self) {
+        self.overlays.write().unwrap().clear();
+    }
+}
+
+impl FileProvider for OverlayFiles {
+    fn exists(&self, path: &Path) -> bool {
+        self.overlays.read().unwrap().keys().any(|file| file.starts_with(path)) ||
+        self.base.exists(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<String> {
+        match self.overlays.read().unwrap().get(path) {
+            Some(contents) => Ok(contents.clone()),
+            None => self.base.read(path),
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.base.canonicalize(path).or_else(|err| {
+            if self.exists(path) { Ok(path.to_path_buf()) } else { Err(err) }
+        })
+    }
+
+    fn file_names(&self, dir: &Path) -> io::Result<Vec<String>> {
+        let mut names = self.base.file_names(dir).unwrap_or_default();
+        for file in self.overlays.read().unwrap().keys().filter(|file| file.parent() == Some(dir)) {
+            if let Some(name) = file.file_name().and_then(|name| name.to_str()) {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    /// Overlays are stamped `None`: only changing them makes their files change.
+    fn stamp(&self, path: &Path) -> Option<FileStamp> {
+        if self.overlays.read().unwrap().contains_key(path) {
+            None
+        } else {
+            self.base.stamp(path)
+        }
+    }
+
+    fn device(&self, path: &Path) -> Option<u64> {
+        self.base.device(path)
+    }
+
+    fn owner(&self, path: &Path) -> Option<u32> {
+        self.base.owner(path)
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} isn't in memory", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use {get_config_with, LookupOptions, WarningKind};
+
+    #[test]
+    fn looks_files_up_in_memory() {
+        let mut files = MemoryFiles::new();
+        files.insert("/repo/.editorconfig", "root = true\n[*]\nindent_style = tab\n");
+        files.insert("/repo/src/.EditorConfig", "[*.rs]\nindent_size = 4\n");
+        files.insert("/repo/src/lib.rs", "");
+        assert!(files.exists(Path::new("/repo/src")));
+        assert!(!files.exists(Path::new("/repo/docs")));
+        let lookup = LookupOptions {
+            files: Arc::new(files),
+            conffile_any_case: true,
+            ..LookupOptions::default()
+        };
+        let mut warnings = vec![];
+        let properties = get_config_with(Path::new("/repo/src/lib.rs"), &lookup, &mut warnings)
+            .unwrap();
+        assert_eq!(properties.get("indent_style").unwrap(), "tab");
+        assert_eq!(properties.get("indent_size").unwrap(), "4");
+        assert_eq!(warnings[0].kind, WarningKind::MiscasedName);
+    }
+}
@@ -0,0 +1,130 @@
+//! The rewrites `--fix` applies to config files, and checking that applying them again
+//! changes nothing, so CI can trust a fixed tree to stay fixed.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use {redundant_settings, remove_redundant, simplify_config, Error, LookupOptions, OverlayFiles};
+
+/// A rewrite of config files which keeps the properties of every file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Fix {
+    /// Deletes the properties `redundant_settings` finds, like `check --redundant --fix`.
+    RemoveRedundant,
+    /// Simplifies patterns and merges sections with `simplify_config`, like `lint --fix`.
+    Simplify,
+}
+
+impl Fix {
+    /// The contents of the config file at `conf_file` once fixed, reading it and its parents
+    /// through `lookup`.
+    pub fn apply(self, conf_file: &Path, lookup: &LookupOptions) -> Result<String, Error> {
+        let contents = lookup.files.read(conf_file)?;
+        match self {
+            Fix::RemoveRedundant => {
+                Ok(remove_redundant(&contents, &redundant_settings(conf_file, lookup)?))
+            }
+            Fix::Simplify => Ok(simplify_config(&contents)?.0),
+        }
+    }
+}
+
+/// A config file which `fix` changes again after fixing it once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsettledFix {
+    pub conf_file: PathBuf,
+    /// The contents after the first pass.
+    pub once: String,
+    /// The contents after the second pass.
+    pub twice: String,
+}
+
+/// Applies `fix` to the config files of `conf_files` in their order, then again, on copies
+/// held in memory: the files themselves aren't changed. Returns the files the second pass
+/// changed, which a fix should never do.
+pub fn verify_idempotent(fix: Fix,
+                         conf_files: &[PathBuf],
+                         lookup: &LookupOptions)
+                         -> Result<Vec<UnsettledFix>, Error> {
+    let copies = OverlayFiles::new(lookup.files.clone());
+    let lookup = LookupOptions { files: Arc::new(copies.clone()), ..lookup.clone() };
+    let mut passes = vec![];
+    for _ in 0..2 {
+        let mut fixed = vec![];
+        for conf_file in conf_files {
+            let contents = fix.apply(conf_file, &lookup)?;
+            copies.insert(conf_file.clone(), contents.as_str());
+            fixed.push(contents);
+        }
+        passes.push(fixed);
+    }
+    Ok(conf_files.iter()
+        .zip(passes[0].iter().zip(&passes[1]))
+        .filter(|&(_, (once, twice))| once != twice)
+        .map(|(conf_file, (once, twice))| {
+            UnsettledFix {
+                conf_file: conf_file.clone(),
+                once: once.clone(),
+                twice: twice.clone(),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use MemoryFiles;
+
+    /// A xorshift generator, so the configs tried are the same on every run.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+
+        fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+            choices[self.below(choices.len())]
+        }
+    }
+
+    /// A config file of a few sections from a small vocabulary, so that patterns and
+    /// properties often repeat those of other sections and of the parent.
+    fn random_config(rng: &mut Rng) -> String {
+        let patterns = ["*", "*.md", "*.{md,md}", "{a,a}.txt", "{x,{y,y}}", "*.{js,ts}",
+                        "src/*.rs", "{*.md,*.md}", "[ab]{c,c}", "*.{rs,rs,toml}"];
+        let keys = ["indent_size", "indent_style", "charset", "x"];
+        let values = ["2", "4", "space", "tab", "utf-8", "Latin1"];
+        let mut config = String::new();
+        for _ in 0..rng.below(6) {
+            config.push_str(&format!("[{}]\n", rng.pick(&patterns)));
+            for _ in 0..rng.below(3) {
+                config.push_str(&format!("{} = {}\n", rng.pick(&keys), rng.pick(&values)));
+            }
+        }
+        config
+    }
+
+    #[test]
+    fn fixing_twice_changes_nothing_more() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let conf_files = [PathBuf::from("/r/a/b/.editorconfig"),
+                          PathBuf::from("/r/a/.editorconfig"),
+                          PathBuf::from("/r/.editorconfig")];
+        for _ in 0..50 {
+            let mut files = MemoryFiles::new();
+            for conf_file in &conf_files {
+                files.insert(conf_file.clone(), random_config(&mut rng));
+            }
+            let lookup = LookupOptions { files: Arc::new(files), ..LookupOptions::default() };
+            for &fix in &[Fix::RemoveRedundant, Fix::Simplify] {
+                let unsettled = verify_idempotent(fix, &conf_files, &lookup).unwrap();
+                assert_eq!(unsettled, [], "{:?}", fix);
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Flattening the config files applying to a file into one, for tools that can't look for
+//! config files themselves.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use ini::escape_value;
+use {get_config_with, AuditLog, Error, LookupOptions, Warning};
+
+/// Returns a config file with `root = true` giving every file the properties `target`
+/// gets from its config files, with comments listing those files.
+///
+/// The properties are resolved once, so the result is only equivalent for `target`:
+/// sections of the original files that don't match it are left out.
+pub fn freeze_config(target: &Path,
+                     lookup: &LookupOptions,
+                     warnings: &mut Vec<Warning>)
+                     -> Result<String, Error> {
+    // Its own, as the one of `lookup` may list files other lookups read
+    let audit = Arc::new(AuditLog::new());
+    let lookup = LookupOptions { audit: Some(audit.clone()), ..lookup.clone() };
+    let properties = get_config_with(target, &lookup, warnings)?;
+    let mut frozen = format!("# Frozen by `editorconfig export` for {}, from:\n",
+                             target.display());
+    for file in audit.files_read() {
+        frozen.push_str(&format!("#   {}\n", file.display()));
+    }
+    frozen.push_str("root = true\n\n[*]\n");
+    for (key, value) in &properties {
+        frozen.push_str(&format!("{} = {}\n", key, escape_value(value)));
+    }
+    Ok(frozen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    use match_file;
+    use test_support::FixtureBuilder;
+
+    #[test]
+    fn freezes_the_chain_of_a_file() {
+        let target = env::current_dir().unwrap().join("test_files/overrides/foo/file.txt");
+        let lookup = LookupOptions {
+            conffile_overrides: vec![".editorconfig.user".to_string()],
+            ..LookupOptions::default()
+        };
+        let frozen = freeze_config(&target, &lookup, &mut vec![]).unwrap();
+        assert!(frozen.contains("foo/.editorconfig.user\n"));
+        assert!(frozen.contains("\nroot = true\n\n[*]\n"));
+        let expected = get_config_with(&target, &lookup, &mut vec![]).unwrap();
+        assert_eq!(match_file(&frozen, "anything.txt").unwrap(), expected);
+    }
+
+    #[test]
+    fn freezes_values_as_they_read_back() {
+        let fixture = FixtureBuilder::new()
+            .config("other/.editorconfig", "root = true\n")
+            .config("repo/.editorconfig", "root = true\n[*]\nx = a \\; b \\# c\ny = C:\\\\dir\n")
+            .file("other/file.txt")
+            .file("repo/file.txt")
+            .build()
+            .unwrap();
+        let lookup = LookupOptions {
+            audit: Some(Arc::new(AuditLog::new())),
+            ..fixture.lookup_options()
+        };
+        get_config_with(&fixture.path("other/file.txt"), &lookup, &mut vec![]).unwrap();
+        let target = fixture.path("repo/file.txt");
+        let frozen = freeze_config(&target, &lookup, &mut vec![]).unwrap();
+        // Not the files of lookups before
+        assert!(!frozen.contains("other/.editorconfig"), "{}", frozen);
+        let frozen_properties = match_file(&frozen, "anything.txt").unwrap();
+        assert_eq!(frozen_properties["x"], "a ; b # c");
+        assert_eq!(frozen_properties["y"], r"C:\dir");
+        assert_eq!(frozen_properties, get_config_with(&target, &lookup, &mut vec![]).unwrap());
+    }
+}
@@ -0,0 +1,231 @@
+//! Matching paths against section patterns without looking up a whole configuration.
+//!
+//! Patterns have the semantics of section names in config files:
+//!
+//! - `*` matches any characters but `/`, `**` any characters including `/`, and `?` any
+//!   one character,
+//! - `[abc]` and `[a-z]` match one of the characters, `[!abc]` one character but those,
+//! - `{a,b}` matches either alternative, `{1..10}` an integer in the range,
+//! - a pattern without `/` matches a file of that name in any directory, one with `/` is
+//!   relative to the directory of the config file, whether or not it starts with `/`.
+//!
+//! Paths are relative to the directory of the config file, use `/` as separator and have
+//! no leading `/`. Characters are Unicode code points and matching is case sensitive.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::{Mutex, OnceLock};
+
+use regex::Regex;
+
+use {matches_translated, translate_glob, Error, TranslatedGlob};
+
+/// A section pattern, like `*.{js,ts}` or `lib/**/*.rs`, ready to match paths.
+///
+/// ```
+/// let glob = editorconfig::Glob::new("src/**/*.{rs,toml}").unwrap();
+/// assert!(glob.matches("src/cli/main.rs"));
+/// assert!(!glob.matches("tests/src/main.rs"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Glob {
+    pattern: String,
+    translated: TranslatedGlob,
+    regex: Regex,
+}
+
+impl Glob {
+    /// Compiles `pattern`, with the semantics of a section name.
+    pub fn new(pattern: &str) -> Result<Glob, Error> {
+        Glob::from_translated(pattern, translate_glob(pattern))
+    }
+
+    /// Compiles a pattern already translated, e.g. with flags added to its regex.
+    pub(crate) fn from_translated(pattern: &str, translated: TranslatedGlob) -> Result<Glob, Error> {
+        match Regex::new(&translated.regex) {
+            Ok(regex) => {
+                Ok(Glob {
+                    pattern: pattern.to_string(),
+                    translated,
+                    regex,
+                })
+            }
+            Err(err) => {
+                Err(Error::InvalidGlob {
+                    pattern: pattern.to_string(),
+                    message: err.to_string(),
+                })
+            }
+        }
+    }
+
+    /// The pattern, as given to `new`.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether the pattern matches `path`, relative to the directory of the config file
+    /// and using `/` as separator.
+    pub fn matches(&self, path: &str) -> bool {
+        matches_translated(&self.regex, &self.translated, path)
+    }
+
+    /// The regex the pattern was translated to.
+    pub(crate) fn regex_source(&self) -> &str {
+        &self.translated.regex
+    }
+
+    /// Whether matches have numbers to check against ranges, which a `RegexSet` can't do.
+    pub(crate) fn has_numeric_ranges(&self) -> bool {
+        !self.translated.numeric_ranges.is_empty()
+    }
+}
+
+/// A pattern translated to a regex, see `translate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Translation {
+    /// Matches the paths the pattern matches, anchored at both ends. Its only capture
+    /// groups are the numbers of numeric ranges, all other groups are non-capturing.
+    pub regex: String,
+    /// The range the number of each capture group has to be in, in order. Ranges whose
+    /// ends don't fit an `i32` are empty.
+    pub numeric_ranges: Vec<RangeInclusive<i32>>,
+}
+
+/// Translates `pattern` to the regex this crate matches paths with, so other tools can put
+/// it in their own regex sets. A path matches if the regex does and, when the pattern has
+/// numeric ranges like `{1..10}`, every captured number is in its range.
+///
+/// ```
+/// let translation = editorconfig::glob::translate("log{1..3}.txt");
+/// assert_eq!(translation.numeric_ranges, [1..=3]);
+/// let regex = regex::Regex::new(&translation.regex).unwrap();
+/// assert_eq!(&regex.captures("old/log2.txt").unwrap()[1], "2");
+/// ```
+pub fn translate(pattern: &str) -> Translation {
+    let translated = translate_glob(pattern);
+    let numeric_ranges = translated.numeric_ranges
+        .iter()
+        .map(|range| {
+            let mut ends = range.split(r"\.\.").map(|end| end.parse::<i32>());
+            match (ends.next(), ends.next()) {
+                (Some(Ok(min)), Some(Ok(max))) => min..=max,
+                _ => RangeInclusive::new(1, 0),
+            }
+        })
+        .collect();
+    Translation {
+        regex: translated.regex,
+        numeric_ranges,
+    }
+}
+
+/// Whether `pattern` matches `path`, compiling the pattern for this call only. Use `Glob`
+/// to match many paths with the same pattern.
+///
+/// ```
+/// assert!(editorconfig::glob::matches("*.{js,ts}", "web/app.ts").unwrap());
+/// assert!(!editorconfig::glob::matches("/*.md", "docs/index.md").unwrap());
+/// ```
+pub fn matches(pattern: &str, path: &str) -> Result<bool, Error> {
+    Ok(Glob::new(pattern)?.matches(path))
+}
+
+impl fmt::Display for Glob {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+/// How many patterns the process-wide cache of `cached_glob` keeps.
+const GLOB_CACHE_CAPACITY: usize = 1024;
+
+/// Compiled patterns, dropping the least recently used ones past its capacity.
+struct GlobCache {
+    capacity: usize,
+    /// The compiled pattern, or the regex error, and when it was last used.
+    entries: HashMap<(String, bool), (Result<Glob, String>, u64)>,
+    clock: u64,
+}
+
+impl GlobCache {
+    fn new(capacity: usize) -> GlobCache {
+        GlobCache {
+            capacity,
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, pattern: &str, case_sensitive: bool) -> Result<Glob, String> {
+        self.clock += 1;
+        let key = (pattern.to_string(), case_sensitive);
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.1 = self.clock;
+            return entry.0.clone();
+        }
+        if self.entries.len() >= self.capacity {
+            let oldest = self.entries
+                .iter()
+                .min_by_key(|&(_, &(_, used))| used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        let mut translated = translate_glob(pattern);
+        if !case_sensitive {
+            translated.regex = format!("(?i){}", translated.regex);
+        }
+        let glob = Glob::from_translated(pattern, translated).map_err(|err| match err {
+            Error::InvalidGlob { message, .. } => message,
+            err => err.to_string(),
+        });
+        self.entries.insert(key, (glob.clone(), self.clock));
+        glob
+    }
+}
+
+/// Compiles a section pattern, or returns it from a cache shared by the whole process, as
+/// lookups of many files read the same config files over and over. The error is the
+/// message of the regex error.
+pub(crate) fn cached_glob(pattern: &str, case_sensitive: bool) -> Result<Glob, String> {
+    static CACHE: OnceLock<Mutex<GlobCache>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(GlobCache::new(GLOB_CACHE_CAPACITY)));
+    cache.lock().unwrap().get(pattern, case_sensitive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiles_patterns_once() {
+        let glob = Glob::new("file{1..3}.{txt,md}").unwrap();
+        assert!(glob.matches("file2.md"));
+        assert!(glob.matches("a/file3.txt"));
+        assert!(!glob.matches("file4.txt"));
+        assert_eq!(glob.to_string(), "file{1..3}.{txt,md}");
+        assert_eq!(translate("v{-2..99999999999}").numeric_ranges.len(), 1);
+        assert!(translate("v{-2..99999999999}").numeric_ranges[0].is_empty());
+        match Glob::new("(") {
+            Err(Error::InvalidGlob { ref pattern, .. }) => assert_eq!(pattern, "("),
+            result => panic!("expected an invalid glob, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn caches_the_most_recently_used_patterns() {
+        let mut cache = GlobCache::new(2);
+        assert!(cache.get("*.rs", true).unwrap().matches("lib.rs"));
+        assert!(cache.get("*.md", true).is_ok());
+        assert!(cache.get("*.rs", true).is_ok());
+        assert!(cache.get("(", true).is_err());
+        let mut cached: Vec<_> = cache.entries.keys().map(|key| key.0.as_str()).collect();
+        cached.sort();
+        assert_eq!(cached, ["(", "*.rs"]);
+        assert!(cache.get("*.RS", false).unwrap().matches("LIB.rs"));
+        assert!(cached_glob("*.rs", true).unwrap().matches("a/b.rs"));
+    }
+}
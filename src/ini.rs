@@ -25,7 +25,7 @@
 //! Ini
 
 use std::fs::{OpenOptions, File};
-use std::ops::{Index, IndexMut};
+use std::ops::{Index, IndexMut, Range};
 use std::char;
 use std::io::{self, Write, Read};
 use std::fmt::{self, Display};
@@ -57,28 +57,17 @@ pub enum EscapePolicy {
 
 impl EscapePolicy {
     fn escape_basics(&self) -> bool {
-        match *self {
-            EscapePolicy::Nothing => false,
-            _ => true,
-        }
+        !matches!(*self, EscapePolicy::Nothing)
     }
 
     fn escape_reserved(&self) -> bool {
-        match *self {
-            EscapePolicy::Reserved => true,
-            EscapePolicy::ReservedUnicode => true,
-            EscapePolicy::Everything => true,
-            _ => false,
-        }
+        matches!(*self,
+                 EscapePolicy::Reserved | EscapePolicy::ReservedUnicode | EscapePolicy::Everything)
     }
 
     fn escape_unicode(&self) -> bool {
-        match *self {
-            EscapePolicy::BasicsUnicode => true,
-            EscapePolicy::ReservedUnicode => true,
-            EscapePolicy::Everything => true,
-            _ => false,
-        }
+        matches!(*self,
+                 EscapePolicy::BasicsUnicode | EscapePolicy::ReservedUnicode | EscapePolicy::Everything)
     }
 
     /// Given a character this returns true if it should be escaped as
@@ -86,10 +75,10 @@ impl EscapePolicy {
     pub fn should_escape(&self, c: char) -> bool {
         match c {
             '\\' |
-            '\x00'...'\x1f' |
-            '\x7f'...'\u{00ff}' => self.escape_basics(),
+            '\x00'..='\x1f' |
+            '\x7f'..='\u{00ff}' => self.escape_basics(),
             ';' | '#' | '=' | ':' => self.escape_reserved(),
-            '\u{0080}'...'\u{FFFF}' => self.escape_unicode(),
+            '\u{0080}'..='\u{FFFF}' => self.escape_unicode(),
             _ => false,
         }
     }
@@ -124,9 +113,9 @@ fn escape_str(s: &str, policy: EscapePolicy) -> String {
         match c {
             '\\' => escaped.push_str("\\\\"),
             '\0' => escaped.push_str("\\0"),
-            '\x01'...'\x06' |
-            '\x0e'...'\x1f' |
-            '\x7f'...'\u{00ff}' => escaped.push_str(&format!("\\x{:04x}", c as isize)[..]),
+            '\x01'..='\x06' |
+            '\x0e'..='\x1f' |
+            '\x7f'..='\u{00ff}' => escaped.push_str(&format!("\\x{:04x}", c as isize)[..]),
             '\x07' => escaped.push_str("\\a"),
             '\x08' => escaped.push_str("\\b"),
             '\x0c' => escaped.push_str("\\f"),
@@ -134,7 +123,7 @@ fn escape_str(s: &str, policy: EscapePolicy) -> String {
             '\n' => escaped.push_str("\\n"),
             '\t' => escaped.push_str("\\t"),
             '\r' => escaped.push_str("\\r"),
-            '\u{0080}'...'\u{FFFF}' => escaped.push_str(&format!("\\x{:04x}", c as isize)[..]),
+            '\u{0080}'..='\u{FFFF}' => escaped.push_str(&format!("\\x{:04x}", c as isize)[..]),
             _ => {
                 escaped.push('\\');
                 escaped.push(c);
@@ -153,8 +142,8 @@ pub struct SectionSetter<'a> {
 impl<'a> SectionSetter<'a> {
     fn new(ini: &'a mut Ini, section_name: Option<String>) -> SectionSetter<'a> {
         SectionSetter {
-            ini: ini,
-            section_name: section_name,
+            ini,
+            section_name,
         }
     }
 
@@ -203,12 +192,17 @@ pub type Properties = OrderMap<String, String>; // Key-value pairs
 #[derive(Clone, Debug)]
 pub struct Ini {
     sections: OrderMap<Option<String>, Properties>,
+    // Byte ranges of the values in the parsed input, by section and key
+    value_spans: OrderMap<(Option<String>, String), Range<usize>>,
 }
 
 impl Ini {
     /// Create an instance
     pub fn new() -> Ini {
-        Ini { sections: OrderMap::new() }
+        Ini {
+            sections: OrderMap::new(),
+            value_spans: OrderMap::new(),
+        }
     }
 
     /// Set with a specified section, `None` is for the general section
@@ -229,26 +223,26 @@ impl Ini {
     }
 
     /// Get a immutable section
-    pub fn section<'a, S>(&'a self, name: Option<S>) -> Option<&'a Properties>
+    pub fn section<S>(&self, name: Option<S>) -> Option<&Properties>
         where S: Into<String>
     {
         self.sections.get(&name.map(|s| s.into()))
     }
 
     /// Get a mutable section
-    pub fn section_mut<'a, S>(&'a mut self, name: Option<S>) -> Option<&'a mut Properties>
+    pub fn section_mut<S>(&mut self, name: Option<S>) -> Option<&mut Properties>
         where S: Into<String>
     {
         self.sections.get_mut(&name.map(|s| s.into()))
     }
 
     /// Get the entry
-    pub fn entry<'a>(&'a mut self, name: Option<String>) -> Entry<Option<String>, Properties> {
-        self.sections.entry(name.map(|s| s.into()))
+    pub fn entry<'a>(&'a mut self, name: Option<String>) -> Entry<'a, Option<String>, Properties> {
+        self.sections.entry(name)
     }
 
     /// Clear all entries
-    pub fn clear<'a>(&mut self) {
+    pub fn clear(&mut self) {
         self.sections.clear()
     }
 
@@ -257,6 +251,17 @@ impl Ini {
         self.sections.keys()
     }
 
+    /// Get the byte range of a value in the parsed input, `None` for values that were set
+    /// instead of parsed
+    ///
+    /// The range covers the value as written, without the surrounding whitespace, quotes
+    /// or inline comment. Escape sequences are part of it, so it can be longer than the value.
+    pub fn value_span<S>(&self, section: Option<S>, key: &str) -> Option<Range<usize>>
+        where S: Into<String>
+    {
+        self.value_spans.get(&(section.map(|s| s.into()), key.to_string())).cloned()
+    }
+
     /// Set key-value to a section
     pub fn set_to<S>(&mut self, section: Option<S>, key: String, value: String)
         where S: Into<String>
@@ -279,7 +284,7 @@ impl Ini {
     {
         match self.sections.get(&section.map(|s| s.into())) {
             None => None,
-            Some(ref prop) => {
+            Some(prop) => {
                 match prop.get(key) {
                     Some(p) => Some(&p[..]),
                     None => None,
@@ -303,7 +308,7 @@ impl Ini {
     {
         match self.sections.get(&section.map(|s| s.into())) {
             None => default,
-            Some(ref prop) => {
+            Some(prop) => {
                 match prop.get(key) {
                     Some(p) => &p[..],
                     None => default,
@@ -318,7 +323,7 @@ impl Ini {
     {
         match self.sections.get_mut(&section.map(|s| s.into())) {
             None => None,
-            Some(mut prop) => prop.get_mut(key).map(|s| &s[..]),
+            Some(prop) => prop.get_mut(key).map(|s| &s[..]),
         }
     }
 
@@ -333,7 +338,7 @@ impl Ini {
         where S: Into<String>
     {
         match self.section_mut(section) {
-            None => return None,
+            None => None,
             Some(prop) => prop.remove(key),
         }
     }
@@ -350,7 +355,7 @@ impl<'q> Index<&'q Option<String>> for Ini {
     }
 }
 
-impl<'i> IndexMut<&'i Option<String>> for Ini {
+impl IndexMut<&Option<String>> for Ini {
     fn index_mut<'a>(&'a mut self, index: &Option<String>) -> &'a mut Properties {
         match self.sections.get_mut(index) {
             Some(p) => p,
@@ -390,11 +395,11 @@ impl Ini {
                                                 filename: P,
                                                 policy: EscapePolicy)
                                                 -> io::Result<()> {
-        let mut file = try!(OpenOptions::new()
+        let mut file = OpenOptions::new()
             .write(true)
             .truncate(true)
             .create(true)
-            .open(filename.as_ref()));
+            .open(filename.as_ref())?;
         self.write_to_policy(&mut file, policy)
     }
 
@@ -410,32 +415,29 @@ impl Ini {
                                      -> io::Result<()> {
         let mut firstline = true;
 
-        match self.sections.get(&None) {
-            Some(props) => {
-                for (k, v) in props.iter() {
-                    let k_str = escape_str(&k[..], policy);
-                    let v_str = escape_str(&v[..], policy);
-                    try!(write!(writer, "{}={}\n", k_str, v_str));
-                }
-                firstline = false;
+        if let Some(props) = self.sections.get(&None) {
+            for (k, v) in props.iter() {
+                let k_str = escape_str(&k[..], policy);
+                let v_str = escape_str(&v[..], policy);
+                writeln!(writer, "{}={}", k_str, v_str)?;
             }
-            None => {}
+            firstline = false;
         }
 
-        for (section, props) in self.sections.iter().filter(|&(ref s, _)| s.is_some()) {
+        for (section, props) in self.sections.iter().filter(|(s, _)| s.is_some()) {
             if firstline {
                 firstline = false;
             } else {
-                try!(writer.write_all(b"\n"));
+                writer.write_all(b"\n")?;
             }
 
-            if let &Some(ref section) = section {
-                try!(write!(writer, "[{}]\n", escape_str(&section[..], policy)));
+            if let Some(section) = section {
+                writeln!(writer, "[{}]", escape_str(&section[..], policy))?;
 
                 for (k, v) in props.iter() {
                     let k_str = escape_str(&k[..], policy);
                     let v_str = escape_str(&v[..], policy);
-                    try!(write!(writer, "{}={}\n", k_str, v_str));
+                    writeln!(writer, "{}={}", k_str, v_str)?;
                 }
             }
         }
@@ -445,22 +447,31 @@ impl Ini {
 
 impl Ini {
     /// Load from a string
+    ///
+    /// Keys end at the first `=` or `:` of their line, so values can contain both. In unquoted
+    /// values, an unescaped `;` or `#` starts an inline comment and ends the value.
     pub fn load_from_str(buf: &str) -> Result<Ini, Error> {
-        let mut parser = Parser::new(buf.chars());
+        Ini::load_from_str_opt(buf, ParseOption::default())
+    }
+
+    /// Load from a string, with options
+    pub fn load_from_str_opt(buf: &str, opt: ParseOption) -> Result<Ini, Error> {
+        let mut parser = Parser::new(buf);
+        parser.opt = opt;
         parser.parse()
     }
 
     /// Load from a reader
     pub fn read_from<R: Read>(reader: &mut R) -> Result<Ini, Error> {
         let mut s = String::new();
-        try!(reader.read_to_string(&mut s).map_err(|err| {
+        reader.read_to_string(&mut s).map_err(|err| {
             Error {
                 line: 0,
                 col: 0,
                 msg: format!("{}", err),
             }
-        }));
-        let mut parser = Parser::new(s.chars());
+        })?;
+        let mut parser = Parser::new(&s);
         parser.parse()
     }
 
@@ -480,6 +491,368 @@ impl Ini {
     }
 }
 
+/// A config file as written, down to its comments, blank lines and spacing, so that tools
+/// can edit it and write it back without losing what people wrote by hand
+///
+/// Unlike `Ini`, sections of the same name are kept apart, in the order they're written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    // The lines before the first section header come first, as a section without a name
+    sections: Vec<DocumentSection>,
+}
+
+/// A section of a `Document`, from its header to the next one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocumentSection {
+    name: Option<String>,
+    // The header as written, empty for the lines before the first header
+    header: String,
+    entries: Vec<DocumentEntry>,
+    // The line ending of the document, `\r\n` if it uses it anywhere, for new lines
+    newline: &'static str,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DocumentEntry {
+    Property(DocumentProperty),
+    // Comments, blank lines and anything else kept as written
+    Text(String),
+}
+
+/// A property of a `Document`, with the lines it's written on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocumentProperty {
+    key: String,
+    value: String,
+    text: String,
+    // Byte range of the value in `text`
+    value_span: Range<usize>,
+}
+
+impl Document {
+    /// Parse a document, which fails on the same input as `Ini::load_from_str`
+    pub fn parse(src: &str) -> Result<Document, Error> {
+        let mut parser = Parser::new(src);
+        parser.parse()?;
+        let line_start = |pos: usize| src[..pos].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = |pos: usize| src[pos..].find('\n').map_or(src.len(), |i| pos + i + 1);
+        let newline = if src.contains("\r\n") { "\r\n" } else { "\n" };
+
+        let mut sections = vec![DocumentSection {
+                                    name: None,
+                                    header: String::new(),
+                                    entries: vec![],
+                                    newline,
+                                }];
+        // Every item takes its whole lines, unless another item starts on the same line
+        let mut pos = 0;
+        for (i, item) in parser.items.iter().enumerate() {
+            let start = line_start(item.span().start).max(pos);
+            let end = match parser.items.get(i + 1) {
+                Some(next) => line_end(item.span().end).min(next.span().start),
+                None => line_end(item.span().end),
+            };
+            let current = sections.last_mut().expect("there is always a section");
+            if start > pos {
+                current.entries.push(DocumentEntry::Text(src[pos..start].to_string()));
+            }
+            let text = src[start..end].to_string();
+            match *item {
+                ParsedItem::Section { ref name, .. } => {
+                    sections.push(DocumentSection {
+                        name: Some(name.clone()),
+                        header: text,
+                        entries: vec![],
+                        newline,
+                    })
+                }
+                ParsedItem::Property { ref key, ref value, ref value_span, .. } => {
+                    current.entries.push(DocumentEntry::Property(DocumentProperty {
+                        key: key.clone(),
+                        value: value.clone(),
+                        text,
+                        value_span: value_span.start - start..value_span.end - start,
+                    }))
+                }
+            }
+            pos = end;
+        }
+        if pos < src.len() {
+            let current = sections.last_mut().expect("there is always a section");
+            current.entries.push(DocumentEntry::Text(src[pos..].to_string()));
+        }
+        Ok(Document { sections })
+    }
+
+    /// Iterate through sections, starting with the one before the first header
+    pub fn sections(&self) -> impl Iterator<Item = &DocumentSection> {
+        self.sections.iter()
+    }
+
+    /// Iterate through sections to edit them, starting with the one before the first header
+    pub fn sections_mut(&mut self) -> impl Iterator<Item = &mut DocumentSection> {
+        self.sections.iter_mut()
+    }
+
+    /// Set a value in the last section named `section`, `None` for the lines before the
+    /// first header, adding the section at the end if there is none
+    ///
+    /// See `DocumentSection::set`.
+    pub fn set(&mut self, section: Option<&str>, key: &str, value: &str) {
+        let index = self.sections.iter().rposition(|s| s.name.as_deref() == section);
+        let section = match (index, section) {
+            (Some(index), _) => &mut self.sections[index],
+            (None, Some(name)) => self.add_section(name),
+            (None, None) => unreachable!("the section before the first header always exists"),
+        };
+        section.set(key, value);
+    }
+
+    /// Remove `key` from every section named `section`, `None` for the lines before the first
+    /// header, returning the value `Ini` would have read
+    pub fn remove(&mut self, section: Option<&str>, key: &str) -> Option<String> {
+        self.sections
+            .iter_mut()
+            .filter(|s| s.name.as_deref() == section)
+            .filter_map(|s| s.remove(key))
+            .last()
+    }
+
+    /// The line the header of each section is on, from 1, in the order of `sections`. The
+    /// lines before the first header start on line 1.
+    pub fn header_lines(&self) -> Vec<usize> {
+        let mut line = 1;
+        self.sections
+            .iter()
+            .map(|section| {
+                let header_line = line;
+                line += section.text().matches('\n').count();
+                header_line
+            })
+            .collect()
+    }
+
+    /// Merge every section into the one before it when they have the same name, which
+    /// `Ini` reads the same, keeping what follows the header on its line. Returns the
+    /// indexes, in `sections` before merging, of the sections merged away.
+    pub fn merge_adjacent_sections(&mut self) -> Vec<usize> {
+        let mut merged = vec![];
+        let mut sections: Vec<DocumentSection> = vec![];
+        for (index, section) in self.sections.drain(..).enumerate() {
+            match sections.last_mut() {
+                Some(previous) if previous.name.is_some() && previous.name == section.name => {
+                    previous.end_line(section.newline);
+                    let after_header = section.header
+                        .rfind(']')
+                        .map_or("", |end| section.header[end + 1..].trim_start());
+                    if !after_header.is_empty() {
+                        previous.entries.push(DocumentEntry::Text(after_header.to_string()));
+                    }
+                    previous.entries.extend(section.entries);
+                    merged.push(index);
+                }
+                _ => sections.push(section),
+            }
+        }
+        self.sections = sections;
+        merged
+    }
+
+    /// Add an empty section at the end, after a blank line
+    pub fn add_section(&mut self, name: &str) -> &mut DocumentSection {
+        let newline = self.sections[0].newline;
+        if !self.to_string().is_empty() {
+            let last = self.sections.last_mut().expect("there is always a section");
+            last.end_line(newline);
+            if !last.text().ends_with(&format!("{}{}", newline, newline)) {
+                last.entries.push(DocumentEntry::Text(newline.to_string()));
+            }
+        }
+        self.sections.push(DocumentSection {
+            name: Some(name.to_string()),
+            header: format!("[{}]{}", name, newline),
+            entries: vec![],
+            newline,
+        });
+        self.sections.last_mut().expect("a section was just added")
+    }
+
+    /// Write to a file, exactly as parsed but for the changes made since
+    pub fn write_to_file<P: AsRef<Path>>(&self, filename: P) -> io::Result<()> {
+        let mut file = File::create(filename.as_ref())?;
+        self.write_to(&mut file)
+    }
+
+    /// Write to a writer, exactly as parsed but for the changes made since
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self.to_string().as_bytes())
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for section in &self.sections {
+            f.write_str(&section.header)?;
+            for entry in &section.entries {
+                match *entry {
+                    DocumentEntry::Property(ref property) => f.write_str(&property.text)?,
+                    DocumentEntry::Text(ref text) => f.write_str(text)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DocumentSection {
+    /// The section name, `None` for the lines before the first header
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Rename the section, leaving the rest of the header line as is. The section before the
+    /// first header can't be renamed.
+    pub fn rename(&mut self, name: &str) {
+        if self.name.is_none() {
+            return;
+        }
+        if let (Some(start), Some(end)) = (self.header.find('['), self.header.rfind(']')) {
+            self.header.replace_range(start + 1..end, name);
+        }
+        self.name = Some(name.to_string());
+    }
+
+    /// Set the value of the last property with `key`, leaving the rest of its line as is,
+    /// or add a `key = value` line after the last property
+    ///
+    /// Values are trimmed when read, so `value` is too. Backslashes, comment characters, line
+    /// breaks and a leading quote in it are escaped, so it reads back as given.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        let written = escape_value(value);
+        let existing = self.entries.iter_mut().rev().find_map(|entry| match *entry {
+            DocumentEntry::Property(ref mut property) if property.key == key => Some(property),
+            _ => None,
+        });
+        if let Some(property) = existing {
+            let span = property.value_span.clone();
+            property.text.replace_range(span.clone(), &written);
+            property.value_span = span.start..span.start + written.len();
+            property.value = value.to_string();
+            return;
+        }
+
+        let newline = self.newline;
+        let last_property = self.entries
+            .iter()
+            .rposition(|entry| matches!(*entry, DocumentEntry::Property(_)));
+        // Right after the header, unless there is none to keep the leading comments first
+        let index = match last_property {
+            Some(i) => i + 1,
+            None if self.name.is_some() => 0,
+            None => self.entries.len(),
+        };
+        match index.checked_sub(1).map(|i| &mut self.entries[i]) {
+            Some(&mut DocumentEntry::Property(DocumentProperty { ref mut text, .. })) |
+            Some(&mut DocumentEntry::Text(ref mut text)) => end_line(text, newline),
+            None => end_line(&mut self.header, newline),
+        }
+        let text = format!("{} = {}{}", key, written, newline);
+        let start = key.len() + 3;
+        self.entries.insert(index,
+                            DocumentEntry::Property(DocumentProperty {
+                                key: key.to_string(),
+                                value: value.to_string(),
+                                value_span: start..start + written.len(),
+                                text,
+                            }));
+    }
+
+    /// Remove every property with `key` and the lines they're written on, returning the
+    /// last value
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let mut removed = None;
+        self.entries.retain(|entry| match *entry {
+            DocumentEntry::Property(ref property) if property.key == key => {
+                removed = Some(property.value.clone());
+                false
+            }
+            _ => true,
+        });
+        removed
+    }
+
+    /// Iterate through properties, in the order they're written
+    pub fn properties(&self) -> impl Iterator<Item = &DocumentProperty> {
+        self.entries.iter().filter_map(|entry| match *entry {
+            DocumentEntry::Property(ref property) => Some(property),
+            DocumentEntry::Text(_) => None,
+        })
+    }
+}
+
+impl DocumentSection {
+    fn text(&self) -> String {
+        let mut text = self.header.clone();
+        for entry in &self.entries {
+            match *entry {
+                DocumentEntry::Property(ref property) => text.push_str(&property.text),
+                DocumentEntry::Text(ref t) => text.push_str(t),
+            }
+        }
+        text
+    }
+
+    // Make the section end with a line break, if it has any text
+    fn end_line(&mut self, newline: &str) {
+        match self.entries.last_mut() {
+            Some(&mut DocumentEntry::Property(DocumentProperty { ref mut text, .. })) |
+            Some(&mut DocumentEntry::Text(ref mut text)) => end_line(text, newline),
+            None => end_line(&mut self.header, newline),
+        }
+    }
+}
+
+fn end_line(text: &mut String, newline: &str) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push_str(newline);
+    }
+}
+
+// Escape what would end a value or change how it's read, for `DocumentSection::set`
+pub(crate) fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            // A leading quote would start a quoted value
+            '"' | '\'' if i == 0 => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ';' => escaped.push_str("\\;"),
+            '#' => escaped.push_str("\\#"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl DocumentProperty {
+    /// The key, trimmed
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The value, like `Ini` reads it
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
 /// Iterator for sections
 pub struct SectionIterator<'a> {
     mapiter: Iter<'a, Option<String>, Properties>,
@@ -565,11 +938,56 @@ impl IntoIterator for Ini {
 }
 
 // Ini parser
+/// How to parse an INI file
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct ParseOption {
+    /// Whether an unescaped `;` or `#` in an unquoted value starts an inline comment, or is
+    /// part of the value like the EditorConfig spec has it since 0.15.0. `true` by default.
+    pub inline_comments: bool,
+}
+
+impl Default for ParseOption {
+    fn default() -> ParseOption {
+        ParseOption { inline_comments: true }
+    }
+}
+
 struct Parser<'a> {
     ch: Option<char>,
+    src: &'a str,
+    // Byte offset of `ch` in `src`
+    pos: usize,
     rdr: Chars<'a>,
     line: usize,
     col: usize,
+    // Byte offset of the key being parsed
+    key_start: usize,
+    // Section headers and properties in the order they were parsed, for `Document`
+    items: Vec<ParsedItem>,
+    opt: ParseOption,
+}
+
+// A section header or property, with the bytes it was parsed from
+enum ParsedItem {
+    Section {
+        name: String,
+        span: Range<usize>,
+    },
+    Property {
+        key: String,
+        value: String,
+        span: Range<usize>,
+        value_span: Range<usize>,
+    },
+}
+
+impl ParsedItem {
+    fn span(&self) -> &Range<usize> {
+        match *self {
+            ParsedItem::Section { ref span, .. } => span,
+            ParsedItem::Property { ref span, .. } => span,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -591,19 +1009,24 @@ impl error::Error for Error {
         self.msg.as_str()
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    fn cause(&self) -> Option<&dyn error::Error> {
         None
     }
 }
 
 impl<'a> Parser<'a> {
     // Create a parser
-    pub fn new(rdr: Chars<'a>) -> Parser<'a> {
+    pub fn new(src: &'a str) -> Parser<'a> {
         let mut p = Parser {
             ch: None,
+            src,
+            pos: 0,
             line: 0,
             col: 0,
-            rdr: rdr,
+            rdr: src.chars(),
+            key_start: 0,
+            items: vec![],
+            opt: ParseOption::default(),
         };
         p.bump();
         p
@@ -614,6 +1037,9 @@ impl<'a> Parser<'a> {
     }
 
     fn bump(&mut self) {
+        if let Some(c) = self.ch {
+            self.pos += c.len_utf8();
+        }
         self.ch = self.rdr.next();
         match self.ch {
             Some('\n') => {
@@ -668,8 +1094,13 @@ impl<'a> Parser<'a> {
                     self.parse_comment();
                 }
                 '[' => {
+                    let start = self.pos;
                     match self.parse_section() {
                         Ok(sec) => {
+                            self.items.push(ParsedItem::Section {
+                                name: sec.clone(),
+                                span: start..self.pos,
+                            });
                             cursec = Some(sec.to_string());
                             result.sections.entry(cursec.clone()).or_insert(OrderMap::new());
                             self.parse_whitespace();
@@ -678,12 +1109,19 @@ impl<'a> Parser<'a> {
                     }
                 }
                 '=' | ':' => {
-                    if (&curkey[..]).is_empty() {
+                    if curkey.is_empty() {
                         return self.error("Missing key".to_string());
                     }
                     match self.parse_val() {
-                        Ok(val) => {
+                        Ok((val, span)) => {
                             let mval = val[..].trim().to_owned();
+                            self.items.push(ParsedItem::Property {
+                                key: curkey.clone(),
+                                value: mval.clone(),
+                                span: self.key_start..self.pos,
+                                value_span: span.clone(),
+                            });
+                            result.value_spans.insert((cursec.clone(), curkey.clone()), span);
                             let sec = result.sections
                                 .entry(cursec.clone())
                                 .or_insert(OrderMap::new());
@@ -694,10 +1132,11 @@ impl<'a> Parser<'a> {
                     }
                 }
                 _ => {
+                    self.key_start = self.pos;
                     match self.parse_key() {
                         Ok(key) => {
                             let mkey: String = key[..].trim().to_owned();
-                            curkey = mkey.into();
+                            curkey = mkey;
                         }
                         Err(e) => return Err(e),
                     }
@@ -719,7 +1158,10 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_str_until(&mut self, endpoint: &[Option<char>]) -> Result<String, Error> {
+    /// Read up to one of `endpoint`, unescaping as it goes. Values also read `\\`, `\"` and
+    /// `\'` back as the character escaped; section names and keys keep them, as patterns
+    /// escape with backslashes too.
+    fn parse_str_until(&mut self, endpoint: &[Option<char>], value: bool) -> Result<String, Error> {
         let mut result: String = String::new();
 
         while !endpoint.contains(&self.ch) {
@@ -758,14 +1200,20 @@ impl<'a> Parser<'a> {
                                 }
                                 code.push(self.ch.unwrap());
                             }
-                            let r = u32::from_str_radix(&code[..], 16);
+                            // Surrogates are valid hex but no character
+                            let r = u32::from_str_radix(&code[..], 16)
+                                .ok()
+                                .and_then(char::from_u32);
                             match r {
-                                Ok(c) => result.push(char::from_u32(c).unwrap()),
-                                Err(_) => return self.error("Unknown character.".to_string()),
+                                Some(c) => result.push(c),
+                                None => return self.error("Unknown character.".to_string()),
                             }
                         },
                         ';' => result.push(';'),
                         '#' => result.push('#'),
+                        '=' => result.push('='),
+                        ':' => result.push(':'),
+                        c @ '\\' | c @ '"' | c @ '\'' if value => result.push(c),
                         c => {
                             result.push('\\');
                             result.push(c)
@@ -784,40 +1232,56 @@ impl<'a> Parser<'a> {
     fn parse_section(&mut self) -> Result<String, Error> {
         // Skip [
         self.bump();
-        self.parse_str_until(&[Some('\n'), Some(';'), Some('#'), None]).map(|mut n| {
-            n = n.trim_right().to_string();
+        self.parse_str_until(&[Some('\n'), Some(';'), Some('#'), None], false).map(|mut n| {
+            n = n.trim_end().to_string();
             n.pop();
             n
         })
     }
 
     fn parse_key(&mut self) -> Result<String, Error> {
-        self.parse_str_until(&[Some('='), Some(':')])
+        self.parse_str_until(&[Some('='), Some(':')], false)
     }
 
-    fn parse_val(&mut self) -> Result<String, Error> {
+    /// Parse a value and the byte range it was written at
+    ///
+    /// Values are trimmed. The key ends at the first `=` or `:` of the line, everything after
+    /// is the value, so values can contain both, as in `dotnet_naming_rule.x.symbols = a=b`.
+    /// Unquoted values end at a `;` or `#`, which starts an inline comment unless escaped as
+    /// `\;` or `\#`: `severity = warning # rationale` sets `warning`. Quoted values end at the
+    /// closing quote and may span lines or contain `;` and `#`. Without
+    /// `ParseOption::inline_comments`, unquoted values end at the end of their line. `\\`
+    /// reads as `\`, and `\"` or `\'` as a quote, so values can start with one.
+    fn parse_val(&mut self) -> Result<(String, Range<usize>), Error> {
         self.bump();
         // Issue #35: Allow empty value
         self.parse_whitespace_except_line_break();
 
-        match self.ch {
-            None => Ok(String::new()),
-            Some('"') => {
-                self.bump();
-                self.parse_str_until(&[Some('"')]).and_then(|s| {
-                    self.bump(); // Eats the last "
-                    Ok(s)
-                })
-            }
-            Some('\'') => {
-                self.bump();
-                self.parse_str_until(&[Some('\'')]).and_then(|s| {
-                    self.bump(); // Eats the last '
-                    Ok(s)
-                })
+        let quote = match self.ch {
+            None => return Ok((String::new(), self.pos..self.pos)),
+            Some(c @ '"') | Some(c @ '\'') => Some(c),
+            _ => None,
+        };
+        if quote.is_some() {
+            self.bump();
+        }
+        let start = self.pos;
+        let val = match quote {
+            Some(quote) => self.parse_str_until(&[Some(quote)], true)?,
+            None if self.opt.inline_comments => {
+                self.parse_str_until(&[Some('\n'), Some('\r'), Some(';'), Some('#'), None], true)?
             }
-            _ => self.parse_str_until(&[Some('\n'), Some('\r'), Some(';'), Some('#'), None]),
+            None => self.parse_str_until(&[Some('\n'), Some('\r'), None], true)?,
+        };
+        let end = self.pos;
+        if quote.is_some() {
+            self.bump(); // Eats the closing quote
         }
+        // Trimmed like the value
+        let raw = &self.src[start..end];
+        let trimmed_start = start + (raw.len() - raw.trim_start().len());
+        let span = trimmed_start..trimmed_start + raw.trim().len();
+        Ok((val, span))
     }
 }
 
@@ -857,6 +1321,70 @@ mod test {
         assert!(opt.is_ok());
     }
 
+    #[test]
+    fn documents_write_back_what_they_parsed() {
+        let input = "# Top comment\r\nroot = true\r\n\r\n[*.rs] ; Rust\r\n  indent_size = 4 # like rustfmt\r\n\
+                     \r\n[*.md]\nname = \"a\nb\" ; multiline\n[sec2]\nfoo=bar\n[*.md]\nstray\nkey: value";
+        let document = Document::parse(input).unwrap();
+        assert_eq!(document.to_string(), input);
+        let names: Vec<_> = document.sections().map(|section| section.name()).collect();
+        assert_eq!(names, [None, Some("*.rs"), Some("*.md"), Some("sec2"), Some("*.md")]);
+        let properties: Vec<_> = document.sections()
+            .flat_map(|section| section.properties())
+            .map(|property| (property.key(), property.value()))
+            .collect();
+        assert_eq!(properties,
+                   [("root", "true"), ("indent_size", "4"), ("name", "a\nb"), ("foo", "bar"),
+                    ("stray\nkey", "value")]);
+        assert!(Document::parse("[*]\n= 1\n").is_err());
+    }
+
+    #[test]
+    fn documents_edit_only_what_changes() {
+        let mut document = Document::parse("# Top comment\r\nroot = true\r\n\r\n\
+                                            [*.rs] ; Rust\r\nindent_size = 4 # like rustfmt\r\n\
+                                            \r\n[*.md]\r\nindent_size = 2\r\n\r\n[*.rs]")
+            .unwrap();
+        document.set(Some("*.rs"), "indent_size", "8");
+        for section in document.sections_mut() {
+            if section.name().is_some_and(|name| name.ends_with(".rs")) {
+                section.set("max_line_length", "100 ; or #");
+            }
+        }
+        assert_eq!(document.remove(Some("*.md"), "indent_size"), Some("2".to_string()));
+        assert_eq!(document.remove(Some("*.md"), "indent_size"), None);
+        document.set(Some("*.py"), "indent_size", "4");
+        assert_eq!(document.to_string(),
+                   "# Top comment\r\nroot = true\r\n\r\n\
+                    [*.rs] ; Rust\r\nindent_size = 4 # like rustfmt\r\n\
+                    max_line_length = 100 \\; or \\#\r\n\
+                    \r\n[*.md]\r\n\r\n\
+                    [*.rs]\r\nindent_size = 8\r\nmax_line_length = 100 \\; or \\#\r\n\r\n\
+                    [*.py]\r\nindent_size = 4\r\n");
+        let ini = Ini::load_from_str(&document.to_string()).unwrap();
+        assert_eq!(ini.get_from(Some("*.rs"), "indent_size"), Some("8"));
+        assert_eq!(ini.get_from(Some("*.rs"), "max_line_length"), Some("100 ; or #"));
+
+        let mut document = Document::parse("").unwrap();
+        document.set(None, "root", "true");
+        document.add_section("*");
+        assert_eq!(document.to_string(), "root = true\n\n[*]\n");
+    }
+
+    #[test]
+    fn documents_rename_and_merge_sections() {
+        let mut document = Document::parse("root = true\n\n[*.{md,md}] ; docs\nindent_size = 2\n\n\
+                                            [*.md] ; again\ncharset = utf-8\n[*]\nx = 1\n")
+            .unwrap();
+        assert_eq!(document.header_lines(), [1, 3, 6, 8]);
+        document.sections_mut().nth(1).unwrap().rename("*.md");
+        assert_eq!(document.merge_adjacent_sections(), [2]);
+        assert_eq!(document.to_string(),
+                   "root = true\n\n[*.md] ; docs\nindent_size = 2\n\n; again\n\
+                    charset = utf-8\n[*]\nx = 1\n");
+        assert_eq!(document.header_lines(), [1, 3, 8]);
+    }
+
     #[test]
     fn test_parse_comment() {
         let input = "; abcdefghijklmn\n";
@@ -875,6 +1403,69 @@ gender = mail ; abdddd
         assert_eq!(ini.get_from(Some("section name"), "name").unwrap(), "hello");
     }
 
+    #[test]
+    fn splits_on_the_first_separator_only() {
+        let input = "[*.{cs,a=b}]
+dotnet_diagnostic.CA1000.severity = warning # rationale
+pattern = a=b:c
+url: https://example.com/?a=b
+escaped\\=key = x\\=y
+";
+        let ini = Ini::load_from_str(input).unwrap();
+        let section = ini.section(Some("*.{cs,a=b}")).unwrap();
+        assert_eq!(section["dotnet_diagnostic.CA1000.severity"], "warning");
+        assert_eq!(section["pattern"], "a=b:c");
+        assert_eq!(section["url"], "https://example.com/?a=b");
+        assert_eq!(section["escaped=key"], "x=y");
+    }
+
+    #[test]
+    fn reads_back_reserved_characters_written_escaped() {
+        let mut ini = Ini::new();
+        ini.with_section(Some("*")).set("key", "a=b:c;d#e");
+        let mut written = Vec::new();
+        ini.write_to_policy(&mut written, EscapePolicy::Reserved).unwrap();
+        let read = Ini::load_from_str(&String::from_utf8(written).unwrap()).unwrap();
+        assert_eq!(read.get_from(Some("*"), "key").unwrap(), "a=b:c;d#e");
+    }
+
+    #[test]
+    fn reads_back_the_values_documents_set() {
+        let values = [r"a\nb", r"C:\dir\", "\"quoted\"", "'a", "a'b", "x ; y # z\tw"];
+        let mut document = Document::parse("[*]\n").unwrap();
+        for (i, value) in values.iter().enumerate() {
+            document.set(Some("*"), &format!("k{}", i), value);
+        }
+        let ini = Ini::load_from_str(&document.to_string()).unwrap();
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(ini.get_from(Some("*"), &format!("k{}", i)), Some(*value), "{}", document);
+        }
+        let reparsed = Document::parse(&document.to_string()).unwrap();
+        let read: Vec<_> = reparsed.sections()
+            .flat_map(|section| section.properties())
+            .map(|property| property.value())
+            .collect();
+        assert_eq!(read, values);
+    }
+
+    #[test]
+    fn rejects_escapes_of_surrogates() {
+        let ini = Ini::load_from_str("[*]\nkey = \\x00e9\n").unwrap();
+        assert_eq!(ini.get_from(Some("*"), "key").unwrap(), "é");
+        assert!(Ini::load_from_str("[*]\nkey = \\xd800\n").is_err());
+    }
+
+    #[test]
+    fn keeps_inline_comments_in_values_when_asked() {
+        let input = "# comment\n[*]\nname = hello # abc\nother = a ; b\nquoted = \"x\" ; y\n";
+        let opt = ParseOption { inline_comments: false };
+        let ini = Ini::load_from_str_opt(input, opt).unwrap();
+        assert_eq!(ini.get_from(Some("*"), "name").unwrap(), "hello # abc");
+        assert_eq!(ini.get_from(Some("*"), "other").unwrap(), "a ; b");
+        assert_eq!(ini.get_from(Some("*"), "quoted").unwrap(), "x");
+        assert_eq!(ini.section(None::<String>), None);
+    }
+
     #[test]
     fn test_sharp_comment() {
         let input = "
@@ -1000,6 +1591,22 @@ Key = 'Value   # This is not a comment ; at all'
                    "Value   # This is not a comment ; at all");
     }
 
+    #[test]
+    fn test_value_spans() {
+        let input = "root = true\n[*.md]\nkey = \"Ünï # x \" ; comment\nother =  a\\;b   # c\nempty =\n";
+        let ini = Ini::load_from_str(input).unwrap();
+        let span = |section: Option<&str>, key: &str| {
+            &input[ini.value_span(section, key).unwrap()]
+        };
+        assert_eq!(span(None, "root"), "true");
+        assert_eq!(span(Some("*.md"), "key"), "Ünï # x");
+        assert_eq!(ini.get_from(Some("*.md"), "key").unwrap(), "Ünï # x");
+        assert_eq!(span(Some("*.md"), "other"), "a\\;b");
+        assert_eq!(ini.get_from(Some("*.md"), "other").unwrap(), "a;b");
+        assert_eq!(span(Some("*.md"), "empty"), "");
+        assert!(ini.value_span(Some("*.md"), "missing").is_none());
+    }
+
     #[test]
     fn load_from_str_with_valid_empty_input() {
         let input = "key1=\nkey2=val2\n";
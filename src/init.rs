@@ -0,0 +1,459 @@
+//! Writing a first config file by asking the user a few questions, and keeping it up to
+//! date with `init --update`.
+//!
+//! The generated settings are kept between two marker lines. The first one records the
+//! answers, so the block can be generated again without asking, while everything outside
+//! of the markers is left to manual edits.
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use walk::{walk_files, WalkOptions};
+
+/// Starts the generated block, followed by the answers it was generated from.
+const BEGIN_MARKER: &str = "# BEGIN editorconfig init:";
+/// Ends the generated block.
+const END_MARKER: &str = "# END editorconfig init";
+
+/// Conventions `init` knows for a language.
+struct Language {
+    /// Names the user can give, the first one being shown.
+    names: &'static [&'static str],
+    /// Extensions (or file names) of the language's files, to detect it.
+    extensions: &'static [&'static str],
+    section: &'static str,
+    /// Why the section is there.
+    comment: &'static str,
+    properties: &'static [(&'static str, &'static str)],
+}
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        names: &["rust", "rs"],
+        extensions: &["rs"],
+        section: "*.rs",
+        comment: "rustfmt indents with 4 spaces.",
+        properties: &[("indent_style", "space"), ("indent_size", "4")],
+    },
+    Language {
+        names: &["python", "py"],
+        extensions: &["py"],
+        section: "*.py",
+        comment: "PEP 8 indents with 4 spaces.",
+        properties: &[("indent_style", "space"), ("indent_size", "4")],
+    },
+    Language {
+        names: &["javascript", "js", "typescript", "ts"],
+        extensions: &["js", "jsx", "ts", "tsx"],
+        section: "*.{js,jsx,ts,tsx,json}",
+        comment: "JavaScript and JSON are usually indented with 2 spaces.",
+        properties: &[("indent_style", "space"), ("indent_size", "2")],
+    },
+    Language {
+        names: &["go"],
+        extensions: &["go"],
+        section: "*.go",
+        comment: "gofmt indents with tabs.",
+        properties: &[("indent_style", "tab")],
+    },
+    Language {
+        names: &["make", "makefile"],
+        extensions: &["Makefile", "mk"],
+        section: "{Makefile,*.mk}",
+        comment: "Makefiles require tabs.",
+        properties: &[("indent_style", "tab")],
+    },
+    Language {
+        names: &["markdown", "md"],
+        extensions: &["md"],
+        section: "*.md",
+        comment: "Trailing spaces are line breaks in Markdown.",
+        properties: &[("trim_trailing_whitespace", "false")],
+    },
+    Language {
+        names: &["yaml", "yml"],
+        extensions: &["yml", "yaml"],
+        section: "*.{yml,yaml}",
+        comment: "YAML forbids tabs.",
+        properties: &[("indent_style", "space"), ("indent_size", "2")],
+    },
+];
+
+/// Answers to the questions of `init --interactive`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitAnswers {
+    /// Indices in `LANGUAGES`.
+    languages: Vec<usize>,
+    indent_style: &'static str,
+    indent_size: u32,
+    max_line_length: Option<u32>,
+    end_of_line: &'static str,
+}
+
+/// Values substituted in the header of generated config files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TemplateVars {
+    /// Name of the project, usually the name of its directory.
+    pub project: String,
+    /// Generation date, as `YYYY-MM-DD`.
+    pub date: String,
+}
+
+impl TemplateVars {
+    /// The variables for a project in `dir`, generated today.
+    pub fn for_dir(dir: &Path) -> TemplateVars {
+        let days = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs() / 86400)
+            .unwrap_or(0);
+        TemplateVars {
+            project: dir.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            date: civil_date(days as i64),
+        }
+    }
+}
+
+/// Converts days since 1970-01-01 to a `YYYY-MM-DD` date.
+fn civil_date(days: i64) -> String {
+    // From Howard Hinnant's `civil_from_days`
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Returns the names of the languages whose files are found below `root`.
+pub fn detect_languages(root: &Path) -> Vec<&'static str> {
+    let files = walk_files(root, &WalkOptions::default(), &mut vec![]).unwrap_or_default();
+    LANGUAGES.iter()
+        .filter(|language| {
+            files.iter().any(|file| {
+                let extension = file.extension().and_then(|e| e.to_str());
+                let name = file.file_name().and_then(|n| n.to_str());
+                language.extensions.iter().any(|&e| extension == Some(e) || name == Some(e))
+            })
+        })
+        .map(|language| language.names[0])
+        .collect()
+}
+
+/// Asks about languages, indentation, line length and line endings on `output`, reading
+/// the answers from `input`. The languages in `detected` are suggested by default.
+///
+/// Empty answers pick the default shown in brackets, invalid ones are asked again.
+pub fn ask_answers(input: &mut dyn BufRead,
+                   output: &mut dyn Write,
+                   detected: &[&str])
+                   -> io::Result<InitAnswers> {
+    let names: Vec<_> = LANGUAGES.iter().map(|language| language.names[0]).collect();
+    let default_languages = if detected.is_empty() {
+        "none".to_string()
+    } else {
+        detected.join(",")
+    };
+    let languages = ask(input,
+                        output,
+                        &format!("Languages used, separated by commas ({})", names.join(", ")),
+                        &default_languages,
+                        parse_languages)?;
+    let indent_style = ask(input, output, "Indent with tabs or spaces", "spaces", |answer| {
+        match answer {
+            "tabs" | "tab" => Ok("tab"),
+            "spaces" | "space" => Ok("space"),
+            _ => Err("answer tabs or spaces".to_string()),
+        }
+    })?;
+    let indent_size = ask(input, output, "Columns per indentation level", "4", parse_size)?;
+    let max_line_length = ask(input, output, "Maximum line length", "none", |answer| {
+        match answer {
+            "none" => Ok(None),
+            _ => parse_size(answer).map(Some).map_err(|e| format!("{} or none", e)),
+        }
+    })?;
+    let end_of_line = ask(input, output, "Line endings: lf, crlf or cr", "lf", parse_end_of_line)?;
+    Ok(InitAnswers {
+        languages,
+        indent_style,
+        indent_size,
+        max_line_length,
+        end_of_line,
+    })
+}
+
+fn parse_languages(answer: &str) -> Result<Vec<usize>, String> {
+    if answer == "none" {
+        return Ok(vec![]);
+    }
+    answer.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            LANGUAGES.iter()
+                .position(|language| language.names.contains(&name))
+                .ok_or_else(|| format!("unknown language {}", name))
+        })
+        .collect()
+}
+
+fn parse_size(answer: &str) -> Result<u32, String> {
+    match answer.parse::<u32>() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err("answer a positive number".to_string()),
+    }
+}
+
+fn parse_end_of_line(answer: &str) -> Result<&'static str, String> {
+    match answer {
+        "lf" => Ok("lf"),
+        "crlf" => Ok("crlf"),
+        "cr" => Ok("cr"),
+        _ => Err("answer lf, crlf or cr".to_string()),
+    }
+}
+
+impl InitAnswers {
+    /// Records the answers on the begin marker line.
+    fn to_marker(&self) -> String {
+        format!("{} languages={} indent_style={} indent_size={} max_line_length={} end_of_line={}",
+                BEGIN_MARKER,
+                language_names(&self.languages, ",", "none"),
+                self.indent_style,
+                self.indent_size,
+                self.max_line_length.map(|l| l.to_string()).unwrap_or_else(|| "none".to_string()),
+                self.end_of_line)
+    }
+
+    /// Reads back the answers recorded by `to_marker`.
+    fn from_marker(line: &str) -> Result<InitAnswers, String> {
+        let mut answers = InitAnswers {
+            languages: vec![],
+            indent_style: "space",
+            indent_size: 4,
+            max_line_length: None,
+            end_of_line: "lf",
+        };
+        for field in line[BEGIN_MARKER.len()..].split_whitespace() {
+            let mut parts = field.splitn(2, '=');
+            let (key, value) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+            match key {
+                "languages" => answers.languages = parse_languages(value)?,
+                "indent_style" if value == "tab" => answers.indent_style = "tab",
+                "indent_style" => answers.indent_style = "space",
+                "indent_size" => answers.indent_size = parse_size(value)?,
+                "max_line_length" if value == "none" => answers.max_line_length = None,
+                "max_line_length" => answers.max_line_length = Some(parse_size(value)?),
+                "end_of_line" => answers.end_of_line = parse_end_of_line(value)?,
+                _ => return Err(format!("unknown answer `{}` in the init marker", key)),
+            }
+        }
+        Ok(answers)
+    }
+}
+
+/// Joins the names of the languages at `indices` in `LANGUAGES`, or returns `none`.
+fn language_names(indices: &[usize], separator: &str, none: &str) -> String {
+    if indices.is_empty() {
+        return none.to_string();
+    }
+    let names: Vec<_> = indices.iter().map(|&index| LANGUAGES[index].names[0]).collect();
+    names.join(separator)
+}
+
+/// Returns the contents of a config file following `answers`, with comments explaining
+/// each choice.
+pub fn render_config(answers: &InitAnswers, vars: &TemplateVars) -> String {
+    let mut config = String::new();
+    config.push_str(&format!("# EditorConfig for {}, see https://editorconfig.org\n\n",
+                             vars.project));
+    config.push_str("# Don't look for config files in parent directories.\nroot = true\n\n");
+    config.push_str(&render_block(answers, vars));
+    config
+}
+
+/// The generated block, markers included.
+fn render_block(answers: &InitAnswers, vars: &TemplateVars) -> String {
+    let mut block = String::new();
+    block.push_str(&answers.to_marker());
+    block.push('\n');
+    block.push_str(&format!("# Generated on {} for {}. Edit the answers above and run\n",
+                            vars.date,
+                            language_names(&answers.languages, ", ", "no language in particular")));
+    block.push_str("# `editorconfig init --update` to change this block, or add settings after \
+                    it.\n\n");
+    block.push_str("# Settings for every file.\n[*]\n");
+    if answers.indent_style == "tab" {
+        block.push_str(&format!("# Indent with tabs, shown {} columns wide.\n",
+                                answers.indent_size));
+    } else {
+        block.push_str(&format!("# Indent with {} spaces.\n", answers.indent_size));
+    }
+    block.push_str(&format!("indent_style = {}\nindent_size = {}\n",
+                            answers.indent_style,
+                            answers.indent_size));
+    let convention = match answers.end_of_line {
+        "crlf" => "CRLF, the Windows convention",
+        "cr" => "CR, the classic Mac OS convention",
+        _ => "LF, the Unix convention",
+    };
+    block.push_str(&format!("# End lines with {}.\nend_of_line = {}\n",
+                            convention,
+                            answers.end_of_line));
+    block.push_str("# Use UTF-8, without a byte order mark.\ncharset = utf-8\n");
+    block.push_str("# Remove whitespace at the end of lines and end files with a newline.\n");
+    block.push_str("trim_trailing_whitespace = true\ninsert_final_newline = true\n");
+    if let Some(length) = answers.max_line_length {
+        block.push_str(&format!("# Wrap lines longer than {} characters.\nmax_line_length = {}\n",
+                                length,
+                                length));
+    }
+    for &index in &answers.languages {
+        let language = &LANGUAGES[index];
+        block.push_str(&format!("\n# {}\n[{}]\n", language.comment, language.section));
+        for &(key, value) in language.properties {
+            block.push_str(&format!("{} = {}\n", key, value));
+        }
+    }
+    block.push_str(END_MARKER);
+    block.push('\n');
+    block
+}
+
+/// Generates the block between the markers of `existing` again from the answers recorded
+/// on its first line, leaving the rest untouched. Returns `existing` unchanged if only
+/// the generation date would change.
+pub fn update_config(existing: &str, vars: &TemplateVars) -> Result<String, String> {
+    let begin = existing.find(BEGIN_MARKER).ok_or("no generated block to update")?;
+    let end = existing[begin..]
+        .find(END_MARKER)
+        .ok_or("the generated block isn't terminated")? + begin;
+    let end = existing[end..].find('\n').map(|i| end + i + 1).unwrap_or(existing.len());
+    let old_block = &existing[begin..end];
+    let marker = old_block.lines().next().unwrap_or("");
+    let answers = InitAnswers::from_marker(marker)?;
+    let new_block = render_block(&answers, vars);
+    let without_date = |block: &str| -> Vec<String> {
+        block.lines()
+            .filter(|line| !line.starts_with("# Generated on "))
+            .map(str::to_string)
+            .collect()
+    };
+    if without_date(old_block) == without_date(&new_block) {
+        return Ok(existing.to_string());
+    }
+    Ok(format!("{}{}{}", &existing[..begin], new_block, &existing[end..]))
+}
+
+/// Asks `question` until `parse` accepts the answer.
+fn ask<T, F>(input: &mut dyn BufRead,
+             output: &mut dyn Write,
+             question: &str,
+             default: &str,
+             parse: F)
+             -> io::Result<T>
+    where F: Fn(&str) -> Result<T, String>
+{
+    loop {
+        write!(output, "{} [{}]: ", question, default)?;
+        output.flush()?;
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no answer given"));
+        }
+        let answer = answer.trim().to_lowercase();
+        let answer = if answer.is_empty() { default.to_string() } else { answer };
+        match parse(&answer) {
+            Ok(value) => return Ok(value),
+            Err(message) => writeln!(output, "  {}", message)?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> TemplateVars {
+        TemplateVars {
+            project: "demo".to_string(),
+            date: "2026-10-16".to_string(),
+        }
+    }
+
+    fn answer(answers: &str) -> (String, String) {
+        let mut output = vec![];
+        let answers = ask_answers(&mut answers.as_bytes(), &mut output, &[]).unwrap();
+        (render_config(&answers, &vars()), String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn uses_defaults_for_empty_answers() {
+        let (config, _) = answer("\n\n\n\n\n");
+        assert!(config.starts_with("# EditorConfig for demo, see https://editorconfig.org\n"));
+        assert!(config.contains("[*]\n# Indent with 4 spaces.\n\
+                                 indent_style = space\nindent_size = 4\n"));
+        assert!(config.contains("end_of_line = lf\n"));
+        assert!(!config.contains("max_line_length ="));
+    }
+
+    #[test]
+    fn adds_language_sections_and_asks_again_on_invalid_answers() {
+        let (config, output) = answer("rust, Makefile, cobol\nrs, md\nTabs\n8\nabc\n100\ncrlf\n");
+        assert!(output.contains("  unknown language cobol\n"));
+        assert!(output.contains("  answer a positive number or none\n"));
+        assert!(config.contains("indent_style = tab\nindent_size = 8\n"));
+        assert!(config.contains("end_of_line = crlf\n"));
+        assert!(config.contains("max_line_length = 100\n"));
+        assert!(config.contains("\n[*.rs]\nindent_style = space\nindent_size = 4\n"));
+        assert!(config.contains("\n[*.md]\ntrim_trailing_whitespace = false\n"));
+        assert!(config.contains("# Generated on 2026-10-16 for rust, markdown."));
+        assert!(!config.contains("Makefile"));
+        let properties = ::match_file(&config, "src/lib.rs").unwrap();
+        assert_eq!(properties.get("indent_style").unwrap(), "space");
+        assert_eq!(properties.get("end_of_line").unwrap(), "crlf");
+    }
+
+    #[test]
+    fn fails_when_input_ends() {
+        assert!(ask_answers(&mut "rust\n".as_bytes(), &mut vec![], &[]).is_err());
+    }
+
+    #[test]
+    fn suggests_detected_languages() {
+        assert_eq!(detect_languages(Path::new("src")), vec!["rust"]);
+        let detected = ["go", "yaml"];
+        let answers = ask_answers(&mut "\n\n\n\n\n".as_bytes(), &mut vec![], &detected).unwrap();
+        assert!(render_config(&answers, &vars()).contains("\n[*.go]\n"));
+    }
+
+    #[test]
+    fn updates_only_the_generated_block() {
+        let (config, _) = answer("go\n\n\n\n\n");
+        let edited = config.replace("languages=go", "languages=go,python") +
+                     "\n[vendor/**]\ncharset = unset\n";
+        let later = TemplateVars { date: "2027-01-01".to_string(), ..vars() };
+        let updated = update_config(&edited, &later).unwrap();
+        assert!(updated.contains("\n[*.py]\n"));
+        assert!(updated.contains("# Generated on 2027-01-01 for go, python."));
+        assert!(updated.ends_with("# END editorconfig init\n\n[vendor/**]\ncharset = unset\n"));
+        // Updating again only changes the date, so the file is left as is
+        let even_later = TemplateVars { date: "2028-01-01".to_string(), ..vars() };
+        assert_eq!(update_config(&updated, &even_later).unwrap(), updated);
+        assert!(update_config("root = true\n", &later).is_err());
+    }
+
+    #[test]
+    fn converts_days_to_dates() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(20742), "2026-10-16");
+        assert_eq!(civil_date(11016), "2000-02-29");
+    }
+}
@@ -0,0 +1,145 @@
+//! Whether `;` and `#` after a value start a comment, which changed in version 0.15.0 of the
+//! spec: `indent_size = 4 ; like rustfmt` sets `4` before it and `4 ; like rustfmt` after.
+
+use std::fmt;
+use std::str::FromStr;
+
+use ini::{Ini, ParseOption};
+use spec_version::SpecVersion;
+use {Error, LookupOptions};
+
+/// How config files read an unescaped `;` or `#` after a value, see
+/// `LookupOptions::inline_comments`. Quoted values and lines starting with `;` or `#` read
+/// the same either way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InlineComments {
+    /// They start a comment, as in the reference cores before 0.15.0.
+    Strip,
+    /// They are part of the value, as the spec has it since 0.15.0.
+    Keep,
+}
+
+impl InlineComments {
+    /// How inline comments are read when emulating `spec_version`: `Keep` from 0.15.0 on,
+    /// `Strip` for earlier versions and when none is given, like this crate always did.
+    pub fn for_spec_version(spec_version: Option<&str>) -> InlineComments {
+        match spec_version.and_then(|version| version.parse::<SpecVersion>().ok()) {
+            Some(version) if version >= SpecVersion(0, 15, 0) => InlineComments::Keep,
+            _ => InlineComments::Strip,
+        }
+    }
+
+    /// How `lookup` reads inline comments.
+    pub fn for_lookup(lookup: &LookupOptions) -> InlineComments {
+        lookup.inline_comments
+            .unwrap_or_else(|| InlineComments::for_spec_version(lookup.spec_version.as_deref()))
+    }
+
+    pub(crate) fn parse_option(self) -> ParseOption {
+        ParseOption { inline_comments: self == InlineComments::Strip }
+    }
+}
+
+impl FromStr for InlineComments {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<InlineComments, String> {
+        match s {
+            "strip" => Ok(InlineComments::Strip),
+            "keep" => Ok(InlineComments::Keep),
+            _ => Err(format!("unknown inline comment handling `{}`, expected strip or keep", s)),
+        }
+    }
+}
+
+impl fmt::Display for InlineComments {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            InlineComments::Strip => "strip",
+            InlineComments::Keep => "keep",
+        })
+    }
+}
+
+/// A property whose value reads differently depending on `InlineComments`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InlineComment {
+    pub section: Option<String>,
+    pub key: String,
+    /// The value with `InlineComments::Strip`.
+    pub stripped: String,
+    /// The value with `InlineComments::Keep`.
+    pub kept: String,
+    /// The line of the property, from 1.
+    pub line: usize,
+}
+
+/// Returns the properties of the config file `contents` followed by an inline comment,
+/// which are part of the value for lookups emulating 0.15.0 or later.
+pub fn find_inline_comments(contents: &str) -> Result<Vec<InlineComment>, Error> {
+    let stripped = Ini::load_from_str_opt(contents, InlineComments::Strip.parse_option())?;
+    let kept = Ini::load_from_str_opt(contents, InlineComments::Keep.parse_option())?;
+    let mut result = vec![];
+    for (section, properties) in stripped.iter() {
+        for (key, value) in properties.iter() {
+            let kept_value = match kept.get_from(section.as_ref(), key) {
+                Some(kept_value) if kept_value != value => kept_value,
+                _ => continue,
+            };
+            let line = stripped.value_span(section.clone(), key)
+                .map_or(0, |span| contents[..span.start].matches('\n').count() + 1);
+            result.push(InlineComment {
+                section: section.clone(),
+                key: key.clone(),
+                stripped: value.clone(),
+                kept: kept_value.to_string(),
+                line,
+            });
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use get_config_with;
+
+    #[test]
+    fn keeps_inline_comments_from_0_15_0_on() {
+        assert_eq!(InlineComments::for_spec_version(None), InlineComments::Strip);
+        assert_eq!(InlineComments::for_spec_version(Some("0.12.1")), InlineComments::Strip);
+        assert_eq!(InlineComments::for_spec_version(Some("0.15.0")), InlineComments::Keep);
+        assert_eq!(InlineComments::for_spec_version(Some("0.16")), InlineComments::Keep);
+        assert_eq!(InlineComments::for_spec_version(Some("1.0.0")), InlineComments::Keep);
+
+        let path = Path::new("test_files/inline_comments/file.txt").canonicalize().unwrap();
+        let strip = LookupOptions::default();
+        let keep = LookupOptions { spec_version: Some("0.15.0".to_string()), ..strip.clone() };
+        let explicit = LookupOptions {
+            inline_comments: Some(InlineComments::Strip),
+            ..keep.clone()
+        };
+        let value = |lookup: &LookupOptions| {
+            get_config_with(&path, lookup, &mut vec![]).unwrap()["indent_size"].clone()
+        };
+        assert_eq!(value(&strip), "4");
+        assert_eq!(value(&keep), "4 ; like rustfmt");
+        assert_eq!(value(&explicit), "4");
+    }
+
+    #[test]
+    fn finds_values_followed_by_inline_comments() {
+        let contents = "root = true\n# comment\n[*]\nindent_size = 4 ; like rustfmt\n\
+                        charset = \"utf-8\" # quoted\nend_of_line = lf\n";
+        assert_eq!(find_inline_comments(contents).unwrap(),
+                   vec![InlineComment {
+                            section: Some("*".to_string()),
+                            key: "indent_size".to_string(),
+                            stripped: "4".to_string(),
+                            kept: "4 ; like rustfmt".to_string(),
+                            line: 4,
+                        }]);
+    }
+}
@@ -1,35 +1,321 @@
 //! A crate that implements [editorconfig](http://editorconfig.org/).
+extern crate argparse;
 extern crate regex;
 
 extern crate ordermap;
+extern crate unicode_normalization;
+#[cfg(feature = "embed")]
+extern crate editorconfig_macros;
 
+mod audit;
+mod cache;
+mod classify;
+pub mod cli;
+mod complexity;
+mod config;
+mod derived;
+mod diagnostics;
+mod discovery;
+mod doctor;
+mod embedded;
+mod env_overrides;
+mod environment;
+mod error;
+mod files;
+mod fix;
+mod freeze;
+pub mod glob;
 mod ini;
-use regex::{Regex, Captures};
+mod init;
+mod inline_comments;
+mod lint;
+mod manifest;
+mod messages;
+mod observer;
+mod output_format;
+mod owners;
+mod parallel;
+mod path_style;
+mod preprocessing;
+mod properties;
+mod provenance;
+mod redundancy;
+mod registry;
+mod repair;
+mod reporter;
+mod resolver;
+mod simplify;
+mod spec_version;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+mod timings;
+mod tool_config;
+mod trust;
+mod uri;
+mod validation;
+mod walk;
+mod warning;
+#[cfg(any(test, feature = "watch"))]
+mod watch;
+use regex::{Regex, RegexSet, Captures};
 
 use ordermap::OrderMap;
+use unicode_normalization::UnicodeNormalization;
 
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::error::Error;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 
-/// Finds all possible `conffile`s starting from `path` until root.
-fn crawl_paths(path: &Path, conffile: &str) -> Result<Vec<PathBuf>, Box<Error>> {
-    let mut path = if path.exists() {
-        path.canonicalize()?
+use cache::{ConfigCache, ParsedConfig, ResolvedLookup};
+use complexity::expensive_sections;
+use glob::cached_glob;
+
+pub use audit::AuditLog;
+pub use classify::{classify, Classifier, FileClass};
+pub use complexity::{expensive_patterns, pattern_cost, PatternCost, PatternIssue};
+#[cfg(feature = "embed")]
+pub use editorconfig_macros::embed;
+pub use config::{Charset, Config, EndOfLine, IndentStyle};
+pub use derived::{effective_indent_size, effective_indent_unit, effective_tab_width};
+pub use diagnostics::{explain, group_diagnostics, Diagnostic, DiagnosticGroup, GroupBy, RuleInfo,
+                      RULES};
+pub use discovery::{find_all_configs, find_all_configs_with, find_config_files, ConfigFileInfo};
+pub use doctor::{diagnose, Finding, HealthReport, Severity};
+pub use embedded::{EmbeddedConfig, EmbeddedSection};
+pub use env_overrides::{EnvOverrides, CONF_FILENAME_VAR, ENV_OVERRIDE_VARS, GLOBAL_CONFIG_VAR};
+pub use environment::{GitEolSettings, GitSparseCheckout};
+pub use error::Error;
+pub use files::{FileProvider, FileStamp, MemoryFiles, OverlayFiles, RealFiles};
+pub use fix::{verify_idempotent, Fix, UnsettledFix};
+pub use freeze::freeze_config;
+pub use glob::Glob;
+pub use ini::{Document, DocumentProperty, DocumentSection};
+pub use inline_comments::{find_inline_comments, InlineComment, InlineComments};
+pub use lint::{lint_config_files, lint_tree};
+pub use manifest::{group_by_config, properties_fingerprint, ConfigGroup, Manifest};
+pub use messages::{MessageArgs, MessageCatalog, DEFAULT_MESSAGES};
+pub use observer::ResolveObserver;
+pub use output_format::OutputFormat;
+pub use owners::{CodeOwners, CODEOWNERS_LOCATIONS};
+pub use path_style::PathStyle;
+pub use preprocessing::PreprocessingProfile;
+pub use properties::Properties;
+pub use provenance::{explain_lookup, get_config_provenance, Assignment, ConsultedConfig,
+                     Explanation, PropertyTrace, ResolvedProperty};
+pub use redundancy::{redundant_settings, remove_redundant, RedundantSetting};
+pub use registry::{PropertyRegistry, PropertySpec, ValueType};
+pub use repair::{repair, Repair};
+pub use reporter::{write_report, JsonReporter, JunitReporter, RdjsonReporter, ReportOptions,
+                   Reporter, SarifReporter, TextReporter};
+pub use resolver::{Resolver, ResolverBuilder};
+pub use simplify::{patterns_equivalent, simplify_config, simplify_pattern, Simplification};
+pub use spec_version::SpecVersion;
+pub use timings::{Phase, TimingEntry, Timings};
+pub use tool_config::{ToolConfig, TOOL_CONFIG_FILE_NAME};
+pub use uri::{get_config_for_uri, UriMap};
+pub use validation::{find_invalid_values, find_misspelled_keys, find_newer_settings, InvalidValue,
+                     MisspelledKey, NewerSetting};
+pub use walk::{walk_files, WalkOptions, VCS_DIRS};
+pub use warning::{Warning, WarningAggregator, WarningKind};
+#[cfg(any(test, feature = "watch"))]
+pub use watch::Watcher;
+
+/// Sections whose name is longer than this many characters are ignored, unless
+/// `LookupOptions::max_section_name_length` says otherwise.
+pub const MAX_SECTION_NAME_LENGTH: usize = 4096;
+/// Properties whose key is longer than this many characters are ignored, unless
+/// `LookupOptions::max_key_length` says otherwise. The limit applies to each part of keys
+/// namespaced with dots, like `dotnet_diagnostic.ca1000.severity`.
+pub const MAX_KEY_LENGTH: usize = 50;
+/// Properties whose value is longer than this many characters are ignored, unless
+/// `LookupOptions::max_value_length` says otherwise.
+pub const MAX_VALUE_LENGTH: usize = 255;
+
+/// The environment variable `env::home_dir` reads the home directory from.
+#[cfg(windows)]
+const HOME_VAR: &str = "USERPROFILE";
+#[cfg(not(windows))]
+const HOME_VAR: &str = "HOME";
+
+/// The optional features of the crate this build was compiled with.
+pub fn active_features() -> Vec<&'static str> {
+    let features = [("core-cli", cfg!(feature = "core-cli")),
+                    ("embed", cfg!(feature = "embed")),
+                    ("test-support", cfg!(feature = "test-support")),
+                    ("watch", cfg!(feature = "watch"))];
+    features.iter().filter(|&&(_, active)| active).map(|&(name, _)| name).collect()
+}
+
+/// The names of the config files `lookup` reads, in the order they override each other.
+fn conffile_names(lookup: &LookupOptions) -> Vec<&str> {
+    let mut conffiles = vec![lookup.conffile.as_str()];
+    conffiles.extend(lookup.conffile_overrides.iter().map(String::as_str));
+    conffiles
+}
+
+/// Finds all possible config files starting from `path` until root, in the order they have
+/// to be read: closest directory first and, within a directory, the last of the
+/// `conffile_names` first.
+fn crawl_paths(path: &Path, lookup: &LookupOptions) -> Result<Vec<PathBuf>, Error> {
+    Ok(crawl_from(lookup_path(path, lookup)?, lookup))
+}
+
+/// `crawl_paths` for the files directly in the directory `dir`: the config files of `dir`
+/// itself, then of its parents.
+fn crawl_dir_paths(dir: &Path, lookup: &LookupOptions) -> Result<Vec<PathBuf>, Error> {
+    let dir = lookup_path(dir, lookup)?;
+    Ok(crawl_until(Some(&dir), lookup, None, &mut |_| false))
+}
+
+/// `path` as lookups crawl from it, canonical when `lookup` canonicalizes and it exists.
+fn lookup_path(path: &Path, lookup: &LookupOptions) -> Result<PathBuf, Error> {
+    if lookup.canonicalize && lookup.files.exists(path) {
+        Ok(lookup.files.canonicalize(path)?)
     } else {
-        path.to_path_buf()
-    };
+        Ok(path.to_path_buf())
+    }
+}
+
+/// `crawl_paths` for a path already given by `lookup_path`.
+fn crawl_from(path: PathBuf, lookup: &LookupOptions) -> Vec<PathBuf> {
+    crawl_until(path.parent(), lookup, None, &mut |_| false)
+}
 
+/// Finds the config files of `dir` and of its parents like `crawl_from` does for the files
+/// in `dir`, crawling nothing for `None`. Skips the directories `cache` knows hold no config
+/// file, and stops after the config file for which `stop` returns `true`.
+fn crawl_until(dir: Option<&Path>,
+               lookup: &LookupOptions,
+               cache: Option<&ConfigCache>,
+               stop: &mut dyn FnMut(&Path) -> bool)
+               -> Vec<PathBuf> {
+    let conffiles = conffile_names(lookup);
+    let files = &lookup.files;
+    let home = if lookup.stop_at_home { home_dir(lookup) } else { None };
     let mut result = vec![];
-    while path.parent().is_some() {
-        let mut adjacent_file = path.clone();
-        adjacent_file.set_file_name(conffile);
-        path.pop();
-        if !adjacent_file.exists() {
-            continue;
+    // The file system of the directories crawled so far
+    let mut device = None;
+    // The directory crawled last, below `dir`
+    let mut below: Option<&Path> = None;
+    let mut next = dir;
+    while let Some(dir) = next {
+        if lookup.one_file_system {
+            let dir_device = files.device(dir);
+            if device.is_some() && dir_device.is_some() && dir_device != device {
+                if let (Some(audit), Some(below)) = (lookup.audit.as_ref(), below) {
+                    audit.record_stop(below, "its parent directory is on another file system");
+                }
+                break;
+            }
+            device = dir_device.or(device);
         }
-        result.push(adjacent_file);
+        let stamp = cache.and_then(|_| files.stamp(dir));
+        if !cache.is_some_and(|cache| cache.known_without_config(dir, stamp)) {
+            let found = result.len();
+            for conffile in conffiles.iter().rev() {
+                let adjacent_file = dir.join(conffile);
+                if files.exists(&adjacent_file) {
+                    result.push(adjacent_file);
+                } else if lookup.conffile_any_case {
+                    result.extend(find_any_case(&adjacent_file, files.as_ref()));
+                }
+            }
+            if result.len() == found {
+                if let Some(cache) = cache {
+                    cache.record_without_config(dir, stamp);
+                }
+            } else if result[found..].iter().any(|conf_file| stop(conf_file)) {
+                break;
+            }
+        }
+        if home.as_deref() == Some(dir) {
+            if let Some(ref audit) = lookup.audit {
+                audit.record_stop(dir, "it's the home directory");
+            }
+            break;
+        }
+        below = Some(dir);
+        next = dir.parent();
+    }
+    result
+}
+
+/// The home directory of the user, canonical when `lookup` canonicalizes.
+fn home_dir(lookup: &LookupOptions) -> Option<PathBuf> {
+    let home = lookup.home_dir.clone().or_else(|| env_home_dir(lookup))?;
+    if lookup.canonicalize {
+        Some(lookup.files.canonicalize(&home).unwrap_or(home))
+    } else {
+        Some(home)
     }
-    return Ok(result);
+}
+
+/// The home directory of the environment, recording the variable read in the audit log of
+/// `lookup`.
+fn env_home_dir(lookup: &LookupOptions) -> Option<PathBuf> {
+    if let Some(ref audit) = lookup.audit {
+        audit.record_env_var(HOME_VAR);
+    }
+    env::home_dir()
+}
+
+/// The existing config files giving the properties no other config file sets, in the order
+/// they apply: `global_config`, then the config file of the user with `user_config`.
+fn fallback_configs(lookup: &LookupOptions) -> Vec<PathBuf> {
+    let user_config = if lookup.user_config {
+        if let Some(ref audit) = lookup.audit {
+            audit.record_env_var("XDG_CONFIG_HOME");
+        }
+        let xdg_config_home = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from);
+        let home = lookup.home_dir.clone().or_else(|| env_home_dir(lookup));
+        user_config_candidates(home, xdg_config_home)
+            .into_iter()
+            .find(|path| lookup.files.exists(path))
+    } else {
+        None
+    };
+    lookup.global_config
+        .iter()
+        .filter(|path| lookup.files.exists(path))
+        .cloned()
+        .chain(user_config)
+        .map(|path| if lookup.canonicalize {
+            lookup.files.canonicalize(&path).unwrap_or(path)
+        } else {
+            path
+        })
+        .collect()
+}
+
+/// Where the config file of the user may be, in order of preference: in the XDG config
+/// directory, `~/.config` unless `XDG_CONFIG_HOME` is set to an absolute path, and then in
+/// the home directory.
+fn user_config_candidates(home: Option<PathBuf>, xdg_config_home: Option<PathBuf>) -> Vec<PathBuf> {
+    let xdg_config_home = xdg_config_home.filter(|dir| dir.is_absolute())
+        .or_else(|| home.as_ref().map(|home| home.join(".config")));
+    xdg_config_home.map(|dir| dir.join("editorconfig").join("config"))
+        .into_iter()
+        .chain(home.map(|home| home.join(".editorconfig")))
+        .collect()
+}
+
+/// Finds the file named like `path` but for the case of ASCII letters, e.g. `.EditorConfig`
+/// for `.editorconfig`. The first name in byte order wins if there are several.
+fn find_any_case(path: &Path, files: &dyn FileProvider) -> Option<PathBuf> {
+    let (dir, name) = (path.parent()?, path.file_name()?.to_str()?);
+    let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    files.file_names(dir)
+        .ok()?
+        .into_iter()
+        .filter(|n| n.eq_ignore_ascii_case(name))
+        .min()
+        .map(|n| path.with_file_name(n))
 }
 
 fn has_imbalanced_braces(text: &str) -> bool {
@@ -46,7 +332,7 @@ fn has_imbalanced_braces(text: &str) -> bool {
             }
         }
     }
-    return depth != 0;
+    depth != 0
 }
 
 fn translate_alternation(caps: &Captures) -> String {
@@ -61,7 +347,18 @@ fn translate_alternation(caps: &Captures) -> String {
     format!("(?:{}){}", cases, quantifier)
 }
 
-fn glob_match(pattern: &String, candidate: &String) -> bool {
+/// A glob translated to a regex. Numeric ranges become capture groups whose values
+/// have to be checked against `numeric_ranges` after matching.
+#[derive(Clone, Debug)]
+struct TranslatedGlob {
+    regex: String,
+    numeric_ranges: Vec<String>,
+}
+
+/// Patterns and paths are matched as Unicode text: like in the other EditorConfig cores,
+/// `?`, `*` and `[...]` work on code points, not bytes, so `?` matches `é` but not the
+/// two code points of a decomposed `é`.
+fn translate_glob(pattern: &str) -> TranslatedGlob {
     let orig_had_slash = pattern.contains('/');
     // Step 1. Escape the crap out of the existing pattern
     let pattern = pattern.replace(".", r"\.");
@@ -79,11 +376,18 @@ fn glob_match(pattern: &String, candidate: &String) -> bool {
     // Since all other input groups are non-capturing, just make sure every capture group in the output
     // matches the corresponding range.
     let numeric_range_regex = Regex::new(r"\{(-?\d+\\\.\\\.-?\d+)\}").unwrap();
-    let has_numeric_ranges = numeric_range_regex.is_match(&pattern);
-    let numeric_ranges: Vec<_> = numeric_range_regex.captures_iter(&pattern).collect();
+    let numeric_ranges: Vec<_> = numeric_range_regex.captures_iter(&pattern)
+        .map(|caps| caps[1].to_string())
+        .collect();
     let pattern = numeric_range_regex.replace_all(&pattern, r"(0|-?[1-9]\d*)");
     // If we had /**/, make the directory and leading / optional
     let pattern = pattern.replace("/.*/", "(?:/.*)?/");
+    // Paths are relative to the config file, without a leading /, so a leading **/ or /**/
+    // matches any number of directories including none, like foo matching **/foo
+    let pattern = match pattern.strip_prefix("(?:/.*)?/").or_else(|| pattern.strip_prefix(".*/")) {
+        Some(rest) => format!("(?:.*/)?{}", rest),
+        None => pattern,
+    };
     let pattern = pattern.replace("[!", "[^");
     // Handle single-option "alternation" manually earlier
     let fake_alternation_regex = Regex::new(r"\{([^,]+)\}").unwrap();
@@ -111,14 +415,19 @@ fn glob_match(pattern: &String, candidate: &String) -> bool {
     } else {
         "(?:.*?/)?"
     };
-    let pattern = format!("^{}{}$", leading_expr, pattern);
-    // Step 3. Actually do the testing
-    let final_regex = Regex::new(&pattern).unwrap();
-    if has_numeric_ranges && final_regex.is_match(candidate) {
+    TranslatedGlob {
+        regex: format!("^{}{}$", leading_expr, pattern),
+        numeric_ranges,
+    }
+}
+
+/// Step 3. Actually do the testing, `final_regex` being compiled from `glob.regex`.
+fn matches_translated(final_regex: &Regex, glob: &TranslatedGlob, candidate: &str) -> bool {
+    if !glob.numeric_ranges.is_empty() && final_regex.is_match(candidate) {
         let caps: Vec<_> = final_regex.captures_iter(candidate).collect();
-        for (num, range_spec) in caps.iter().zip(numeric_ranges.iter()) {
+        for (num, range_spec) in caps.iter().zip(glob.numeric_ranges.iter()) {
             if let Ok(num) = num.get(1).unwrap().as_str().parse::<i32>() {
-                let ends: Vec<Result<i32, _>> = range_spec.get(1).unwrap().as_str().split(r"\.\.").map(|x| x.parse()).collect();
+                let ends: Vec<Result<i32, _>> = range_spec.split(r"\.\.").map(|x| x.parse()).collect();
                 if let Ok(min) = ends[0] {
                     if let Ok(max) = ends[1] {
                         if min > num || num > max {
@@ -136,12 +445,165 @@ fn glob_match(pattern: &String, candidate: &String) -> bool {
         }
         return true;
     }
-    return final_regex.is_match(candidate);
+    final_regex.is_match(candidate)
+}
+
+/// Returns the properties the config file at `conf_file` gives to `target`, parsing it
+/// unless it's in `cache` already and its stamp didn't change since.
+fn parse_config(target: &Path,
+                conf_file: &Path,
+                lookup: &LookupOptions,
+                warnings: &mut Vec<Warning>,
+                cache: &ConfigCache)
+                -> Result<OrderMap<String, String>, Error> {
+    let parsed = parsed_config(conf_file, lookup, warnings, cache)?;
+    match_config(target, conf_file, &parsed.ini, lookup, warnings)
+}
+
+/// Parses the config file at `conf_file` unless it's in `cache` already and its stamp didn't
+/// change since, adding the warnings parsing it gave to `warnings`.
+pub(crate) fn parsed_config(conf_file: &Path,
+                            lookup: &LookupOptions,
+                            warnings: &mut Vec<Warning>,
+                            cache: &ConfigCache)
+                            -> Result<Arc<ParsedConfig>, Error> {
+    let parsed = match cache.get(conf_file) {
+        Some(ref parsed) if parsed.stamp == lookup.files.stamp(conf_file) => parsed.clone(),
+        _ => {
+            // Taken first, so changes made while reading are seen by the next lookup
+            let stamp = lookup.files.stamp(conf_file);
+            let mut parse_warnings = vec![];
+            let ini = load_config(conf_file, lookup, &mut parse_warnings)?;
+            cache.insert(conf_file,
+                         ParsedConfig {
+                             ini,
+                             warnings: parse_warnings,
+                             stamp,
+                         })
+        }
+    };
+    warnings.extend(parsed.warnings.iter().cloned());
+    Ok(parsed)
+}
+
+/// Reads and parses the config file at `conf_file`, repairing it if `lookup` says so.
+fn load_config(conf_file: &Path,
+               lookup: &LookupOptions,
+               warnings: &mut Vec<Warning>)
+               -> Result<ini::Ini, Error> {
+    let started = Instant::now();
+    let contents = lookup.files.read(conf_file)?;
+    let parse_option = InlineComments::for_lookup(lookup).parse_option();
+    let ini_data = if lookup.repair_malformed {
+        let (repaired, repairs) = repair(&contents);
+        // A single warning, as warnings of a file are aggregated by kind
+        if !repairs.is_empty() {
+            let repairs: Vec<_> = repairs.iter()
+                .map(|repair| format!("line {}: {}", repair.line, repair.message))
+                .collect();
+            warnings.push(Warning::new(conf_file,
+                                       WarningKind::Repaired,
+                                       format!("repaired, {}", repairs.join("; "))));
+        }
+        ini::Ini::load_from_str_opt(&repaired, parse_option)
+            .map_err(|err| Error::from(err).in_file(conf_file))?
+    } else {
+        ini::Ini::load_from_str_opt(&contents, parse_option)
+            .map_err(|err| Error::from(err).in_file(conf_file))?
+    };
+    if let Some(ref timings) = lookup.timings {
+        timings.record(conf_file, Phase::Parse, started.elapsed());
+        timings.record_expensive_patterns(conf_file, expensive_sections(&ini_data));
+    }
+    Ok(ini_data)
 }
 
-fn parse_config(target: &Path, conf_file: &Path) -> Result<OrderMap<String, String>, Box<Error>> {
+/// Returns the properties the parsed config file at `conf_file` gives to `target`.
+fn match_config(target: &Path,
+                conf_file: &Path,
+                ini_data: &ini::Ini,
+                lookup: &LookupOptions,
+                warnings: &mut Vec<Warning>)
+                -> Result<OrderMap<String, String>, Error> {
     let context = conf_file.parent().unwrap();
-    let ini_data = ini::Ini::load_from_file(conf_file)?;
+    match_config_in(target, context, conf_file, ini_data, lookup, warnings)
+}
+
+/// `match_config`, with section patterns relative to `context` instead of the directory of
+/// the config file.
+fn match_config_in(target: &Path,
+                   context: &Path,
+                   conf_file: &Path,
+                   ini_data: &ini::Ini,
+                   lookup: &LookupOptions,
+                   warnings: &mut Vec<Warning>)
+                   -> Result<OrderMap<String, String>, Error> {
+    let target = target.strip_prefix(context)?;
+    // Paths that aren't valid Unicode only match the sections their valid parts match
+    let target = target.to_string_lossy().into_owned();
+    #[cfg(windows)]
+    let target = target.replace("\\", "/");
+    let started = Instant::now();
+    let mut on_match = |section: &str| {
+        if let Some(ref observer) = lookup.observer {
+            observer.section_matched(conf_file, section);
+        }
+    };
+    let mut on_invalid = |section: &str, err: &str| {
+        warnings.push(Warning::new(conf_file,
+                                   WarningKind::InvalidPattern,
+                                   format!("section [{}] can't be translated to a regex, matching \
+                                            it literally instead: {}",
+                                           section,
+                                           err)));
+    };
+    let result = match_sections(ini_data, &target, lookup, &mut on_match, &mut on_invalid);
+    for section in ini_data.sections().flatten() {
+        if section.chars().count() > lookup.max_section_name_length {
+            warnings.push(Warning::new(conf_file,
+                                       WarningKind::TooLong,
+                                       format!("ignored a section whose name is longer than {} \
+                                                characters",
+                                               lookup.max_section_name_length)));
+        }
+    }
+    if let Some(ref timings) = lookup.timings {
+        timings.record(conf_file, Phase::Match, started.elapsed());
+    }
+    Ok(result)
+}
+
+/// Lowercases `text` when `lookup` isn't case sensitive.
+fn case_folded(text: &str, lookup: &LookupOptions) -> String {
+    if lookup.case_sensitive {
+        text.to_string()
+    } else {
+        text.to_lowercase()
+    }
+}
+
+/// Whether `section` names `target` without any special character, like a pattern: a name
+/// without `/` matches files of that name in any directory.
+fn matches_literally(section: &str, target: &str) -> bool {
+    if section.contains('/') {
+        section.trim_start_matches('/') == target
+    } else {
+        target.rsplit('/').next() == Some(section)
+    }
+}
+
+/// Returns the properties of every section of `ini_data` matching `target`, a path
+/// relative to the config file using `/` as separator. `on_match` gets the name of
+/// every matching section.
+///
+/// Sections whose translated pattern isn't a valid regex are compared to the path as is,
+/// and given to `on_invalid` with the regex error.
+fn match_sections(ini_data: &ini::Ini,
+                  target: &str,
+                  lookup: &LookupOptions,
+                  on_match: &mut dyn FnMut(&str),
+                  on_invalid: &mut dyn FnMut(&str, &str))
+                  -> OrderMap<String, String> {
     let mut result = OrderMap::new();
     if let Some(general) = ini_data.section::<String>(None) {
         if let Some(root) = general.get("root") {
@@ -150,58 +612,180 @@ fn parse_config(target: &Path, conf_file: &Path) -> Result<OrderMap<String, Stri
             }
         }
     }
-    let target = target.strip_prefix(&context)?;
-    let target = target.as_os_str().to_os_string().into_string().unwrap();
-    #[cfg(windows)]
-    let target = target.replace("\\", "/");
-    for (label, data) in ini_data.iter() {
-        if let Some(ref label) = *label {
-            if label.len() > 4096 {
-                continue;
+    let target: String = if lookup.normalize_unicode {
+        target.nfc().collect()
+    } else {
+        target.to_string()
+    };
+    let target = case_folded(&target, lookup);
+    let sections: Vec<_> = ini_data.iter()
+        .filter_map(|(label, data)| label.as_ref().map(|label| (label, data)))
+        .filter(|&(label, _)| label.chars().count() <= lookup.max_section_name_length)
+        .map(|(label, data)| {
+            let glob = if lookup.normalize_unicode {
+                cached_glob(&label.nfc().collect::<String>(), lookup.case_sensitive)
+            } else {
+                cached_glob(label, lookup.case_sensitive)
+            };
+            (label, glob, data)
+        })
+        .collect();
+    // Match all sections at once, except for those with numeric ranges which need
+    // their captures checked one by one, and invalid ones
+    let in_set = |glob: &Result<Glob, String>| {
+        glob.as_ref().is_ok_and(|glob| !glob.has_numeric_ranges())
+    };
+    // Patterns compiling on their own can still be too big together, those sections are
+    // then matched one by one
+    let set_matches = RegexSet::new(sections.iter()
+            .filter_map(|section| section.1.as_ref().ok())
+            .filter(|glob| !glob.has_numeric_ranges())
+            .map(Glob::regex_source))
+        .ok()
+        .map(|set| set.matches(&target));
+    let mut set_index = 0;
+    for (label, glob, data) in sections {
+        let matched = match (glob, set_matches.as_ref()) {
+            (Err(ref err), _) => {
+                on_invalid(label, err);
+                matches_literally(&case_folded(label, lookup), &target)
             }
-            if glob_match(label, &target) {
-                for (k, v) in data.iter() {
-                    result.insert(k.clone(), v.clone());
-                }
+            (ref glob, Some(set_matches)) if in_set(glob) => {
+                set_index += 1;
+                set_matches.matched(set_index - 1)
+            }
+            (Ok(glob), _) => glob.matches(&target),
+        };
+        if matched {
+            on_match(label);
+            for (k, v) in data.iter() {
+                result.insert(k.clone(), v.clone());
             }
         }
     }
 
-    // Preprocessing may or may not actually be part of the spec
-    // so I'm stealing this from editorconfig-core-py
-    if let Some(indent_style) = result.clone().get("indent_style") {
-        if indent_style == "tab" {
-            if result.get("indent_size").is_none() {
-                result.insert("indent_size".to_string(), "tab".to_string());
+    // Preprocessing may or may not actually be part of the spec, hosts pick their rules
+    PreprocessingProfile::for_lookup(lookup).apply(&mut result);
+    result
+}
+
+/// Returns the properties a single config file gives to `relative_path`, a path relative
+/// to the config file using `/` as separator.
+///
+/// Parent directories aren't looked at, which makes this handy for scripts and tests.
+///
+/// ```
+/// let properties = editorconfig::match_file("[*.rs]\nindent_size = 4\n", "src/lib.rs").unwrap();
+/// assert_eq!(properties.get("indent_size").unwrap(), "4");
+/// ```
+pub fn match_file(config_contents: &str,
+                  relative_path: &str)
+                  -> Result<OrderMap<String, String>, Error> {
+    let ini_data = ini::Ini::load_from_str(config_contents)?;
+    let properties = match_sections(&ini_data,
+                                    relative_path,
+                                    &LookupOptions::default(),
+                                    &mut |_| {},
+                                    &mut |_, _| {});
+    Ok(clean_properties(&properties, &LookupOptions::default(), &mut |_| {}))
+}
+
+/// A property as written in a config file, see `property_spans`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropertySpan {
+    /// The section the property is in, `None` before the first section.
+    pub section: Option<String>,
+    /// The key, as written.
+    pub key: String,
+    /// The value, with escape sequences resolved.
+    pub value: String,
+    /// Byte range of the value in the config file.
+    pub span: Range<usize>,
+}
+
+/// Returns every property of a config file with the bytes its value was read from, so
+/// tools pointing at or rewriting values agree with lookups about where they are.
+///
+/// Values are trimmed, and an unquoted value ends at a `;` or `#` starting an inline
+/// comment. Quotes around a value aren't part of it, nor of its range. When a key is
+/// repeated in a section, the last value is returned, as it's the one lookups use.
+///
+/// ```
+/// let contents = "[*.rs]\nindent_size = 4 ; like rustfmt\n";
+/// let properties = editorconfig::property_spans(contents).unwrap();
+/// assert_eq!(&contents[properties[0].span.clone()], "4");
+/// ```
+pub fn property_spans(config_contents: &str) -> Result<Vec<PropertySpan>, Error> {
+    let ini_data = ini::Ini::load_from_str(config_contents)?;
+    let mut result = vec![];
+    for (section, properties) in &ini_data {
+        for (key, value) in properties {
+            if let Some(span) = ini_data.value_span(section.clone(), key) {
+                result.push(PropertySpan {
+                    section: section.clone(),
+                    key: key.clone(),
+                    value: value.clone(),
+                    span,
+                });
             }
         }
     }
-    if let Some(indent_size) = result.clone().get("indent_size") {
-        if indent_size != "tab" {
-            if result.get("tab_width").is_none() {
-                result.insert("tab_width".to_string(), indent_size.clone());
-            }
+    Ok(result)
+}
+
+/// Lowercases keys and the values `lookup.property_registry` says to, and drops `root` and
+/// the properties that exceed the length limits of `lookup`, telling `on_too_long` why.
+fn clean_properties(properties: &OrderMap<String, String>,
+                    lookup: &LookupOptions,
+                    on_too_long: &mut dyn FnMut(String))
+                    -> OrderMap<String, String> {
+    let mut result = OrderMap::new();
+    for (k, v) in properties.iter() {
+        let k = k.to_lowercase();
+        let v = if lookup.property_registry.lowercases(&k) {
+            v.to_lowercase()
         } else {
-            if let Some(tab_width) = result.clone().get("tab_width") {
-                result.insert("indent_size".to_string(), tab_width.clone());
-            }
+            v.clone()
+        };
+        // Namespaced keys like `dotnet_diagnostic.ca1000.severity` are limited part by part
+        if k.split('.').any(|part| part.chars().count() > lookup.max_key_length) {
+            on_too_long(format!("ignored `{}`, keys are limited to {} characters",
+                                k,
+                                lookup.max_key_length));
+            continue;
+        }
+        if v.chars().count() > lookup.max_value_length {
+            on_too_long(format!("ignored the value of `{}`, values are limited to {} characters",
+                                k,
+                                lookup.max_value_length));
+            continue;
+        }
+        if !result.contains_key(&k) && k != "root" {
+            result.insert(k, v);
         }
     }
-    Ok(result)
+    result
 }
 
-fn is_known_key(key: &str) -> bool {
-    let known_keys = ["indent_style",
-                      "indent_size",
-                      "tab_width",
-                      "end_of_line",
-                      "charset",
-                      "trim_trailing_whitespace",
-                      "insert_final_newline"];
-    known_keys.contains(&key)
+/// Drops the properties set to `unset`, which removes the values config files further up
+/// or fallback configs give them, as the spec says. Done once all config files are merged,
+/// so the closest `unset` wins like any other value.
+fn remove_unset(properties: &mut OrderMap<String, String>) {
+    properties.retain(|_, value| !value.eq_ignore_ascii_case("unset"));
 }
 
-
+/// Returns the plain number a lenient lookup reads `value` of `key` as, if it's a size
+/// written with a unit like `4ch` or `100 chars`.
+fn lenient_value(key: &str, value: &str) -> Option<String> {
+    if !["indent_size", "tab_width", "max_line_length"].contains(&key) {
+        return None;
+    }
+    static SIZE_WITH_UNIT: OnceLock<Regex> = OnceLock::new();
+    let size_with_unit_regex = SIZE_WITH_UNIT.get_or_init(|| {
+        Regex::new(r"^(\d+)\s*(ch|chars?|characters?|cols?|columns?|spaces?)$").unwrap()
+    });
+    size_with_unit_regex.captures(&value.trim().to_lowercase()).map(|caps| caps[1].to_string())
+}
 
 /// Finds the configuration that applies to the file passed in `file_path`.
 ///
@@ -228,7 +812,7 @@ fn is_known_key(key: &str) -> bool {
 ///
 /// - when the `file_path` is malformed (check `std::fs::canonicalize` docs) or is a directory.
 ///
-pub fn get_config(file_path: &Path) -> Result<OrderMap<String, String>, Box<Error>> {
+pub fn get_config(file_path: &Path) -> Result<OrderMap<String, String>, Error> {
     get_config_conffile(file_path, ".editorconfig")
 }
 
@@ -236,28 +820,402 @@ pub fn get_config(file_path: &Path) -> Result<OrderMap<String, String>, Box<Erro
 /// # MAINLY USED FOR TESTING AND INTERNAL USE, CHECK `get_config`.
 ///
 /// Looks for config data in given filename; in normal operation this will be ".editorconfig".
+/// `Resolver::builder()` sets this and the other lookup options.
 pub fn get_config_conffile(file_path: &Path,
                            conffile: &str)
-                           -> Result<OrderMap<String, String>, Box<Error>> {
-    let paths = crawl_paths(file_path, conffile)?;
+                           -> Result<OrderMap<String, String>, Error> {
+    let options = LookupOptions {
+        conffile: conffile.to_string(),
+        ..LookupOptions::default()
+    };
+    get_config_with(file_path, &options, &mut vec![])
+}
+
+/// Like `get_config_conffile`, returning what the reference core of `version` would, like
+/// `-b`. `None` is the same as `get_config_conffile`.
+pub fn get_config_conffile_version(file_path: &Path,
+                                   conffile: &str,
+                                   version: Option<&str>)
+                                   -> Result<OrderMap<String, String>, Error> {
+    let options = LookupOptions {
+        conffile: conffile.to_string(),
+        spec_version: version.map(str::to_string),
+        ..LookupOptions::default()
+    };
+    get_config_with(file_path, &options, &mut vec![])
+}
+
+/// Options controlling how `get_config_with` looks up and reads config files.
+#[derive(Clone, Debug)]
+pub struct LookupOptions {
+    /// Name of the config files to look for, `.editorconfig` by default.
+    pub conffile: String,
+    /// Names of files read after `conffile` in the same directory, e.g. `.editorconfig.user`
+    /// for settings that aren't checked in. Each file overrides the ones before it, closer
+    /// directories still override farther ones, and `root = true` in any of them stops the
+    /// lookup once the whole directory is read.
+    pub conffile_overrides: Vec<String>,
+    /// Skip config files which can't be read because of their permissions instead of failing.
+    /// Every skipped file is reported as a `WarningKind::Unreadable` warning.
+    pub skip_unreadable: bool,
+    /// Where to record how long parsing and matching each config file took, if anywhere.
+    pub timings: Option<Arc<Timings>>,
+    /// Where to record which config files were read, if anywhere.
+    pub audit: Option<Arc<AuditLog>>,
+    /// What to notify of the progress of the lookup, if anything.
+    pub observer: Option<Arc<dyn ResolveObserver>>,
+    /// Ignore config files outside of this directory, typically the workspace root.
+    /// Every ignored file is reported as a `WarningKind::Untrusted` warning.
+    pub trust_root: Option<PathBuf>,
+    /// Ignore config files owned by another user than the one owning `trust_root` (or the
+    /// target file if there's no trust root). Only supported on Unix.
+    /// Every ignored file is reported as a `WarningKind::Untrusted` warning.
+    pub trust_same_owner: bool,
+    /// Compare patterns and paths in Unicode normalization form C, so that patterns match
+    /// file names whatever the form they're written in. On by default on macOS, whose file
+    /// systems store names decomposed (NFD) while editors usually write composed ones (NFC).
+    pub normalize_unicode: bool,
+    /// Read sizes written with a unit, like `indent_size = 4ch` or `max_line_length = 100 chars`,
+    /// as plain numbers. Every normalized value is reported as a `WarningKind::LenientValue`
+    /// warning. Otherwise such values are returned as is, for consumers to reject.
+    pub lenient_values: bool,
+    /// Read malformed config files as best as possible instead of failing, e.g. by closing
+    /// unterminated section headers, see `repair`. The repaired lines are reported in a
+    /// `WarningKind::Repaired` warning per config file.
+    pub repair_malformed: bool,
+    /// Don't read config files above this directory, e.g. the root of a repository so that
+    /// settings of the user's home directory don't leak in.
+    pub stop_dir: Option<PathBuf>,
+    /// The version of the EditorConfig core to emulate, the latest if `None`. It changes
+    /// what `preprocessing` and `inline_comments` do by default, see
+    /// `PreprocessingProfile::for_lookup`.
+    pub spec_version: Option<String>,
+    /// The oldest version of the EditorConfig core the editors of the project use, so that
+    /// `lint` reports the properties and values they ignore, see `PropertySpec::since`.
+    /// `None`, the default, reports none.
+    pub min_spec_version: Option<String>,
+    /// Match section patterns against paths case sensitively, as the specification says.
+    /// Turning it off suits case insensitive file systems. On by default.
+    pub case_sensitive: bool,
+    /// Resolve symbolic links and `..` in the looked up path before looking for config
+    /// files, which is what most editors expect. On by default; turning it off looks
+    /// for config files next to the path as given.
+    pub canonicalize: bool,
+    /// Fail with `Error::MissingConfig` when no config file applies to the path, for
+    /// projects where every file has to be covered.
+    pub require_config: bool,
+    /// Also read config files whose name only differs in case, like `.EditorConfig`, as
+    /// left behind by copies from case insensitive file systems. Every such file is
+    /// reported as a `WarningKind::MiscasedName` warning suggesting to rename it.
+    pub conffile_any_case: bool,
+    /// Don't look for config files on another file system than the directory of the path,
+    /// so a project on a network mount doesn't read the config files of the host, slowly.
+    /// Only Unix tells file systems apart, see `FileProvider::device`.
+    pub one_file_system: bool,
+    /// Don't read config files above the home directory of the user when looking up a file
+    /// below it, like the `/etc/.editorconfig` of the system. Files outside of the home
+    /// directory are looked up as usual.
+    pub stop_at_home: bool,
+    /// The home directory of the user, for `stop_at_home` and `user_config`. `None`, the
+    /// default, reads it from the environment.
+    pub home_dir: Option<PathBuf>,
+    /// Which properties are filled in from the others, like `indent_size` from
+    /// `indent_style = tab`. The reference cores' rules by default.
+    pub preprocessing: PreprocessingProfile,
+    /// Whether `;` and `#` after a value start a comment or are part of the value. `None`
+    /// follows `spec_version`, see `InlineComments::for_spec_version`.
+    pub inline_comments: Option<InlineComments>,
+    /// A config file read after all the others, whose properties files only get when no
+    /// other config file sets them, e.g. defaults of the user. It's read whatever
+    /// `root = true` or `stop_dir` say, and skipped if it doesn't exist. Its sections match
+    /// paths from the root of the file system, so `[*.md]` applies to every Markdown file.
+    pub global_config: Option<PathBuf>,
+    /// Also read the config file of the user after all the others, like `global_config`:
+    /// `$XDG_CONFIG_HOME/editorconfig/config`, `~/.config/editorconfig/config` or
+    /// `~/.editorconfig`, the first that exists. `false` by default.
+    pub user_config: bool,
+    /// Sections whose name is longer than this many characters are ignored with a
+    /// `WarningKind::TooLong` warning, `MAX_SECTION_NAME_LENGTH` by default as in the spec.
+    pub max_section_name_length: usize,
+    /// Properties whose key, or a part of a key namespaced with dots, is longer than this
+    /// many characters are ignored with a warning, `MAX_KEY_LENGTH` by default. Raise it for
+    /// keys like those of .NET analyzers.
+    pub max_key_length: usize,
+    /// Properties whose value is longer than this many characters are ignored with a
+    /// warning, `MAX_VALUE_LENGTH` by default.
+    pub max_value_length: usize,
+    /// The properties whose values are lowercased, and which `lint` checks the values of.
+    /// Those of the spec by default, register others to have them normalized and checked too.
+    pub property_registry: PropertyRegistry,
+    /// How config files are found and read, the local file system by default.
+    pub files: Arc<dyn FileProvider>,
+}
+
+impl Default for LookupOptions {
+    fn default() -> LookupOptions {
+        LookupOptions {
+            conffile: ".editorconfig".to_string(),
+            conffile_overrides: vec![],
+            skip_unreadable: false,
+            timings: None,
+            audit: None,
+            observer: None,
+            trust_root: None,
+            trust_same_owner: false,
+            normalize_unicode: cfg!(target_os = "macos"),
+            lenient_values: false,
+            repair_malformed: false,
+            stop_dir: None,
+            spec_version: None,
+            min_spec_version: None,
+            case_sensitive: true,
+            canonicalize: true,
+            require_config: false,
+            conffile_any_case: false,
+            one_file_system: false,
+            stop_at_home: false,
+            home_dir: None,
+            preprocessing: PreprocessingProfile::ReferenceCore,
+            inline_comments: None,
+            global_config: None,
+            user_config: false,
+            max_section_name_length: MAX_SECTION_NAME_LENGTH,
+            max_key_length: MAX_KEY_LENGTH,
+            max_value_length: MAX_VALUE_LENGTH,
+            property_registry: PropertyRegistry::standard(),
+            files: Arc::new(RealFiles),
+        }
+    }
+}
+
+/// Reads and parses the INI file at `path`, keeping read errors apart from parse errors.
+pub(crate) fn load_ini(path: &Path) -> Result<ini::Ini, Error> {
+    let mut contents = String::new();
+    File::open(path)?.read_to_string(&mut contents)?;
+    ini::Ini::load_from_str(&contents).map_err(|err| Error::from(err).in_file(path))
+}
+
+/// Returns `true` if `err` was caused by missing permissions (`EACCES`).
+pub fn is_permission_denied(err: &Error) -> bool {
+    match *err {
+        Error::Io(ref err) => err.kind() == io::ErrorKind::PermissionDenied,
+        _ => false,
+    }
+}
+
+/// Finds the configuration that applies to the file passed in `file_path`, according to `lookup`.
+///
+/// Problems which don't stop the lookup, like unreadable config files when
+/// `lookup.skip_unreadable` is set, are appended to `warnings`.
+pub fn get_config_with(file_path: &Path,
+                       lookup: &LookupOptions,
+                       warnings: &mut Vec<Warning>)
+                       -> Result<OrderMap<String, String>, Error> {
+    get_properties_with(file_path, lookup, warnings).map(Properties::into_map)
+}
+
+/// Finds the configuration of `file_path` like `get_config_with`, along with the config
+/// files it comes from, e.g. to watch them.
+pub fn get_properties_with(file_path: &Path,
+                           lookup: &LookupOptions,
+                           warnings: &mut Vec<Warning>)
+                           -> Result<Properties, Error> {
+    get_config_cached(file_path, lookup, warnings, &ConfigCache::new())
+}
+
+/// `get_properties_with`, reading the config files in `cache` from there.
+pub(crate) fn get_config_cached(file_path: &Path,
+                                lookup: &LookupOptions,
+                                warnings: &mut Vec<Warning>,
+                                cache: &ConfigCache)
+                                -> Result<Properties, Error> {
+    let path = lookup_path(file_path, lookup)?;
+    let paths = crawl_until(path.parent(), lookup, Some(cache), &mut |_| false);
+    merge_configs(file_path, paths, lookup, warnings, cache)
+}
+
+/// `get_config_cached`, keeping the result in `cache` until a config file it was read from or
+/// a directory crawled for it changes its stamp, or `ConfigCache::remove` forgets it.
+/// Lookups with an audit log, timings or an observer are always done in full, so they
+/// report everything they'd do.
+pub(crate) fn resolve_cached(file_path: &Path,
+                             lookup: &LookupOptions,
+                             warnings: &mut Vec<Warning>,
+                             cache: &ConfigCache)
+                             -> Result<Properties, Error> {
+    if lookup.audit.is_some() || lookup.timings.is_some() || lookup.observer.is_some() {
+        return get_config_cached(file_path, lookup, warnings, cache);
+    }
+    let files = &lookup.files;
+    let target = lookup_path(file_path, lookup)?;
+    if let Some(resolved) = cache.resolved(&target) {
+        let current = resolved.stamps.iter().all(|&(ref path, stamp)| files.stamp(path) == stamp);
+        if current {
+            warnings.extend(resolved.warnings.iter().cloned());
+            return Ok(resolved.properties.clone());
+        }
+    }
+    // Taken first, so changes made during the lookup are seen by the next one
+    let mut stamps: Vec<_> = target.ancestors()
+        .skip(1)
+        .map(|dir| (dir.to_path_buf(), files.stamp(dir)))
+        .collect();
+    let mut lookup_warnings = vec![];
+    let properties = get_config_cached(file_path, lookup, &mut lookup_warnings, cache)?;
+    // The stamps the config files had before they were parsed
+    for conf_file in properties.contributing_configs() {
+        let stamp = cache.get(conf_file).and_then(|parsed| parsed.stamp);
+        stamps.push((conf_file.clone(), stamp));
+    }
+    warnings.extend(lookup_warnings.iter().cloned());
+    cache.insert_resolved(&target,
+                          ResolvedLookup {
+                              properties: properties.clone(),
+                              warnings: lookup_warnings,
+                              stamps,
+                          });
+    Ok(properties)
+}
+
+/// Whether there's a config file `lookup` would read in a directory of `file_path`, found
+/// without parsing any config file, see `Resolver::has_config`.
+pub(crate) fn has_config_cached(file_path: &Path,
+                                lookup: &LookupOptions,
+                                cache: &ConfigCache)
+                                -> bool {
+    let path = match lookup_path(file_path, lookup) {
+        Ok(path) => path,
+        Err(_) => return false,
+    };
+    let stop_dir = stop_dir(lookup);
+    let trust = trust::TrustBoundary::new(lookup, file_path);
+    let mut found = false;
+    crawl_until(path.parent(), lookup, Some(cache), &mut |conf_file| {
+        // The config files further up are outside of it too
+        if stop_dir.as_ref().is_some_and(|dir| !conf_file.starts_with(dir)) {
+            return true;
+        }
+        found = trust.reject_reason(conf_file).is_none();
+        found
+    });
+    found
+}
+
+/// `LookupOptions::stop_dir`, canonical when `lookup` canonicalizes.
+fn stop_dir(lookup: &LookupOptions) -> Option<PathBuf> {
+    match lookup.stop_dir {
+        Some(ref dir) if lookup.canonicalize => {
+            Some(lookup.files.canonicalize(dir).unwrap_or_else(|_| dir.clone()))
+        }
+        ref dir => dir.clone(),
+    }
+}
+
+/// Finds the configuration of every file of `file_paths`, in the same order, like
+/// `get_config` but reading and parsing each config file only once, and looking for config
+/// files only once per directory. Use it for many files at once, e.g. a whole repository.
+///
+/// ```
+/// use std::path::Path;
+///
+/// let dir = Path::new("test_files/simple").canonicalize().unwrap();
+/// let paths = [dir.join("file.txt"), dir.join("setup.py")];
+/// let configs = editorconfig::get_configs(&paths);
+/// assert_eq!(configs[1].as_ref().unwrap().indent_size, Some(4));
+/// ```
+pub fn get_configs(file_paths: &[PathBuf]) -> Vec<Result<Config, Error>> {
+    get_configs_with(file_paths, &LookupOptions::default(), &mut vec![])
+}
+
+/// Finds the configuration of every file of `file_paths` like `get_configs`, according to
+/// `lookup`. Problems which don't stop a lookup are appended to `warnings`, for every file
+/// they affect.
+pub fn get_configs_with(file_paths: &[PathBuf],
+                        lookup: &LookupOptions,
+                        warnings: &mut Vec<Warning>)
+                        -> Vec<Result<Config, Error>> {
+    let cache = ConfigCache::new();
+    let mut crawled: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    let mut get_config = |file_path: &Path| {
+        let path = lookup_path(file_path, lookup)?;
+        let dir = path.parent().unwrap_or(&path).to_path_buf();
+        let paths = crawled.entry(dir).or_insert_with(|| crawl_from(path.clone(), lookup));
+        merge_configs(file_path, paths.clone(), lookup, warnings, &cache)
+    };
+    file_paths.iter()
+        .map(|file_path| get_config(file_path).map(|properties| Config::from(&*properties)))
+        .collect()
+}
+
+/// Merges the properties the config files at `paths`, as given by `crawl_paths`, give to
+/// `file_path`.
+fn merge_configs(file_path: &Path,
+                 paths: Vec<PathBuf>,
+                 lookup: &LookupOptions,
+                 warnings: &mut Vec<Warning>,
+                 cache: &ConfigCache)
+                 -> Result<Properties, Error> {
+    let conffiles = conffile_names(lookup);
+    let stop_dir = stop_dir(lookup);
+    let trust = trust::TrustBoundary::new(lookup, file_path);
 
     let mut result = OrderMap::new();
+    let mut read = vec![];
+    // Set once a config file says `root = true`, its siblings are still read
+    let mut root_dir = None;
     for conf_path in paths {
-        let options = parse_config(file_path, &conf_path)?;
-        let old_result = result;
-        result = OrderMap::new();
-        for (k, v) in options.iter() {
-            let k = k.to_lowercase();
-            let v = if is_known_key(&k) {
-                v.to_lowercase()
-            } else {
-                v.clone()
-            };
-            if k.len() > 50 || v.len() > 255 {
+        if root_dir.is_some() && conf_path.parent() != root_dir.as_deref() {
+            break;
+        }
+        if let Some(ref dir) = stop_dir {
+            if !conf_path.starts_with(dir) {
+                if let Some(ref audit) = lookup.audit {
+                    audit.record_stop(dir, "it's the stop directory");
+                }
+                break;
+            }
+        }
+        if let Some(ref observer) = lookup.observer {
+            observer.config_discovered(&conf_path);
+        }
+        if let Some(reason) = trust.reject_reason(&conf_path) {
+            warnings.push(Warning::new(conf_path, WarningKind::Untrusted, reason));
+            continue;
+        }
+        let name = conf_path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        if !conffiles.contains(&name) {
+            if let Some(canonical) = conffiles.iter().find(|c| c.eq_ignore_ascii_case(name)) {
+                warnings.push(Warning::new(conf_path.clone(),
+                                           WarningKind::MiscasedName,
+                                           format!("named {}, rename it to {}", name, canonical)));
+            }
+        }
+        let options = match parse_config(file_path, &conf_path, lookup, warnings, cache) {
+            Ok(parsed) => parsed,
+            Err(ref err) if lookup.skip_unreadable && is_permission_denied(err) => {
+                warnings.push(Warning::new(conf_path.clone(),
+                                           WarningKind::Unreadable,
+                                           format!("skipped, {}", err)));
                 continue;
             }
-            if !result.contains_key(&k) && k != "root" {
-                result.insert(k, v);
+            Err(err) => return Err(err),
+        };
+        if let Some(ref audit) = lookup.audit {
+            audit.record_read(&conf_path);
+        }
+        read.push(conf_path.clone());
+        let old_result = result;
+        result = clean_properties(&options, lookup, &mut |message| {
+            warnings.push(Warning::new(conf_path.clone(), WarningKind::TooLong, message))
+        });
+        if lookup.lenient_values {
+            for (k, v) in result.iter_mut() {
+                if let Some(normalized) = lenient_value(k, v) {
+                    warnings.push(Warning::new(conf_path.clone(),
+                                               WarningKind::LenientValue,
+                                               format!("read `{} = {}` as `{}`", k, v, normalized)));
+                    *v = normalized;
+                }
             }
         }
         for (k, v) in old_result.iter() {
@@ -265,17 +1223,113 @@ pub fn get_config_conffile(file_path: &Path,
         }
         if let Some(root) = options.get("root") {
             if root.to_lowercase() == "true" {
-                break;
+                root_dir = conf_path.parent().map(Path::to_path_buf);
+                if let (Some(audit), Some(dir)) = (lookup.audit.as_ref(), root_dir.as_ref()) {
+                    audit.record_stop(dir, "a config file in it says root = true");
+                }
             }
         }
     }
-    return Ok(result);
+    for conf_path in fallback_configs(lookup) {
+        // The user config may be one of the config files of the file already
+        if read.contains(&conf_path) {
+            continue;
+        }
+        if let Some(ref observer) = lookup.observer {
+            observer.config_discovered(&conf_path);
+        }
+        let parsed = match parsed_config(&conf_path, lookup, warnings, cache) {
+            Ok(parsed) => parsed,
+            Err(ref err) if lookup.skip_unreadable && is_permission_denied(err) => {
+                warnings.push(Warning::new(conf_path.clone(),
+                                           WarningKind::Unreadable,
+                                           format!("skipped, {}", err)));
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        // Patterns are relative to the root of the file system the file is on, so the file
+        // has to be given from there too
+        let target = lookup_path(file_path, lookup)?;
+        let context = target.ancestors().last().unwrap_or(&target);
+        let options = match_config_in(&target, context, &conf_path, &parsed.ini, lookup,
+                                      warnings)?;
+        if let Some(ref audit) = lookup.audit {
+            audit.record_read(&conf_path);
+        }
+        let cleaned = clean_properties(&options, lookup, &mut |message| {
+            warnings.push(Warning::new(conf_path.clone(), WarningKind::TooLong, message))
+        });
+        read.push(conf_path);
+        for (k, v) in cleaned {
+            result.entry(k).or_insert(v);
+        }
+    }
+    remove_unset(&mut result);
+    if lookup.require_config && read.is_empty() {
+        return Err(Error::MissingConfig(file_path.to_path_buf()));
+    }
+    if let Some(ref observer) = lookup.observer {
+        observer.merge_complete(file_path, &result);
+    }
+    Ok(Properties::new(result, read))
+}
+
+/// Finds the configuration that applies to `target` from config files given with their
+/// contents instead of read from disk, e.g. the unsaved buffers of an editor.
+///
+/// Each source is the path the config file would have, and only the sources in a directory
+/// containing `target` apply. Paths aren't canonicalized, so give them all in the same form.
+/// Closer directories override farther ones and, within a directory, later sources
+/// override earlier ones, like `LookupOptions::conffile_overrides`.
+///
+/// ```
+/// use std::path::{Path, PathBuf};
+///
+/// let sources = [(PathBuf::from("/repo/.editorconfig"), "root = true\n[*]\nindent_size = 2\n"),
+///                (PathBuf::from("/repo/src/.editorconfig"), "[*.rs]\nindent_size = 4\n")];
+/// let properties =
+///     editorconfig::get_config_from_sources(Path::new("/repo/src/lib.rs"), &sources).unwrap();
+/// assert_eq!(properties.get("indent_size").unwrap(), "4");
+/// ```
+pub fn get_config_from_sources(target: &Path,
+                               sources: &[(PathBuf, &str)])
+                               -> Result<OrderMap<String, String>, Error> {
+    // In the order `crawl_paths` would find them
+    let mut sources: Vec<_> = sources.iter()
+        .rev()
+        .filter(|(path, _)| path.parent().is_some_and(|dir| target.starts_with(dir)))
+        .collect();
+    sources.sort_by_key(|(path, _)| Reverse(path.components().count()));
+    let lookup = LookupOptions::default();
+
+    let mut result = OrderMap::new();
+    let mut root_dir = None;
+    for (conf_path, contents) in sources {
+        if root_dir.is_some() && conf_path.parent() != root_dir {
+            break;
+        }
+        let ini_data = ini::Ini::load_from_str(contents)
+            .map_err(|err| Error::from(err).in_file(conf_path))?;
+        let options = match_config(target, conf_path, &ini_data, &lookup, &mut vec![])?;
+        let old_result = result;
+        result = clean_properties(&options, &lookup, &mut |_| {});
+        for (k, v) in old_result.iter() {
+            result.insert(k.clone(), v.clone());
+        }
+        if options.get("root").is_some_and(|root| root.to_lowercase() == "true") {
+            root_dir = conf_path.parent();
+        }
+    }
+    remove_unset(&mut result);
+    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::path::Path;
+    use std::sync::Mutex;
 
     #[test]
     fn works_with_multi_level_directories() {
@@ -288,8 +1342,504 @@ mod tests {
         assert_eq!(cfg, map);
     }
 
+    #[test]
+    fn matches_sections_with_and_without_numeric_ranges() {
+        let path = Path::new("./test_files/sections/file5.txt");
+        let path = path.canonicalize().unwrap();
+        let cfg = get_config(&path).unwrap();
+        let mut map = OrderMap::new();
+        map.insert("a".to_owned(), "6".to_owned());
+        map.insert("b".to_owned(), "2".to_owned());
+        map.insert("c".to_owned(), "3".to_owned());
+        assert_eq!(cfg, map);
+    }
+
+    #[test]
+    fn ignores_config_files_outside_the_trust_root() {
+        let path = Path::new("./test_files/non_root_editorconfig/foo/file.txt");
+        let path = path.canonicalize().unwrap();
+        let options = LookupOptions {
+            trust_root: Some(PathBuf::from("./test_files/non_root_editorconfig/foo")),
+            ..LookupOptions::default()
+        };
+        let mut warnings = vec![];
+        let cfg = get_config_with(&path, &options, &mut warnings).unwrap();
+        assert_eq!(cfg.get("insert_final_newline").unwrap(), "false");
+        // The untrusted `root = true` is ignored too, so files further up are reported as well
+        assert!(warnings.iter().all(|warning| warning.kind == WarningKind::Untrusted));
+        assert_eq!(warnings[0].file,
+                   Path::new("./test_files/non_root_editorconfig/.editorconfig")
+                       .canonicalize()
+                       .unwrap());
+    }
+
+    #[test]
+    fn unset_removes_inherited_values() {
+        let mut files = MemoryFiles::new();
+        files.insert("/repo/.editorconfig",
+                     "root = true\n[*]\ncharset = utf-8\nindent_size = 2\nmy_key = a\n");
+        files.insert("/repo/vendor/.editorconfig", "[*]\ncharset = unset\nmy_key = UNSET\n");
+        files.insert("/repo/vendor/lib/.editorconfig",
+                     "[*.c]\nindent_size = unset\n[x.c]\ncharset = latin1\n");
+        files.insert("/global", "[*]\ncharset = utf-16le\nindent_size = 8\n");
+        let lookup = LookupOptions {
+            files: Arc::new(files),
+            global_config: Some(PathBuf::from("/global")),
+            ..LookupOptions::default()
+        };
+        let properties = |path: &str| {
+            get_config_with(Path::new(path), &lookup, &mut vec![]).unwrap()
+        };
+        assert!(properties("/repo/vendor/lib/y.c").is_empty());
+        let x = properties("/repo/vendor/lib/x.c");
+        assert_eq!(x.get("charset").unwrap(), "latin1");
+        assert_eq!(x.get("indent_size"), None);
+        assert_eq!(properties("/repo/vendor/lib/y.h").get("indent_size").unwrap(), "2");
+
+        let sources = [(PathBuf::from("/repo/.editorconfig"), "[*]\ncharset = utf-8\n"),
+                       (PathBuf::from("/repo/src/.editorconfig"), "[*]\ncharset = unset\n")];
+        assert!(get_config_from_sources(Path::new("/repo/src/a.c"), &sources).unwrap().is_empty());
+    }
+
+    #[test]
+    fn returns_what_the_core_of_the_version_would() {
+        let path = env::current_dir().unwrap().join("test_files/simple/Makefile");
+        let latest = get_config_conffile(&path, ".editorconfig").unwrap();
+        assert_eq!(latest.get("indent_size").unwrap(), "tab");
+        let old = get_config_conffile_version(&path, ".editorconfig", Some("0.9.0")).unwrap();
+        assert_eq!(old.get("indent_size"), None);
+        let new = get_config_conffile_version(&path, ".editorconfig", Some("0.12.0")).unwrap();
+        assert_eq!(new, latest);
+    }
+
+    #[test]
+    fn reads_config_files_named_in_another_case_when_asked() {
+        let path = Path::new("./test_files/miscased/file.txt").canonicalize().unwrap();
+        assert_eq!(get_config(&path).unwrap().get("indent_style"), None);
+        let options = LookupOptions {
+            conffile_any_case: true,
+            ..LookupOptions::default()
+        };
+        let mut warnings = vec![];
+        let cfg = get_config_with(&path, &options, &mut warnings).unwrap();
+        assert_eq!(cfg.get("indent_style").unwrap(), "tab");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::MiscasedName);
+        assert_eq!(warnings[0].message, "named .EditorConfig, rename it to .editorconfig");
+    }
+
+    #[test]
+    fn reads_config_files_from_sources_in_memory() {
+        let sources = [(PathBuf::from("/.editorconfig"), "[*]\ncharset = latin1\n"),
+                       (PathBuf::from("/repo/.editorconfig"), "root = true\n[*]\nindent_size = 2\n"),
+                       (PathBuf::from("/repo/src/.editorconfig"), "[*.rs]\nindent_size = 4\n"),
+                       (PathBuf::from("/repo/src/.editorconfig.user"), "[*.rs]\nindent_size = 8\n"),
+                       (PathBuf::from("/repo/docs/.editorconfig"), "[*]\nindent_size = 3\n")];
+        let cfg = get_config_from_sources(Path::new("/repo/src/lib.rs"), &sources).unwrap();
+        assert_eq!(cfg.get("indent_size").unwrap(), "8");
+        assert_eq!(cfg.get("charset"), None);
+        let cfg = get_config_from_sources(Path::new("/repo/README.md"), &sources).unwrap();
+        assert_eq!(cfg.get("indent_size").unwrap(), "2");
+
+        let sources = [(PathBuf::from("/repo/.editorconfig"), "[*]\n= 2\n")];
+        match get_config_from_sources(Path::new("/repo/lib.rs"), &sources) {
+            Err(Error::Parse { file: Some(ref file), .. }) => {
+                assert_eq!(file, Path::new("/repo/.editorconfig"))
+            }
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    /// Counts the reads of the files it holds.
+    struct CountingFiles(MemoryFiles, Mutex<usize>);
+
+    impl FileProvider for CountingFiles {
+        fn exists(&self, path: &Path) -> bool {
+            self.0.exists(path)
+        }
+
+        fn read(&self, path: &Path) -> io::Result<String> {
+            *self.1.lock().unwrap() += 1;
+            self.0.read(path)
+        }
+
+        fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+            self.0.canonicalize(path)
+        }
+    }
+
+    /// Holds its files on two file systems, the second mounted at `/mnt/project`.
+    struct MountedFiles(MemoryFiles);
+
+    impl FileProvider for MountedFiles {
+        fn exists(&self, path: &Path) -> bool {
+            self.0.exists(path)
+        }
+
+        fn read(&self, path: &Path) -> io::Result<String> {
+            self.0.read(path)
+        }
+
+        fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+            self.0.canonicalize(path)
+        }
+
+        fn device(&self, path: &Path) -> Option<u64> {
+            Some(if path.starts_with("/mnt/project") { 2 } else { 1 })
+        }
+    }
+
+    #[test]
+    fn stops_looking_at_file_system_boundaries_when_asked() {
+        let mut files = MemoryFiles::new();
+        files.insert("/.editorconfig", "[*]\ncharset = latin1\n");
+        files.insert("/mnt/project/.editorconfig", "[*]\nindent_size = 2\n");
+        let audit = Arc::new(AuditLog::new());
+        let mut lookup = LookupOptions {
+            files: Arc::new(MountedFiles(files)),
+            audit: Some(audit.clone()),
+            ..LookupOptions::default()
+        };
+        let path = Path::new("/mnt/project/src/lib.rs");
+        let properties = get_config_with(path, &lookup, &mut vec![]).unwrap();
+        assert_eq!(properties.get("charset").unwrap(), "latin1");
+
+        lookup.one_file_system = true;
+        let properties = get_config_with(path, &lookup, &mut vec![]).unwrap();
+        assert_eq!(properties.get("indent_size").unwrap(), "2");
+        assert_eq!(properties.get("charset"), None);
+        assert_eq!(audit.stops(),
+                   [(PathBuf::from("/mnt/project"),
+                     "its parent directory is on another file system".to_string())]);
+    }
+
+    #[test]
+    fn falls_back_to_the_config_of_the_user() {
+        let mut files = MemoryFiles::new();
+        files.insert("/home/me/.editorconfig", "[*]\ncharset = latin1\nindent_size = 8\n");
+        files.insert("/home/me/project/.editorconfig", "root = true\n[*]\nindent_size = 2\n");
+        let lookup = LookupOptions {
+            files: Arc::new(files),
+            global_config: Some(PathBuf::from("/home/me/.editorconfig")),
+            ..LookupOptions::default()
+        };
+        let properties = get_properties_with(Path::new("/home/me/project/lib.rs"),
+                                             &lookup,
+                                             &mut vec![])
+            .unwrap();
+        assert_eq!(properties.get("indent_size").unwrap(), "2");
+        assert_eq!(properties.get("charset").unwrap(), "latin1");
+        assert_eq!(properties.contributing_configs(),
+                   [PathBuf::from("/home/me/project/.editorconfig"),
+                    PathBuf::from("/home/me/.editorconfig")]);
+        // Read once when it's one of the config files of the file too
+        let properties = get_properties_with(Path::new("/home/me/notes.md"), &lookup, &mut vec![])
+            .unwrap();
+        assert_eq!(properties.contributing_configs(), [PathBuf::from("/home/me/.editorconfig")]);
+
+        let home = Some(PathBuf::from("/home/me"));
+        assert_eq!(user_config_candidates(home.clone(), None),
+                   [PathBuf::from("/home/me/.config/editorconfig/config"),
+                    PathBuf::from("/home/me/.editorconfig")]);
+        assert_eq!(user_config_candidates(home.clone(), Some(PathBuf::from("/xdg"))),
+                   [PathBuf::from("/xdg/editorconfig/config"),
+                    PathBuf::from("/home/me/.editorconfig")]);
+        assert_eq!(user_config_candidates(home, Some(PathBuf::from("relative")))[0],
+                   PathBuf::from("/home/me/.config/editorconfig/config"));
+        assert!(user_config_candidates(None, None).is_empty());
+    }
+
+    /// Holds its files in memory below `/work`, where relative paths start, and can't read
+    /// the one at `denied`.
+    struct WorkFiles {
+        files: MemoryFiles,
+        denied: PathBuf,
+    }
+
+    impl FileProvider for WorkFiles {
+        fn exists(&self, path: &Path) -> bool {
+            self.files.exists(&Path::new("/work").join(path))
+        }
+
+        fn read(&self, path: &Path) -> io::Result<String> {
+            if path == self.denied {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, "permission denied"));
+            }
+            self.files.read(path)
+        }
+
+        fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+            self.files.canonicalize(&Path::new("/work").join(path))
+        }
+    }
+
+    #[test]
+    fn matches_the_fallback_configs_against_the_canonical_path() {
+        let mut files = MemoryFiles::new();
+        files.insert("/work/src/lib.rs", "");
+        files.insert("/etc/editorconfig", "[*]\ncharset = latin1\n");
+        files.insert("/home/me/.editorconfig", "[/work/src/*.rs]\nindent_size = 4\n");
+        let mut lookup = LookupOptions {
+            files: Arc::new(WorkFiles { files, denied: PathBuf::from("/etc/editorconfig") }),
+            global_config: Some(PathBuf::from("/etc/editorconfig")),
+            user_config: true,
+            home_dir: Some(PathBuf::from("/home/me")),
+            ..LookupOptions::default()
+        };
+        let path = Path::new("src/lib.rs");
+        assert!(is_permission_denied(&get_config_with(path, &lookup, &mut vec![]).unwrap_err()));
+
+        lookup.skip_unreadable = true;
+        let mut warnings = vec![];
+        let properties = get_config_with(path, &lookup, &mut warnings).unwrap();
+        assert_eq!(properties.get("indent_size").unwrap(), "4");
+        assert_eq!(properties.get("charset"), None);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::Unreadable);
+        assert_eq!(warnings[0].file, Path::new("/etc/editorconfig"));
+    }
+
+    #[test]
+    fn looks_many_files_up_reading_config_files_once() {
+        let mut files = MemoryFiles::new();
+        files.insert("/repo/.editorconfig", "root = true\n[*]\nindent_style = tab\n");
+        files.insert("/repo/src/.editorconfig", "[*.rs]\nindent_size = 4\n");
+        files.insert("/repo/docs/.editorconfig", "[*]\n= 2\n");
+        let files = Arc::new(CountingFiles(files, Mutex::new(0)));
+        let lookup = LookupOptions { files: files.clone(), ..LookupOptions::default() };
+        let paths: Vec<_> = ["/repo/src/a.rs", "/repo/src/b.md", "/repo/README.md",
+                             "/repo/docs/a.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let configs = get_configs_with(&paths, &lookup, &mut vec![]);
+        assert_eq!(configs[0].as_ref().unwrap().indent_size, Some(4));
+        assert_eq!(configs[1].as_ref().unwrap().indent_size, None);
+        assert_eq!(configs[2].as_ref().unwrap().indent_style, Some(IndentStyle::Tab));
+        assert!(configs[3].is_err());
+        assert_eq!(*files.1.lock().unwrap(), 3);
+    }
+
+    fn glob_matches(pattern: &str, candidate: &str) -> bool {
+        glob::matches(pattern, candidate).unwrap()
+    }
+
+    #[test]
+    fn matches_double_stars_at_either_end() {
+        for pattern in &["**/foo", "/**/foo"] {
+            assert!(glob_matches(pattern, "foo"), "{}", pattern);
+            assert!(glob_matches(pattern, "a/b/foo"), "{}", pattern);
+            assert!(!glob_matches(pattern, "a/xfoo"), "{}", pattern);
+            assert!(!glob_matches(pattern, "foo/a"), "{}", pattern);
+        }
+        assert!(glob_matches("**/b/*.rs", "b/c.rs"));
+        assert!(glob_matches("**/b/*.rs", "a/b/c.rs"));
+        assert!(!glob_matches("**/b/*.rs", "ab/c.rs"));
+        // A trailing /** matches everything below the directory, but not a file of that name
+        assert!(glob_matches("foo/**", "foo/a"));
+        assert!(glob_matches("foo/**", "foo/a/b"));
+        assert!(!glob_matches("foo/**", "foo"));
+        assert!(!glob_matches("foo/**", "a/foo/b"));
+        assert!(glob_matches("a/**/b", "a/b"));
+        assert!(glob_matches("a/**/b", "a/x/y/b"));
+        // Alone, ** matches every file below the config file's directory
+        for pattern in &["**", "/**"] {
+            assert!(glob_matches(pattern, "foo"), "{}", pattern);
+            assert!(glob_matches(pattern, "a/b/foo"), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn matches_unicode_patterns_by_code_point() {
+        assert!(glob_matches("统计*.txt", "统计报告.txt"));
+        assert!(glob_matches("统计*.txt", "docs/统计.txt"));
+        assert!(!glob_matches("统计*.txt", "统计.md"));
+        assert!(glob_matches("?.txt", "é.txt"));
+        assert!(glob_matches("?.txt", "😀.txt"));
+        assert!(!glob_matches("?.txt", "e\u{301}.txt"));
+        assert!(glob_matches("??.txt", "e\u{301}.txt"));
+        assert!(glob_matches("[统计].txt", "计.txt"));
+        assert!(glob_matches("[!统计].txt", "表.txt"));
+        assert!(glob_matches("*.{rs,🦀}", "src/main.🦀"));
+        assert!(glob_matches("docs/**/é*.md", "docs/a/b/été.md"));
+        assert!(glob_matches("file{1..3}-ü.txt", "file2-ü.txt"));
+    }
+
+    #[test]
+    fn resolves_unicode_paths_and_properties() {
+        let dir = Path::new("./test_files/unicode").canonicalize().unwrap();
+        let cfg = get_config(&dir.join("统计报告.txt")).unwrap();
+        assert_eq!(cfg.get("charset").unwrap(), "utf-8");
+        let cfg = get_config(&dir.join("ü.md")).unwrap();
+        assert_eq!(cfg.get("indent_style").unwrap(), "tab");
+        // Limits are in characters: the 30 character key is 60 bytes long
+        let cfg = get_config(&dir.join("main.🦀")).unwrap();
+        assert_eq!(cfg.get(&"é".repeat(30)).unwrap(), "ééééé");
+    }
+
+    #[test]
+    fn normalizes_decomposed_file_names() {
+        let dir = Path::new("./test_files/unicode").canonicalize().unwrap();
+        // "résumé.md" as macOS stores it, with each "é" decomposed into "e" and an accent
+        let decomposed = dir.join("re\u{301}sume\u{301}.md");
+        let options = LookupOptions { normalize_unicode: true, ..LookupOptions::default() };
+        let cfg = get_config_with(&decomposed, &options, &mut vec![]).unwrap();
+        assert_eq!(cfg.get("indent_size").unwrap(), "3");
+        let options = LookupOptions { normalize_unicode: false, ..LookupOptions::default() };
+        let cfg = get_config_with(&decomposed, &options, &mut vec![]).unwrap();
+        assert!(cfg.get("indent_size").is_none());
+    }
+
+    #[test]
+    fn matches_a_single_config_file() {
+        let contents = "root = true\n[*]\nIndent_Style = Space\n[*.md]\nindent_size = 2\n\
+                        [docs/*.md]\nindent_size = 4\n";
+        let properties = match_file(contents, "docs/index.md").unwrap();
+        assert_eq!(properties.get("indent_style").unwrap(), "space");
+        assert_eq!(properties.get("indent_size").unwrap(), "4");
+        assert!(properties.get("root").is_none());
+        assert!(match_file(contents, "src/lib.rs").unwrap().get("indent_size").is_none());
+    }
+
+    #[test]
+    fn matches_sections_too_big_to_match_at_once() {
+        let wildcards = "?".repeat(3000);
+        let contents: String = (0..6)
+            .map(|i| format!("[{}.{}]\nindent_size = {}\n", wildcards, i, i + 1))
+            .collect();
+        let sources: Vec<_> = (0..6)
+            .map(|i| cached_glob(&format!("{}.{}", wildcards, i), false).unwrap())
+            .collect();
+        assert!(RegexSet::new(sources.iter().map(Glob::regex_source)).is_err());
+        let properties = match_file(&contents, &format!("{}.4", "x".repeat(3000))).unwrap();
+        assert_eq!(properties.get("indent_size").unwrap(), "5");
+    }
+
+    #[test]
+    fn layers_override_files_over_config_files() {
+        let path = Path::new("./test_files/overrides/foo/file.txt");
+        let path = path.canonicalize().unwrap();
+        let options = LookupOptions {
+            conffile_overrides: vec![".editorconfig.user".to_string()],
+            ..LookupOptions::default()
+        };
+        let cfg = get_config_with(&path, &options, &mut vec![]).unwrap();
+        let mut map = OrderMap::new();
+        map.insert("indent_size".to_owned(), "8".to_owned());
+        map.insert("indent_style".to_owned(), "tab".to_owned());
+        map.insert("end_of_line".to_owned(), "crlf".to_owned());
+        map.insert("charset".to_owned(), "latin1".to_owned());
+        map.insert("tab_width".to_owned(), "8".to_owned());
+        map.insert("insert_final_newline".to_owned(), "true".to_owned());
+        assert_eq!(cfg, map);
+        // Without overrides, only the checked in files are read
+        let cfg = get_config(&path).unwrap();
+        assert_eq!(cfg.get("indent_size").unwrap(), "4");
+        assert_eq!(cfg.get("end_of_line").unwrap(), "lf");
+    }
+
+    #[test]
+    fn normalizes_sizes_with_units_when_lenient() {
+        let path = Path::new("./test_files/lenient/file.txt");
+        let path = path.canonicalize().unwrap();
+        let options = LookupOptions { lenient_values: true, ..LookupOptions::default() };
+        let mut warnings = vec![];
+        let cfg = get_config_with(&path, &options, &mut warnings).unwrap();
+        assert_eq!(cfg.get("indent_size").unwrap(), "4");
+        assert_eq!(cfg.get("max_line_length").unwrap(), "100");
+        assert_eq!(cfg.get("indent_style").unwrap(), "space");
+        assert!(warnings.iter().all(|warning| warning.kind == WarningKind::LenientValue));
+        assert!(warnings.iter()
+            .any(|warning| warning.message == "read `max_line_length = 100 Chars` as `100`"));
+
+        let mut warnings = vec![];
+        let cfg = get_config_with(&path, &LookupOptions::default(), &mut warnings).unwrap();
+        assert_eq!(cfg.get("indent_size").unwrap(), "4ch");
+        assert_eq!(cfg.get("max_line_length").unwrap(), "100 Chars");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn repairs_malformed_configs_when_asked() {
+        let path = Path::new("./test_files/malformed/file.md");
+        let path = path.canonicalize().unwrap();
+        let options = LookupOptions { repair_malformed: true, ..LookupOptions::default() };
+        let mut warnings = vec![];
+        let cfg = get_config_with(&path, &options, &mut warnings).unwrap();
+        assert_eq!(cfg.get("indent_style").unwrap(), "space");
+        assert_eq!(cfg.get("indent_size").unwrap(), "2");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::Repaired);
+        assert_eq!(warnings[0].message,
+                   "repaired, line 3: section header `[*` isn't closed, read as `[*]`; \
+                    line 5: `= 4` has no key, ignored; \
+                    line 7: section header `[*.md` isn't closed, read as `[*.md]`");
+
+        match get_config(&path) {
+            Err(Error::Parse { file: Some(ref file), .. }) => {
+                assert!(file.ends_with("malformed/.editorconfig"))
+            }
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn matches_invalid_patterns_literally() {
+        let dir = Path::new("./test_files/invalid_patterns").canonicalize().unwrap();
+        let mut warnings = vec![];
+        let cfg = get_config_with(&dir.join("a/("), &LookupOptions::default(), &mut warnings)
+            .unwrap();
+        assert_eq!(cfg.get("indent_style").unwrap(), "space");
+        assert_eq!(cfg.get("indent_size").unwrap(), "3");
+        assert!(cfg.get("tab_width").unwrap() != "7");
+        assert_eq!(warnings.len(), 4);
+        assert!(warnings.iter().all(|warning| warning.kind == WarningKind::InvalidPattern));
+        assert!(warnings[0].message.starts_with("section [(] can't be translated to a regex"));
+
+        let cfg = get_config_with(&dir.join("[z-a].txt"), &LookupOptions::default(), &mut vec![])
+            .unwrap();
+        assert_eq!(cfg.get("indent_size").unwrap(), "5");
+        let cfg = get_config_with(&dir.join("sub/[!]"), &LookupOptions::default(), &mut vec![])
+            .unwrap();
+        assert_eq!(cfg.get("charset").unwrap(), "latin1");
+        let properties = match_file("[(]\nindent_size = 2\n", "(").unwrap();
+        assert_eq!(properties.get("indent_size").unwrap(), "2");
+    }
+
     #[test]
     fn get_editorconfig_for_non_existing_file() {
         assert!(get_config(Path::new("./test_files/diocano")).is_ok());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_unreadable_config_files() {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join("editorconfig-skips-unreadable");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let conf = dir.join(".editorconfig");
+        fs::write(&conf, "root = true\n[*]\nindent_style = tab\n").unwrap();
+        fs::set_permissions(&conf, fs::Permissions::from_mode(0o000)).unwrap();
+        let readable = File::open(&conf).is_ok();
+
+        let target = dir.join("file.txt");
+        let mut warnings = vec![];
+        let options = LookupOptions { skip_unreadable: true, ..LookupOptions::default() };
+        let cfg = get_config_with(&target, &options, &mut warnings).unwrap();
+        let strict = get_config_with(&target, &LookupOptions::default(), &mut vec![]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Permissions don't apply to root, so there is nothing to skip.
+        if readable {
+            return;
+        }
+        assert!(cfg.is_empty());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::Unreadable);
+        assert_eq!(warnings[0].file, conf);
+        assert!(strict.is_err());
+    }
 }
@@ -0,0 +1,231 @@
+//! Checking config files on their own, rather than the properties they give to files, so
+//! monorepos can validate all their config files with one command.
+
+use std::path::{Path, PathBuf};
+
+use cache::ConfigCache;
+use complexity::expensive_sections;
+use glob::cached_glob;
+use redundancy::redundant_settings_cached;
+use {find_all_configs_with, find_inline_comments, find_invalid_values, find_misspelled_keys,
+     find_newer_settings, parsed_config, simplify_config, Diagnostic, Error, LookupOptions,
+     Simplification, SpecVersion, WalkOptions, Warning};
+
+/// Lints every config file below `root`, see `lint_config_files`. Config files of parent
+/// directories are left to the trees they're in. Directories skipped as `walk` allows are
+/// appended to `warnings`.
+pub fn lint_tree(root: &Path,
+                 lookup: &LookupOptions,
+                 walk: &WalkOptions,
+                 warnings: &mut Vec<Warning>)
+                 -> Result<Vec<Diagnostic>, Error> {
+    let conf_files: Vec<_> = find_all_configs_with(root, lookup, walk, warnings)?
+        .into_iter()
+        .filter(|info| !info.inherited)
+        .map(|info| info.path)
+        .collect();
+    Ok(lint_config_files(&conf_files, lookup))
+}
+
+/// Finds the problems of the config files at `conf_files`, in their order:
+///
+/// - `broken-config`: files that can't be read or parsed, which are checked no further,
+/// - `invalid-pattern`: sections that can't be translated to a regex,
+/// - `invalid-value`: values `lookup.property_registry` doesn't allow, see
+///   `find_invalid_values`,
+/// - `misspelled-property`: unknown keys close to a known property, see
+///   `find_misspelled_keys`,
+/// - `simplifiable-pattern`: sections `simplify_config` rewrites,
+/// - `inline-comment`: comments after values, see `find_inline_comments`,
+/// - `redundant-setting`: properties restating an inherited value, see
+///   `redundant_settings`,
+/// - `expensive-pattern`: sections likely to be slow to match, see `pattern_cost`,
+/// - `newer-property`: properties and values older cores than `lookup.min_spec_version`
+///   ignore, see `find_newer_settings`.
+///
+/// Every config file is parsed once, including the parents of several of them.
+pub fn lint_config_files(conf_files: &[PathBuf], lookup: &LookupOptions) -> Vec<Diagnostic> {
+    let cache = ConfigCache::new();
+    let minimum = lookup.min_spec_version.as_deref().and_then(|v| v.parse::<SpecVersion>().ok());
+    let mut diagnostics = vec![];
+    for conf_file in conf_files {
+        let parsed = parsed_config(conf_file, lookup, &mut vec![], &cache)
+            .and_then(|parsed| Ok((parsed, lookup.files.read(conf_file)?)));
+        let (parsed, contents) = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                diagnostics.push(Diagnostic {
+                    file: conf_file.clone(),
+                    rule: "broken-config",
+                    message_id: "broken-config.unusable",
+                    args: vec![("error", err.to_string())],
+                });
+                continue;
+            }
+        };
+        for section in parsed.ini.sections().flatten() {
+            if let Err(err) = cached_glob(section, lookup.case_sensitive) {
+                diagnostics.push(Diagnostic {
+                    file: conf_file.clone(),
+                    rule: "invalid-pattern",
+                    message_id: "invalid-pattern.literal",
+                    args: vec![("section", section.clone()), ("error", err)],
+                });
+            }
+        }
+        let costs = expensive_sections(&parsed.ini);
+        let invalid_values = find_invalid_values(&contents, &lookup.property_registry);
+        for invalid in invalid_values.unwrap_or_default() {
+            diagnostics.push(Diagnostic {
+                file: conf_file.clone(),
+                rule: "invalid-value",
+                message_id: "invalid-value.not-allowed",
+                args: vec![("key", invalid.key),
+                           ("value", invalid.value),
+                           ("line", invalid.line.to_string()),
+                           ("expected", invalid.expected)],
+            });
+        }
+        let misspelled_keys = find_misspelled_keys(&contents, &lookup.property_registry);
+        for misspelled in misspelled_keys.unwrap_or_default() {
+            diagnostics.push(Diagnostic {
+                file: conf_file.clone(),
+                rule: "misspelled-property",
+                message_id: "misspelled-property.did-you-mean",
+                args: vec![("key", misspelled.key),
+                           ("line", misspelled.line.to_string()),
+                           ("suggestion", misspelled.suggestion)],
+            });
+        }
+        let (_, simplifications) = simplify_config(&contents).unwrap_or_default();
+        for simplification in simplifications {
+            let (message_id, args) = match simplification {
+                Simplification::Pattern { line, pattern, simplified } => {
+                    ("simplifiable-pattern.pattern",
+                     vec![("section", pattern),
+                          ("line", line.to_string()),
+                          ("simplified", simplified)])
+                }
+                Simplification::Merged { line, pattern } => {
+                    ("simplifiable-pattern.repeated",
+                     vec![("section", pattern), ("line", line.to_string())])
+                }
+            };
+            diagnostics.push(Diagnostic {
+                file: conf_file.clone(),
+                rule: "simplifiable-pattern",
+                message_id,
+                args,
+            });
+        }
+        for comment in find_inline_comments(&contents).unwrap_or_default() {
+            diagnostics.push(Diagnostic {
+                file: conf_file.clone(),
+                rule: "inline-comment",
+                message_id: "inline-comment.part-of-value",
+                args: vec![("key", comment.key),
+                           ("value", comment.stripped),
+                           ("line", comment.line.to_string()),
+                           ("kept", comment.kept)],
+            });
+        }
+        // Broken parents are reported on their own
+        for setting in redundant_settings_cached(conf_file, lookup, &cache).unwrap_or_default() {
+            let line = contents[..setting.span.start].matches('\n').count() + 1;
+            diagnostics.push(Diagnostic {
+                file: conf_file.clone(),
+                rule: "redundant-setting",
+                message_id: "redundant-setting.inherited",
+                args: vec![("section", setting.section),
+                           ("key", setting.key),
+                           ("value", setting.value),
+                           ("line", line.to_string()),
+                           ("inherited_from", setting.inherited_from.display().to_string())],
+            });
+        }
+        for cost in costs {
+            for issue in cost.issues {
+                diagnostics.push(Diagnostic {
+                    file: conf_file.clone(),
+                    rule: "expensive-pattern",
+                    message_id: "expensive-pattern.slow",
+                    args: vec![("section", cost.pattern.clone()),
+                               ("issue", issue.message),
+                               ("suggestion", issue.suggestion)],
+                });
+            }
+        }
+        if let Some(minimum) = minimum {
+            let newer_settings = find_newer_settings(&contents, &lookup.property_registry, minimum);
+            for newer in newer_settings.unwrap_or_default() {
+                diagnostics.push(Diagnostic {
+                    file: conf_file.clone(),
+                    rule: "newer-property",
+                    message_id: "newer-property.ignored",
+                    args: vec![("key", newer.key),
+                               ("value", newer.value),
+                               ("line", newer.line.to_string()),
+                               ("since", newer.since.to_string()),
+                               ("minimum", minimum.to_string())],
+                });
+            }
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::FixtureBuilder;
+    use {MessageCatalog, Severity};
+
+    #[test]
+    fn lints_every_config_file_of_a_tree() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig", "root = true\n[*]\nindent_size = 2\n")
+            .config("a/.editorconfig", "[*]\nindent_size = 2 ; same\n")
+            .config("b/.editorconfig", "[*]\n= 2\n")
+            .config("c/d/.editorconfig", "[*.rs]\nindent_size = 4\n[*.{rs,rs}]\nx = 1\n")
+            .config("e/.editorconfig", "[*]\nindent_style = spaces\n")
+            .config("f/.editorconfig", "[*]\nindent_stile = space\n")
+            .config("g/.editorconfig", "[**/a/**/b/**]\nindent_size = 4\n")
+            .config("h/.editorconfig", "[*.md]\nspelling_language = en-US\n")
+            .build()
+            .unwrap();
+        let mut lookup = fixture.lookup_options();
+        lookup.min_spec_version = Some("0.15.0".to_string());
+        let diagnostics = lint_tree(fixture.root(), &lookup, &WalkOptions::default(), &mut vec![])
+            .unwrap();
+        let found: Vec<_> = diagnostics.iter()
+            .map(|diagnostic| (diagnostic.file.clone(), diagnostic.rule))
+            .collect();
+        assert_eq!(found,
+                   [(fixture.path("a/.editorconfig"), "inline-comment"),
+                    (fixture.path("a/.editorconfig"), "redundant-setting"),
+                    (fixture.path("b/.editorconfig"), "broken-config"),
+                    (fixture.path("c/d/.editorconfig"), "simplifiable-pattern"),
+                    (fixture.path("c/d/.editorconfig"), "simplifiable-pattern"),
+                    (fixture.path("e/.editorconfig"), "invalid-value"),
+                    (fixture.path("f/.editorconfig"), "misspelled-property"),
+                    (fixture.path("g/.editorconfig"), "expensive-pattern"),
+                    (fixture.path("h/.editorconfig"), "newer-property")]);
+        assert_eq!(diagnostics[6].message(&MessageCatalog::english()),
+                   "indent_stile on line 2 isn't a known property, did you mean indent_style?");
+        let invalid = &diagnostics[5];
+        assert_eq!(invalid.severity(), Severity::High);
+        assert_eq!(invalid.message(&MessageCatalog::english()),
+                   "indent_style = spaces on line 2 isn't allowed, expected tab or space");
+        assert_eq!(diagnostics[7].message(&MessageCatalog::english()),
+                   "[**/a/**/b/**] may be slow to match: 3 `**` wildcards, each of which can \
+                    span any number of directories; use a single `**` and match the rest with \
+                    `*`");
+        assert_eq!(diagnostics[8].message(&MessageCatalog::english()),
+                   "spelling_language = en-US on line 2 needs EditorConfig 0.16.0, editors on \
+                    0.15.0 ignore it");
+        lookup.min_spec_version = None;
+        let unchecked = lint_tree(fixture.root(), &lookup, &WalkOptions::default(), &mut vec![])
+            .unwrap();
+        assert_eq!(unchecked.len(), 8);
+    }
+}
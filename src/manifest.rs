@@ -0,0 +1,215 @@
+//! Which properties every file in a tree gets, for build systems to only reformat files
+//! whose effective configuration changed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use ordermap::OrderMap;
+
+use {get_config_with, walk_files, Error, LookupOptions, WalkOptions, Warning};
+
+/// Identifies a set of properties, whatever the order they were resolved in. The same
+/// properties give the same fingerprint across platforms and releases.
+pub fn properties_fingerprint(properties: &OrderMap<String, String>) -> String {
+    let mut sorted: Vec<_> = properties.iter().collect();
+    sorted.sort();
+    // 64 bit FNV-1a, which unlike the hashers of std is specified
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (key, value) in sorted {
+        for byte in key.bytes().chain(Some(b'=')).chain(value.bytes()).chain(Some(b'\n')) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Properties, and the files which get them.
+pub type ConfigGroup = (OrderMap<String, String>, Vec<PathBuf>);
+
+/// Resolves the properties of `paths` and groups together the paths getting the same ones,
+/// so that tools like formatters can configure themselves once per group instead of once
+/// per file. Groups come in the order of their first path, and keep the order of `paths`.
+pub fn group_by_config<P: AsRef<Path>>(paths: &[P],
+                                       lookup: &LookupOptions,
+                                       warnings: &mut Vec<Warning>)
+                                       -> Result<Vec<ConfigGroup>, Error> {
+    let mut groups: Vec<ConfigGroup> = vec![];
+    // Fingerprints, to the index of their group
+    let mut indices = HashMap::new();
+    for path in paths {
+        let path = path.as_ref();
+        let properties = get_config_with(path, lookup, warnings)?;
+        let fingerprint = properties_fingerprint(&properties);
+        let index = *indices.entry(fingerprint).or_insert_with(|| {
+            groups.push((properties, vec![]));
+            groups.len() - 1
+        });
+        groups[index].1.push(path.to_path_buf());
+    }
+    Ok(groups)
+}
+
+/// The fingerprint of every file below a directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// Paths relative to the directory, to their fingerprint.
+    pub files: BTreeMap<PathBuf, String>,
+    /// Fingerprints, to the properties they stand for.
+    pub fingerprints: BTreeMap<String, OrderMap<String, String>>,
+    /// Paths relative to the directory, to why their properties couldn't be resolved. They
+    /// have no fingerprint.
+    pub errors: BTreeMap<PathBuf, String>,
+}
+
+impl Manifest {
+    /// Resolves the properties of every file below `root`.
+    ///
+    /// Only failing to list the files is an error. Files whose properties can't be resolved,
+    /// e.g. because a config file applying to them can't be parsed, are listed in `errors`
+    /// and the others are resolved all the same.
+    pub fn build(root: &Path,
+                 lookup: &LookupOptions,
+                 walk: &WalkOptions,
+                 warnings: &mut Vec<Warning>)
+                 -> Result<Manifest, Error> {
+        let mut manifest = Manifest::default();
+        for file in walk_files(root, walk, warnings)? {
+            let relative = file.strip_prefix(root).unwrap_or(&file).to_path_buf();
+            let properties = match get_config_with(&file, lookup, warnings) {
+                Ok(properties) => properties,
+                Err(err) => {
+                    manifest.errors.insert(relative, err.to_string());
+                    continue;
+                }
+            };
+            let fingerprint = properties_fingerprint(&properties);
+            manifest.files.insert(relative, fingerprint.clone());
+            manifest.fingerprints.entry(fingerprint).or_insert(properties);
+        }
+        Ok(manifest)
+    }
+
+    /// Serializes the manifest as JSON, with `/` separated paths.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n  \"version\": 1,\n  \"files\": {");
+        for (i, (path, fingerprint)) in self.files.iter().enumerate() {
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            let path = path.to_string_lossy().replace('\\', "/");
+            json.push_str(&format!("    {}: {}", json_string(&path), json_string(fingerprint)));
+        }
+        json.push_str("\n  },\n  \"fingerprints\": {");
+        for (i, (fingerprint, properties)) in self.fingerprints.iter().enumerate() {
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            json.push_str(&format!("    {}: {{", json_string(fingerprint)));
+            for (j, (key, value)) in properties.iter().enumerate() {
+                json.push_str(if j == 0 { "" } else { ", " });
+                json.push_str(&format!("{}: {}", json_string(key), json_string(value)));
+            }
+            json.push('}');
+        }
+        json.push_str("\n  },\n  \"errors\": {");
+        for (i, (path, error)) in self.errors.iter().enumerate() {
+            json.push_str(if i == 0 { "\n" } else { ",\n" });
+            let path = path.to_string_lossy().replace('\\', "/");
+            json.push_str(&format!("    {}: {}", json_string(&path), json_string(error)));
+        }
+        json.push_str("\n  }\n}\n");
+        json
+    }
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use test_support::FixtureBuilder;
+
+    #[test]
+    fn fingerprints_ignore_order() {
+        let mut a = OrderMap::new();
+        a.insert("indent_style".to_string(), "tab".to_string());
+        a.insert("tab_width".to_string(), "8".to_string());
+        let mut b = OrderMap::new();
+        b.insert("tab_width".to_string(), "8".to_string());
+        b.insert("indent_style".to_string(), "tab".to_string());
+        assert_eq!(properties_fingerprint(&a), properties_fingerprint(&b));
+        assert_eq!(properties_fingerprint(&OrderMap::new()), "cbf29ce484222325");
+        b.insert("tab_width".to_string(), "4".to_string());
+        assert!(properties_fingerprint(&a) != properties_fingerprint(&b));
+    }
+
+    #[test]
+    fn maps_files_to_fingerprints() {
+        let root = env::current_dir().unwrap().join("test_files/overrides");
+        let lookup = LookupOptions {
+            conffile_overrides: vec![".editorconfig.user".to_string()],
+            ..LookupOptions::default()
+        };
+        let manifest = Manifest::build(&root, &lookup, &WalkOptions::default(), &mut vec![])
+            .unwrap();
+        let fingerprint = &manifest.files[Path::new("foo/file.txt")];
+        assert_eq!(manifest.fingerprints[fingerprint].get("end_of_line").unwrap(), "crlf");
+        let json = manifest.to_json();
+        assert!(json.contains(&format!("    \"foo/file.txt\": \"{}\"", fingerprint)));
+        assert!(json.contains("\"end_of_line\": \"crlf\""));
+        assert_eq!(json_string("a\"b\\c\u{1}"), "\"a\\\"b\\\\c\\u0001\"");
+    }
+
+    #[test]
+    fn records_the_files_that_fail() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig", "root = true\n[*]\nindent_size = 2\n")
+            .config("broken/.editorconfig", "[*]\n= 2\n")
+            .file("a.txt")
+            .file("broken/b.txt")
+            .file(".git/config")
+            .build()
+            .unwrap();
+        let manifest = Manifest::build(fixture.root(),
+                                       &fixture.lookup_options(),
+                                       &WalkOptions::default(),
+                                       &mut vec![])
+            .unwrap();
+        let files: Vec<_> = manifest.files.keys().collect();
+        assert_eq!(files, [Path::new(".editorconfig"), Path::new("a.txt")]);
+        let errors: Vec<_> = manifest.errors.keys().collect();
+        assert_eq!(errors, [Path::new("broken/.editorconfig"), Path::new("broken/b.txt")]);
+        let json = manifest.to_json();
+        assert!(json.contains("  \"errors\": {\n    \"broken/.editorconfig\": "));
+        let error = json_string(&manifest.errors[Path::new("broken/b.txt")]);
+        assert!(json.contains(&format!(",\n    \"broken/b.txt\": {}\n  }}\n}}\n", error)));
+    }
+
+    #[test]
+    fn groups_files_by_properties() {
+        let dir = env::current_dir().unwrap().join("test_files/simple");
+        let paths: Vec<_> = ["a.py", "b.txt", "lib/c.py", "d.txt"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect();
+        let groups = group_by_config(&paths, &LookupOptions::default(), &mut vec![]).unwrap();
+        let names: Vec<Vec<_>> = groups.iter()
+            .map(|(_, files)| files.iter().map(|file| file.strip_prefix(&dir).unwrap()).collect())
+            .collect();
+        assert_eq!(names,
+                   [vec![Path::new("a.py"), Path::new("lib/c.py")],
+                    vec![Path::new("b.txt"), Path::new("d.txt")]]);
+        assert_eq!(groups[0].0.get("indent_size").unwrap(), "4");
+    }
+}
@@ -0,0 +1,160 @@
+//! Texts of diagnostics, looked up by stable message IDs so hosts can translate them.
+//!
+//! A catalog file has one `id = template` line per message, and `#` comments. Templates
+//! refer to the arguments of a diagnostic as `{name}`, e.g.
+//!
+//! ```text
+//! git-line-endings.conflict = git extrait en {checkout_eol} à cause de {setting}
+//! ```
+
+use ordermap::OrderMap;
+
+/// The message IDs diagnostics use and their English texts. IDs are never renamed or
+/// reused for a different meaning.
+pub const DEFAULT_MESSAGES: &[(&str, &str)] = &[("git-line-endings.conflict",
+                                                 "end_of_line = {end_of_line} but git checks \
+                                                  files out with {checkout_eol} because of \
+                                                  {setting}"),
+                                                ("redundant-setting.inherited",
+                                                 "[{section}] {key} = {value} on line {line} \
+                                                  restates the value inherited from \
+                                                  {inherited_from}"),
+                                                ("inline-comment.part-of-value",
+                                                 "{key} = {value} on line {line} is followed by \
+                                                  a comment, EditorConfig 0.15.0 and later read \
+                                                  the value as `{kept}`"),
+                                                ("broken-config.unusable",
+                                                 "can't be used: {error}"),
+                                                ("invalid-pattern.literal",
+                                                 "[{section}] can't be translated to a regex, \
+                                                  it only matches itself: {error}"),
+                                                ("simplifiable-pattern.pattern",
+                                                 "[{section}] on line {line} matches the same \
+                                                  files as [{simplified}]"),
+                                                ("simplifiable-pattern.repeated",
+                                                 "[{section}] on line {line} repeats the \
+                                                  section right before it"),
+                                                ("invalid-value.not-allowed",
+                                                 "{key} = {value} on line {line} isn't allowed, \
+                                                  expected {expected}"),
+                                                ("misspelled-property.did-you-mean",
+                                                 "{key} on line {line} isn't a known property, \
+                                                  did you mean {suggestion}?"),
+                                                ("expensive-pattern.slow",
+                                                 "[{section}] may be slow to match: {issue}; \
+                                                  {suggestion}"),
+                                                ("newer-property.ignored",
+                                                 "{key} = {value} on line {line} needs \
+                                                  EditorConfig {since}, editors on {minimum} \
+                                                  ignore it")];
+
+/// Values filling the `{name}` placeholders of a message.
+pub type MessageArgs = Vec<(&'static str, String)>;
+
+/// Translated message templates, falling back to the English ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageCatalog {
+    messages: OrderMap<String, String>,
+}
+
+impl MessageCatalog {
+    /// A catalog without translations, giving the English texts.
+    pub fn english() -> MessageCatalog {
+        MessageCatalog::default()
+    }
+
+    /// Parses a catalog file. Unknown IDs are rejected, so typos don't silently leave
+    /// messages untranslated.
+    pub fn parse(contents: &str) -> Result<MessageCatalog, String> {
+        let mut messages = OrderMap::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let id = parts.next().unwrap_or("").trim();
+            let template = match parts.next() {
+                Some(template) => template.trim(),
+                None => return Err(format!("line {}: expected `id = template`", number + 1)),
+            };
+            if !DEFAULT_MESSAGES.iter().any(|&(known, _)| known == id) {
+                return Err(format!("line {}: unknown message `{}`", number + 1, id));
+            }
+            messages.insert(id.to_string(), template.to_string());
+        }
+        Ok(MessageCatalog { messages })
+    }
+
+    /// Returns the message `id` with its placeholders replaced by `args`.
+    pub fn format(&self, id: &str, args: &[(&'static str, String)]) -> String {
+        let template = match self.messages.get(id) {
+            Some(template) => template.as_str(),
+            None => {
+                match DEFAULT_MESSAGES.iter().find(|&&(known, _)| known == id) {
+                    Some(&(_, template)) => template,
+                    None => id,
+                }
+            }
+        };
+        // In one pass, so that values are never read as placeholders themselves
+        let mut message = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            message.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let value = rest.find('}').and_then(|end| {
+                let name = &rest[1..end];
+                args.iter().find(|&&(arg, _)| arg == name).map(|(_, value)| (value, end))
+            });
+            match value {
+                Some((value, end)) => {
+                    message.push_str(value);
+                    rest = &rest[end + 1..];
+                }
+                // Not a placeholder, or one without argument
+                None => {
+                    message.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        message.push_str(rest);
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_translations_with_english_fallback() {
+        let args = vec![("end_of_line", "lf".to_string()),
+                        ("checkout_eol", "CRLF".to_string()),
+                        ("setting", "core.autocrlf = true".to_string())];
+        assert_eq!(MessageCatalog::english().format("git-line-endings.conflict", &args),
+                   "end_of_line = lf but git checks files out with CRLF because of \
+                    core.autocrlf = true");
+
+        let french = MessageCatalog::parse("# French\n\
+                                            git-line-endings.conflict = {checkout_eol} \
+                                            au lieu de {end_of_line} ({setting})\n")
+            .unwrap();
+        assert_eq!(french.format("git-line-endings.conflict", &args),
+                   "CRLF au lieu de lf (core.autocrlf = true)");
+
+        // Values holding braces are written as is
+        let args = vec![("section", "{a,b}".to_string()),
+                        ("issue", "{issue} {".to_string()),
+                        ("suggestion", "none".to_string())];
+        assert_eq!(MessageCatalog::english().format("expensive-pattern.slow", &args),
+                   "[{a,b}] may be slow to match: {issue} {; none");
+        let braces = MessageCatalog::parse("expensive-pattern.slow = {{section}} {unknown} {\n")
+            .unwrap();
+        assert_eq!(braces.format("expensive-pattern.slow", &args), "{{a,b}} {unknown} {");
+
+        assert!(MessageCatalog::parse("git-line-ending.conflict = typo\n").is_err());
+        assert!(MessageCatalog::parse("no template\n").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Callbacks fired while a lookup progresses, for hosts showing what's being resolved.
+
+use std::fmt;
+use std::path::Path;
+
+use ordermap::OrderMap;
+
+/// Receives the milestones of lookups. Every method does nothing by default, so
+/// implementations only override the ones they care about.
+///
+/// The callbacks run on the thread doing the lookup, in the middle of it: they should
+/// return quickly, e.g. by just updating a progress indicator.
+pub trait ResolveObserver: Send + Sync {
+    /// A config file was found next to the target or in one of its parent directories.
+    /// It may still be skipped, e.g. because it's unreadable or untrusted.
+    fn config_discovered(&self, _conf_file: &Path) {}
+
+    /// The section `section` of `conf_file` matched the target.
+    fn section_matched(&self, _conf_file: &Path, _section: &str) {}
+
+    /// All config files were read and merged into `properties`, the result of the lookup.
+    fn merge_complete(&self, _target: &Path, _properties: &OrderMap<String, String>) {}
+}
+
+impl fmt::Debug for dyn ResolveObserver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ResolveObserver")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use std::{env, fs, process};
+    use {get_config_with, LookupOptions};
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl ResolveObserver for RecordingObserver {
+        fn config_discovered(&self, conf_file: &Path) {
+            let dir = conf_file.parent().unwrap().file_name().unwrap();
+            self.events.lock().unwrap().push(format!("discovered {}", dir.to_string_lossy()));
+        }
+
+        fn section_matched(&self, _conf_file: &Path, section: &str) {
+            self.events.lock().unwrap().push(format!("matched [{}]", section));
+        }
+
+        fn merge_complete(&self, _target: &Path, properties: &OrderMap<String, String>) {
+            self.events.lock().unwrap().push(format!("merged {} properties", properties.len()));
+        }
+    }
+
+    #[test]
+    fn notifies_observer_of_milestones() {
+        let path = Path::new("./test_files/multi_level/foo/bar/file.txt");
+        let path = path.canonicalize().unwrap();
+        let observer = Arc::new(RecordingObserver::default());
+        let options = LookupOptions { observer: Some(observer.clone()), ..LookupOptions::default() };
+        get_config_with(&path, &options, &mut vec![]).unwrap();
+        assert_eq!(*observer.events.lock().unwrap(),
+                   vec!["discovered multi_level", "matched [*]", "merged 2 properties"]);
+    }
+
+    #[test]
+    fn reports_skipped_config_files_as_discovered() {
+        let dir = env::temp_dir().join(format!("editorconfig-observer-{}", process::id()));
+        fs::create_dir_all(dir.join("r/a")).unwrap();
+        // A name no config file above the temporary directory has
+        let conffile = ".editorconfig-observed";
+        fs::write(dir.join("r").join(conffile), "[*]\ncharset = latin1\n").unwrap();
+        fs::write(dir.join("r/a").join(conffile),
+                  "[*.md]\nindent_size = 2\n[*.rs]\nindent_size = 4\n")
+            .unwrap();
+        fs::write(dir.join("r/a/x.rs"), "").unwrap();
+        let observer = Arc::new(RecordingObserver::default());
+        let options = LookupOptions {
+            conffile: conffile.to_string(),
+            observer: Some(observer.clone()),
+            trust_root: Some(dir.join("r/a")),
+            ..LookupOptions::default()
+        };
+        let properties = get_config_with(&dir.join("r/a/x.rs"), &options, &mut vec![]);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(properties.unwrap().get("charset"), None);
+        // indent_size, and tab_width following it
+        assert_eq!(*observer.events.lock().unwrap(),
+                   vec!["discovered a", "matched [*.rs]", "discovered r", "merged 2 properties"]);
+    }
+}
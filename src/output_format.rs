@@ -0,0 +1,91 @@
+//! What the results of a lookup are written as.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// How the properties of the targets, or the diagnostics of `check` and `lint`, are written
+/// out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `key=value` lines, under a `[path]` header when there are several targets.
+    #[default]
+    Text,
+    /// A JSON array with an object per target, holding either its properties or its error.
+    Json,
+    /// A JSON object of the properties keyed by target, e.g. `{"file.rs": {"indent_size":
+    /// "4"}}`. Targets that failed are left out, their errors only go to stderr. Lookups
+    /// only.
+    JsonMap,
+    /// A SARIF 2.1.0 log, for code scanning dashboards. Diagnostics only.
+    Sarif,
+    /// A JUnit XML report with a failed test case per diagnostic, for CI test tabs.
+    /// Diagnostics only.
+    Junit,
+    /// The Reviewdog Diagnostic Format, for review comments. Diagnostics only.
+    Rdjson,
+}
+
+impl OutputFormat {
+    /// Whether the format can only hold diagnostics, not the properties of targets.
+    pub fn is_diagnostics_only(self) -> bool {
+        match self {
+            OutputFormat::Text | OutputFormat::Json | OutputFormat::JsonMap => false,
+            OutputFormat::Sarif | OutputFormat::Junit | OutputFormat::Rdjson => true,
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<OutputFormat, String> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "json-map" => Ok(OutputFormat::JsonMap),
+            "sarif" => Ok(OutputFormat::Sarif),
+            "junit" => Ok(OutputFormat::Junit),
+            "rdjson" => Ok(OutputFormat::Rdjson),
+            _ => {
+                Err(format!("unknown output format `{}`, expected text, json, json-map, \
+                             sarif, junit or rdjson",
+                            s))
+            }
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::JsonMap => "json-map",
+            OutputFormat::Sarif => "sarif",
+            OutputFormat::Junit => "junit",
+            OutputFormat::Rdjson => "rdjson",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_what_it_displays() {
+        let formats = [OutputFormat::Text, OutputFormat::Json, OutputFormat::JsonMap,
+                       OutputFormat::Sarif, OutputFormat::Junit, OutputFormat::Rdjson];
+        for &format in &formats {
+            assert_eq!(format.to_string().parse::<OutputFormat>().unwrap(), format);
+        }
+        let diagnostics_only: Vec<_> = formats.iter()
+            .filter(|format| format.is_diagnostics_only())
+            .map(OutputFormat::to_string)
+            .collect();
+        assert_eq!(diagnostics_only, ["sarif", "junit", "rdjson"]);
+        assert_eq!("JSON".parse::<OutputFormat>().unwrap_err(),
+                   "unknown output format `JSON`, expected text, json, json-map, sarif, junit \
+                    or rdjson");
+    }
+}
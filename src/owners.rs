@@ -0,0 +1,125 @@
+//! Who owns which files according to a CODEOWNERS file, to route check results.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use {Error, Glob};
+
+/// Where CODEOWNERS files are looked for, relative to the repository root.
+pub const CODEOWNERS_LOCATIONS: &[&str] = &["CODEOWNERS", ".github/CODEOWNERS", "docs/CODEOWNERS"];
+
+struct OwnersRule {
+    glob: Glob,
+    /// Whether the pattern ended with `/`, only matching what's inside directories.
+    directory_only: bool,
+    owners: Vec<String>,
+}
+
+/// The rules of a CODEOWNERS file.
+pub struct CodeOwners {
+    /// The directory the patterns are relative to.
+    root: PathBuf,
+    rules: Vec<OwnersRule>,
+}
+
+impl CodeOwners {
+    /// Looks for a CODEOWNERS file in `start` and then in every parent directory, and
+    /// reads the first one found.
+    pub fn find(start: &Path) -> Result<Option<CodeOwners>, Error> {
+        for dir in start.ancestors() {
+            for location in CODEOWNERS_LOCATIONS {
+                let path = dir.join(location);
+                if path.is_file() {
+                    return Ok(Some(CodeOwners::parse(&fs::read_to_string(&path)?, dir)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parses the contents of a CODEOWNERS file whose patterns are relative to `root`.
+    /// Lines without owners are kept, as they remove the ownership given by earlier lines.
+    pub fn parse(contents: &str, root: &Path) -> CodeOwners {
+        let rules = contents.lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let pattern = fields.next().unwrap_or("");
+                let directory_only = pattern.ends_with('/');
+                // Same semantics as config file sections: patterns without a slash match at
+                // any depth, others are relative to the root
+                let pattern = pattern.trim_end_matches('/');
+                let pattern = if pattern.contains('/') && !pattern.starts_with('/') {
+                    format!("/{}", pattern)
+                } else {
+                    pattern.to_string()
+                };
+                // Lines whose pattern can't be compiled are skipped, like comments
+                Glob::new(&pattern).ok().map(|glob| {
+                    OwnersRule {
+                        glob,
+                        directory_only,
+                        owners: fields.map(str::to_string).collect(),
+                    }
+                })
+            })
+            .collect();
+        CodeOwners {
+            root: root.to_path_buf(),
+            rules,
+        }
+    }
+
+    /// Returns the owners of `path`, from the last matching rule. Relative paths are taken
+    /// to be relative to the root of the CODEOWNERS file.
+    pub fn owners_of(&self, path: &Path) -> &[String] {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        for rule in self.rules.iter().rev() {
+            // A pattern naming a directory owns everything inside
+            let mut candidates = ancestors(&relative);
+            if !rule.directory_only {
+                candidates.push(&relative);
+            }
+            let matches = |candidate: &&str| rule.glob.matches(candidate);
+            if candidates.iter().any(matches) {
+                return &rule.owners;
+            }
+        }
+        &[]
+    }
+}
+
+/// The directories containing `path`, e.g. `a` and `a/b` for `a/b/c`.
+fn ancestors(path: &str) -> Vec<&str> {
+    path.match_indices('/')
+        .map(|(index, _)| &path[..index])
+        .filter(|dir| !dir.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let owners = CodeOwners::parse("# Default owners\n\
+                                        *       @org/everyone\n\
+                                        *.rs    @org/rust @alice\n\
+                                        /docs/  @org/docs\n\
+                                        build   @org/build\n\
+                                        /src/legacy/**/*.rs @bob\n\
+                                        /src/legacy/vendored.rs\n",
+                                       Path::new("/repo"));
+        let owners_of = |path: &str| owners.owners_of(Path::new(path)).join(" ");
+        assert_eq!(owners_of("/repo/README.md"), "@org/everyone");
+        assert_eq!(owners_of("src/main.rs"), "@org/rust @alice");
+        assert_eq!(owners_of("docs/guide/index.md"), "@org/docs");
+        assert_eq!(owners_of("src/docs/index.md"), "@org/everyone");
+        assert_eq!(owners_of("tools/build/run.sh"), "@org/build");
+        assert_eq!(owners_of("src/legacy/a/b.rs"), "@bob");
+        assert_eq!(owners_of("src/legacy/vendored.rs"), "");
+    }
+}
@@ -0,0 +1,74 @@
+//! Running lookups on several threads while keeping their results in the order they were
+//! asked for, so that the output of the bulk modes doesn't depend on thread scheduling.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// The number of threads `jobs` asks for, 0 standing for one per CPU.
+pub(crate) fn effective_jobs(jobs: usize) -> usize {
+    if jobs == 0 {
+        thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+    } else {
+        jobs
+    }
+}
+
+/// Calls `f` on every item of `items` on up to `jobs` threads, see `effective_jobs`, and
+/// returns the results in the order of `items`, whichever finished first.
+///
+/// Results are buffered until all of them are in, so callers write them out in order
+/// once the slowest item is done.
+pub(crate) fn map_ordered<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+    where T: Sync,
+          R: Send,
+          F: Fn(&T) -> R + Sync
+{
+    let jobs = effective_jobs(jobs).min(items.len());
+    if jobs <= 1 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    // The result of each item goes to the slot at its index
+    let slots: Vec<Mutex<Option<R>>> = items.iter().map(|_| Mutex::new(None)).collect();
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= items.len() {
+                    break;
+                }
+                let result = f(&items[i]);
+                *slots[i].lock().unwrap() = Some(result);
+            });
+        }
+    });
+    slots.into_iter()
+        .map(|slot| slot.into_inner().unwrap().expect("every item has a result"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn keeps_the_order_of_the_items() {
+        // The first items finish last
+        let items: Vec<u64> = (0..16).collect();
+        let results = map_ordered(&items, 4, |&i| {
+            thread::sleep(Duration::from_millis(16 - i));
+            i * 2
+        });
+        assert_eq!(results, (0..16).map(|i| i * 2).collect::<Vec<_>>());
+        assert_eq!(map_ordered(&items, 1, |&i| i), items);
+        assert!(map_ordered(&[] as &[u64], 0, |&i| i).is_empty());
+    }
+
+    #[test]
+    fn uses_one_thread_per_cpu_for_zero_jobs() {
+        assert!(effective_jobs(0) >= 1);
+        assert_eq!(effective_jobs(3), 3);
+    }
+}
@@ -0,0 +1,126 @@
+//! How file paths are shown to the user.
+
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// How paths are echoed back in the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PathStyle {
+    /// Exactly as the path was given or found.
+    #[default]
+    AsGiven,
+    /// Relative to a base directory, usually the current working directory.
+    Relative,
+    /// Absolute, with `.` and `..` components resolved.
+    Absolute,
+}
+
+impl FromStr for PathStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<PathStyle, String> {
+        match s {
+            "as-given" => Ok(PathStyle::AsGiven),
+            "relative" => Ok(PathStyle::Relative),
+            "absolute" => Ok(PathStyle::Absolute),
+            _ => Err(format!("unknown path style `{}`, expected relative, absolute or as-given", s)),
+        }
+    }
+}
+
+impl fmt::Display for PathStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            PathStyle::AsGiven => "as-given",
+            PathStyle::Relative => "relative",
+            PathStyle::Absolute => "absolute",
+        })
+    }
+}
+
+impl PathStyle {
+    /// Formats `path` in this style. Relative paths are taken to be relative to `base`,
+    /// which must be absolute.
+    pub fn display(&self, path: &Path, base: &Path) -> String {
+        let shown = match *self {
+            PathStyle::AsGiven => path.to_path_buf(),
+            PathStyle::Absolute => absolute(path, base),
+            PathStyle::Relative => relative_to(&absolute(path, base), &normalize(base)),
+        };
+        shown.display().to_string()
+    }
+}
+
+/// Resolves `.` and `..` without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            c => result.push(c.as_os_str()),
+        }
+    }
+    result
+}
+
+fn absolute(path: &Path, base: &Path) -> PathBuf {
+    normalize(&base.join(path))
+}
+
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let path_components: Vec<_> = path.components().collect();
+    let base_components: Vec<_> = base.components().collect();
+    let common = path_components.iter()
+        .zip(base_components.iter())
+        .take_while(|&(a, b)| a == b)
+        .count();
+    // Nothing in common (e.g. different drives on Windows)
+    if common == 0 {
+        return path.to_path_buf();
+    }
+    let mut result = PathBuf::new();
+    for _ in common..base_components.len() {
+        result.push("..");
+    }
+    for component in &path_components[common..] {
+        result.push(component.as_os_str());
+    }
+    if result.as_os_str().is_empty() {
+        result.push(".");
+    }
+    result
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_paths_in_every_style() {
+        let base = Path::new("/home/user/project");
+        let path = Path::new("./src/../src/main.rs");
+        assert_eq!(PathStyle::AsGiven.display(path, base), "./src/../src/main.rs");
+        assert_eq!(PathStyle::Absolute.display(path, base), "/home/user/project/src/main.rs");
+        assert_eq!(PathStyle::Relative.display(path, base), "src/main.rs");
+    }
+
+    #[test]
+    fn relative_paths_climb_out_of_base() {
+        let base = Path::new("/home/user/project");
+        assert_eq!(PathStyle::Relative.display(Path::new("/home/user/.editorconfig"), base),
+                   "../.editorconfig");
+        assert_eq!(PathStyle::Relative.display(Path::new("/home/user/project"), base), ".");
+    }
+
+    #[test]
+    fn parses_path_styles() {
+        assert_eq!("relative".parse(), Ok(PathStyle::Relative));
+        assert_eq!("absolute".parse(), Ok(PathStyle::Absolute));
+        assert_eq!("as-given".parse(), Ok(PathStyle::AsGiven));
+        assert!("canonical".parse::<PathStyle>().is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! The properties lookups add to the ones config files set, which hosts don't all agree on.
+
+use ordermap::OrderMap;
+
+use {LookupOptions, SpecVersion};
+
+/// Which properties are filled in from the others after matching the sections of a config
+/// file, see `LookupOptions::preprocessing`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PreprocessingProfile {
+    /// Like the reference cores, as editorconfig-core-py does it:
+    ///
+    /// - `indent_style = tab` without `indent_size` sets `indent_size = tab`,
+    /// - a numeric `indent_size` without `tab_width` sets `tab_width` to it,
+    /// - `indent_size = tab` with a `tab_width` sets `indent_size` to it.
+    #[default]
+    ReferenceCore,
+    /// Like editors keeping their own tab size for tab indentation, e.g. VS Code: the rules
+    /// of `ReferenceCore` but `indent_style = tab` not setting `indent_size`.
+    VsCodeLike,
+    /// The properties as the config files set them.
+    None,
+}
+
+impl PreprocessingProfile {
+    /// The profile of `lookup`, `ReferenceCore` keeping to the rules of its `spec_version`:
+    /// cores before 0.10.0 don't set `indent_size = tab`, like `VsCodeLike`.
+    pub fn for_lookup(lookup: &LookupOptions) -> PreprocessingProfile {
+        let version = lookup.spec_version.as_deref().and_then(|v| v.parse::<SpecVersion>().ok());
+        match (lookup.preprocessing, version) {
+            (PreprocessingProfile::ReferenceCore, Some(version))
+                if version < SpecVersion::INDENT_SIZE_TAB => PreprocessingProfile::VsCodeLike,
+            (profile, _) => profile,
+        }
+    }
+
+    /// Fills in the properties this profile derives from the others.
+    pub fn apply(self, properties: &mut OrderMap<String, String>) {
+        if self == PreprocessingProfile::None {
+            return;
+        }
+        if self == PreprocessingProfile::ReferenceCore &&
+           properties.get("indent_style").is_some_and(|style| style == "tab") &&
+           properties.get("indent_size").is_none() {
+            properties.insert("indent_size".to_string(), "tab".to_string());
+        }
+        if let Some(indent_size) = properties.get("indent_size").cloned() {
+            if indent_size != "tab" {
+                if properties.get("tab_width").is_none() {
+                    properties.insert("tab_width".to_string(), indent_size);
+                }
+            } else if let Some(tab_width) = properties.get("tab_width").cloned() {
+                properties.insert("indent_size".to_string(), tab_width);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(profile: PreprocessingProfile, pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        let mut properties: OrderMap<_, _> =
+            pairs.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect();
+        profile.apply(&mut properties);
+        properties.into_iter().collect()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn reference_core_fills_in_indent_size_and_tab_width() {
+        let profile = PreprocessingProfile::ReferenceCore;
+        assert_eq!(applied(profile, &[("indent_style", "tab")]),
+                   pairs(&[("indent_style", "tab"), ("indent_size", "tab")]));
+        assert_eq!(applied(profile, &[("indent_style", "tab"), ("tab_width", "8")]),
+                   pairs(&[("indent_style", "tab"), ("tab_width", "8"), ("indent_size", "8")]));
+        assert_eq!(applied(profile, &[("indent_size", "4")]),
+                   pairs(&[("indent_size", "4"), ("tab_width", "4")]));
+        assert_eq!(applied(profile, &[("indent_size", "4"), ("tab_width", "8")]),
+                   pairs(&[("indent_size", "4"), ("tab_width", "8")]));
+    }
+
+    #[test]
+    fn vs_code_like_leaves_the_size_of_tabs_alone() {
+        let profile = PreprocessingProfile::VsCodeLike;
+        assert_eq!(applied(profile, &[("indent_style", "tab")]), pairs(&[("indent_style", "tab")]));
+        assert_eq!(applied(profile, &[("indent_style", "tab"), ("tab_width", "8")]),
+                   pairs(&[("indent_style", "tab"), ("tab_width", "8")]));
+        assert_eq!(applied(profile, &[("indent_size", "tab"), ("tab_width", "8")]),
+                   pairs(&[("indent_size", "8"), ("tab_width", "8")]));
+        assert_eq!(applied(profile, &[("indent_size", "4")]),
+                   pairs(&[("indent_size", "4"), ("tab_width", "4")]));
+    }
+
+    #[test]
+    fn reference_core_follows_the_spec_version() {
+        let lookup = |version: Option<&str>, preprocessing| LookupOptions {
+            spec_version: version.map(str::to_string),
+            preprocessing,
+            ..LookupOptions::default()
+        };
+        let core = PreprocessingProfile::ReferenceCore;
+        assert_eq!(PreprocessingProfile::for_lookup(&lookup(None, core)), core);
+        assert_eq!(PreprocessingProfile::for_lookup(&lookup(Some("0.10.0"), core)), core);
+        assert_eq!(PreprocessingProfile::for_lookup(&lookup(Some("0.9.0"), core)),
+                   PreprocessingProfile::VsCodeLike);
+        assert_eq!(PreprocessingProfile::for_lookup(&lookup(Some("0.9.0"),
+                                                            PreprocessingProfile::None)),
+                   PreprocessingProfile::None);
+    }
+
+    #[test]
+    fn none_adds_nothing() {
+        let written = [("indent_style", "tab"), ("indent_size", "tab"), ("tab_width", "8")];
+        assert_eq!(applied(PreprocessingProfile::None, &written), pairs(&written));
+        assert_eq!(applied(PreprocessingProfile::None, &[("indent_size", "4")]),
+                   pairs(&[("indent_size", "4")]));
+    }
+}
@@ -0,0 +1,119 @@
+//! The properties of a file along with the config files they were read from.
+
+use std::ops::Deref;
+use std::path::PathBuf;
+
+use ordermap::OrderMap;
+
+/// The properties of a file, like `get_config_with` returns them, and the config files the
+/// lookup read to get them. Derefs to the properties.
+///
+/// ```
+/// use std::path::Path;
+/// use editorconfig::{get_properties_with, LookupOptions};
+///
+/// let path = Path::new("test_files/simple/file.txt").canonicalize().unwrap();
+/// let properties = get_properties_with(&path, &LookupOptions::default(), &mut vec![]).unwrap();
+/// assert_eq!(properties.get("end_of_line").unwrap(), "lf");
+/// assert_eq!(properties.contributing_configs().len(), 1);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Properties {
+    properties: OrderMap<String, String>,
+    contributing_configs: Vec<PathBuf>,
+}
+
+impl Properties {
+    pub(crate) fn new(properties: OrderMap<String, String>,
+                      contributing_configs: Vec<PathBuf>)
+                      -> Properties {
+        Properties {
+            properties,
+            contributing_configs,
+        }
+    }
+
+    /// The config files read, closest to the file first, including those giving it no
+    /// property: editing any of them can change the properties.
+    pub fn contributing_configs(&self) -> &[PathBuf] {
+        &self.contributing_configs
+    }
+
+    /// The properties whose key is namespaced with `namespace` and a dot, keyed by the rest of
+    /// their key, like the `dotnet_diagnostic.*` keys of .NET analyzers:
+    ///
+    /// ```
+    /// use std::path::Path;
+    /// use editorconfig::{get_properties_with, LookupOptions};
+    ///
+    /// let path = Path::new("test_files/namespaced/Program.cs").canonicalize().unwrap();
+    /// let properties = get_properties_with(&path, &LookupOptions::default(), &mut vec![]).unwrap();
+    /// let diagnostics = properties.namespace("dotnet_diagnostic");
+    /// assert_eq!(diagnostics.get("ca1000.severity").unwrap(), "warning");
+    /// assert_eq!(properties.namespace("dotnet_diagnostic.CA1000").get("severity").unwrap(),
+    ///            "warning");
+    /// ```
+    ///
+    /// Keys are lowercase like all keys, `namespace` matches whatever its case.
+    pub fn namespace(&self, namespace: &str) -> OrderMap<String, String> {
+        let prefix = format!("{}.", namespace.to_lowercase());
+        self.properties
+            .iter()
+            .filter(|&(key, _)| key.starts_with(&prefix) && key.len() > prefix.len())
+            .map(|(key, value)| (key[prefix.len()..].to_string(), value.clone()))
+            .collect()
+    }
+
+    /// The properties alone.
+    pub fn into_map(self) -> OrderMap<String, String> {
+        self.properties
+    }
+}
+
+impl Deref for Properties {
+    type Target = OrderMap<String, String>;
+
+    fn deref(&self) -> &OrderMap<String, String> {
+        &self.properties
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use {get_properties_with, LookupOptions};
+
+    #[test]
+    fn lists_the_config_files_read_closest_first() {
+        let dir = Path::new("test_files/overrides").canonicalize().unwrap();
+        let lookup = LookupOptions {
+            conffile_overrides: vec![".editorconfig.user".to_string()],
+            ..LookupOptions::default()
+        };
+        let properties = get_properties_with(&dir.join("foo/file.txt"), &lookup, &mut vec![])
+            .unwrap();
+        assert_eq!(properties.contributing_configs(),
+                   [dir.join("foo/.editorconfig.user"), dir.join("foo/.editorconfig"),
+                    dir.join(".editorconfig.user"), dir.join(".editorconfig")]);
+        assert_eq!(properties.get("charset").unwrap(), "latin1");
+    }
+
+    #[test]
+    fn groups_namespaced_keys_kept_whole() {
+        let dir = Path::new("test_files/namespaced").canonicalize().unwrap();
+        let properties = get_properties_with(&dir.join("Program.cs"),
+                                             &LookupOptions::default(),
+                                             &mut vec![])
+            .unwrap();
+        let diagnostics = properties.namespace("Dotnet_Diagnostic");
+        assert_eq!(diagnostics.keys().collect::<Vec<_>>(),
+                   ["ca1000.severity", "ide0055.severity"]);
+        assert_eq!(diagnostics["ide0055.severity"], "Suggestion");
+        // Longer than `MAX_KEY_LENGTH` as a whole, not part by part
+        assert_eq!(properties.namespace("dotnet_code_quality.ca1062")
+                       ["null_check_validation_methods"],
+                   "Validate|EnsureNotNull");
+        assert!(properties.namespace("dotnet").is_empty());
+        assert!(properties.namespace("indent_style").is_empty());
+    }
+}
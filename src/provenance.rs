@@ -0,0 +1,281 @@
+//! Where the properties of a file come from, for users wondering why a file gets a value.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use ordermap::OrderMap;
+
+use cache::ConfigCache;
+use {get_config_cached, Error, LookupOptions, ResolveObserver, Warning};
+
+/// A property of a file with the config file and section setting it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedProperty {
+    pub value: String,
+    /// The config file setting the property, `None` when the lookup filled it in from the
+    /// others, like `tab_width` from `indent_size`.
+    pub source_file: Option<PathBuf>,
+    /// The last section of `source_file` matching the file and setting the property.
+    pub section: Option<String>,
+}
+
+/// How the properties of a file were found, see `explain_lookup`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    /// The config files read, closest to the file first.
+    pub config_files: Vec<ConsultedConfig>,
+    /// Every property of the file, with the values config files set it to.
+    pub properties: OrderMap<String, PropertyTrace>,
+}
+
+/// A config file read by a lookup, with its sections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsultedConfig {
+    pub path: PathBuf,
+    /// The sections in the order of the file, each with whether it matched the file.
+    pub sections: Vec<(String, bool)>,
+}
+
+/// A property of a file and the values it was given along the way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropertyTrace {
+    pub value: String,
+    /// Every matching section setting the property, the one that won first: closer config
+    /// files first and, within a file, later sections first. Empty when the lookup filled
+    /// the property in from the others, like `tab_width` from `indent_size`.
+    pub assignments: Vec<Assignment>,
+}
+
+/// A value a section of a config file gives to a property.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Assignment {
+    pub config_file: PathBuf,
+    pub section: String,
+    /// As written in the config file.
+    pub value: String,
+}
+
+/// Finds the configuration of `file_path` like `get_config_with`, with the config files
+/// read, the sections which matched and those which didn't, and every value each property
+/// was given.
+///
+/// ```
+/// use std::path::Path;
+/// use editorconfig::{explain_lookup, LookupOptions};
+///
+/// let path = Path::new("test_files/simple/file.txt").canonicalize().unwrap();
+/// let explanation = explain_lookup(&path, &LookupOptions::default(), &mut vec![]).unwrap();
+/// assert_eq!(explanation.config_files[0].sections[0], ("*".to_string(), true));
+/// assert_eq!(explanation.properties["end_of_line"].assignments[0].section, "*");
+/// ```
+pub fn explain_lookup(file_path: &Path,
+                      lookup: &LookupOptions,
+                      warnings: &mut Vec<Warning>)
+                      -> Result<Explanation, Error> {
+    explain_cached(file_path, lookup, warnings, &ConfigCache::new())
+}
+
+/// `explain_lookup`, reading the config files in `cache` from there.
+pub(crate) fn explain_cached(file_path: &Path,
+                             lookup: &LookupOptions,
+                             warnings: &mut Vec<Warning>,
+                             cache: &ConfigCache)
+                             -> Result<Explanation, Error> {
+    let matches = Arc::new(MatchRecorder {
+        matched: Mutex::default(),
+        inner: lookup.observer.clone(),
+    });
+    let mut recording = lookup.clone();
+    recording.observer = Some(matches.clone());
+    // The cache keeps what the lookup parsed, to look the sections up
+    let properties = get_config_cached(file_path, &recording, warnings, cache)?;
+    let matched = matches.matched.lock().unwrap();
+
+    let mut config_files = vec![];
+    let mut assignments = vec![];
+    for path in properties.contributing_configs() {
+        let parsed = match cache.get(path) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        let matched_here = matched.iter()
+            .find(|&(file, _)| file == path)
+            .map_or(&[][..], |(_, sections)| sections.as_slice());
+        let sections: Vec<_> = parsed.ini
+            .sections()
+            .filter_map(|section| section.clone())
+            .map(|section| {
+                let is_matched = matched_here.contains(&section);
+                (section, is_matched)
+            })
+            .collect();
+        for section in matched_here.iter().rev() {
+            for (key, value) in parsed.ini.section(Some(section.as_str())).into_iter().flatten() {
+                assignments.push((key.to_lowercase(),
+                                  Assignment {
+                                      config_file: path.clone(),
+                                      section: section.clone(),
+                                      value: value.clone(),
+                                  }));
+            }
+        }
+        config_files.push(ConsultedConfig {
+            path: path.clone(),
+            sections,
+        });
+    }
+
+    let properties = properties.into_map()
+        .into_iter()
+        .map(|(key, value)| {
+            let assignments = assignments.iter()
+                .filter(|&(k, _)| *k == key)
+                .map(|(_, assignment)| assignment.clone())
+                .collect();
+            (key, PropertyTrace { value, assignments })
+        })
+        .collect();
+    Ok(Explanation {
+        config_files,
+        properties,
+    })
+}
+
+/// Finds the configuration of `file_path` like `get_config_with`, with the config file and
+/// section each property comes from. See `explain_lookup` for the values they override.
+///
+/// ```
+/// use std::path::Path;
+/// use editorconfig::{get_config_provenance, LookupOptions};
+///
+/// let path = Path::new("test_files/simple/file.txt").canonicalize().unwrap();
+/// let properties = get_config_provenance(&path, &LookupOptions::default(), &mut vec![]).unwrap();
+/// let end_of_line = &properties["end_of_line"];
+/// assert_eq!(end_of_line.value, "lf");
+/// assert!(end_of_line.source_file.as_ref().unwrap().ends_with("simple/.editorconfig"));
+/// assert_eq!(end_of_line.section.as_ref().unwrap(), "*");
+/// ```
+pub fn get_config_provenance(file_path: &Path,
+                             lookup: &LookupOptions,
+                             warnings: &mut Vec<Warning>)
+                             -> Result<OrderMap<String, ResolvedProperty>, Error> {
+    let explanation = explain_lookup(file_path, lookup, warnings)?;
+    Ok(explanation.properties
+        .into_iter()
+        .map(|(key, trace)| {
+            let winner = trace.assignments.into_iter().next();
+            let property = ResolvedProperty {
+                value: trace.value,
+                source_file: winner.as_ref().map(|assignment| assignment.config_file.clone()),
+                section: winner.map(|assignment| assignment.section),
+            };
+            (key, property)
+        })
+        .collect())
+}
+
+/// Records the sections matched in each config file, in the order the lookup read them,
+/// passing every callback on to the observer of the lookup.
+struct MatchRecorder {
+    matched: Mutex<Vec<(PathBuf, Vec<String>)>>,
+    inner: Option<Arc<dyn ResolveObserver>>,
+}
+
+impl ResolveObserver for MatchRecorder {
+    fn config_discovered(&self, conf_file: &Path) {
+        if let Some(ref inner) = self.inner {
+            inner.config_discovered(conf_file);
+        }
+    }
+
+    fn section_matched(&self, conf_file: &Path, section: &str) {
+        let mut matched = self.matched.lock().unwrap();
+        match matched.last_mut() {
+            Some(&mut (ref file, ref mut sections)) if file == conf_file => {
+                sections.push(section.to_string())
+            }
+            _ => matched.push((conf_file.to_path_buf(), vec![section.to_string()])),
+        }
+        if let Some(ref inner) = self.inner {
+            inner.section_matched(conf_file, section);
+        }
+    }
+
+    fn merge_complete(&self, target: &Path, properties: &OrderMap<String, String>) {
+        if let Some(ref inner) = self.inner {
+            inner.merge_complete(target, properties);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::FixtureBuilder;
+    use {get_config_with, load_ini, Resolver};
+
+    #[test]
+    fn tells_which_file_and_section_set_each_property() {
+        let path = Path::new("test_files/overrides/foo/file.txt").canonicalize().unwrap();
+        let lookup = LookupOptions {
+            conffile_overrides: vec![".editorconfig.user".to_string()],
+            ..LookupOptions::default()
+        };
+        let plain = get_config_with(&path, &lookup, &mut vec![]).unwrap();
+        let properties = get_config_provenance(&path, &lookup, &mut vec![]).unwrap();
+        assert_eq!(properties.keys().collect::<Vec<_>>(), plain.keys().collect::<Vec<_>>());
+        let source_file = |key: &str| properties[key].source_file.clone().unwrap();
+        assert!(source_file("charset").ends_with("foo/.editorconfig.user"));
+        assert!(source_file("end_of_line").ends_with("overrides/.editorconfig.user"));
+        assert!(source_file("insert_final_newline").ends_with("overrides/.editorconfig"));
+        assert_eq!(properties["tab_width"].source_file, None);
+        for (key, property) in properties.iter().filter(|&(key, _)| key != "tab_width") {
+            assert_eq!(&property.value, &plain[key]);
+            let source_file = property.source_file.as_ref().unwrap();
+            let ini = load_ini(source_file).unwrap();
+            let section = ini.section(property.section.as_ref()).unwrap();
+            assert!(section.contains_key(key), "{} isn't set by {:?}", key, property);
+        }
+    }
+
+    #[test]
+    fn explains_sections_and_overridden_values() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig",
+                    "root = true\n[*]\nindent_size = 2\n[*.md]\ncharset = latin1\n")
+            .config("src/.editorconfig", "[*.rs]\nindent_size = 4\n[lib.rs]\nindent_size = 8\n")
+            .file("src/lib.rs")
+            .build()
+            .unwrap();
+        let resolver = Resolver::builder().lookup_options(fixture.lookup_options()).build();
+        let explanation = resolver.explain(&fixture.path("src/lib.rs")).unwrap();
+        let consulted: Vec<_> = explanation.config_files.iter().map(|c| c.path.clone()).collect();
+        assert_eq!(consulted, [fixture.path("src/.editorconfig"), fixture.path(".editorconfig")]);
+        assert_eq!(explanation.config_files[1].sections,
+                   [("*".to_string(), true), ("*.md".to_string(), false)]);
+
+        let indent_size = &explanation.properties["indent_size"];
+        assert_eq!(indent_size.value, "8");
+        let chain: Vec<_> = indent_size.assignments
+            .iter()
+            .map(|a| (a.config_file.clone(), a.section.as_str(), a.value.as_str()))
+            .collect();
+        assert_eq!(chain,
+                   [(fixture.path("src/.editorconfig"), "lib.rs", "8"),
+                    (fixture.path("src/.editorconfig"), "*.rs", "4"),
+                    (fixture.path(".editorconfig"), "*", "2")]);
+        assert!(explanation.properties["tab_width"].assignments.is_empty());
+        assert_eq!(explanation.properties.get("charset"), None);
+    }
+
+    #[test]
+    fn leaves_filled_in_properties_without_source() {
+        let path = Path::new("test_files/simple").canonicalize().unwrap().join("setup.py");
+        let lookup = LookupOptions::default();
+        let properties = get_config_provenance(&path, &lookup, &mut vec![]).unwrap();
+        assert_eq!(properties["indent_size"].section.as_ref().unwrap(), "*.py");
+        assert_eq!(properties["charset"].section.as_ref().unwrap(), "*.{js,py}");
+        assert_eq!(properties["tab_width"].value, "4");
+        assert_eq!(properties["tab_width"].source_file, None);
+    }
+}
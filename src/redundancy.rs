@@ -0,0 +1,167 @@
+//! Finding properties of config files which restate what files already inherit from config
+//! files further up, so that configs spread over a monorepo stay minimal.
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use cache::ConfigCache;
+use ini::Ini;
+use {crawl_paths, parsed_config, property_spans, Error, LookupOptions};
+
+/// A property which can be deleted without changing the properties of any file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RedundantSetting {
+    pub section: String,
+    pub key: String,
+    pub value: String,
+    /// Byte range of the value in the config file.
+    pub span: Range<usize>,
+    /// The config file the same value is inherited from.
+    pub inherited_from: PathBuf,
+}
+
+/// Returns the properties of the config file at `conf_file` that restate the value they
+/// would inherit from the config files of parent directories found with `lookup`.
+///
+/// The check errs on the side of reporting nothing: a property is only redundant if its
+/// section is the only one of the file setting its key, the section has no `/` (so it
+/// matches the same files in both directories), and the closest parent config file setting
+/// the key last does so in a section of the same name, with the same value.
+pub fn redundant_settings(conf_file: &Path,
+                          lookup: &LookupOptions)
+                          -> Result<Vec<RedundantSetting>, Error> {
+    redundant_settings_cached(conf_file, lookup, &ConfigCache::new())
+}
+
+/// `redundant_settings`, parsing the config files of parent directories unless they're in
+/// `cache` already.
+pub(crate) fn redundant_settings_cached(conf_file: &Path,
+                                        lookup: &LookupOptions,
+                                        cache: &ConfigCache)
+                                        -> Result<Vec<RedundantSetting>, Error> {
+    let contents = lookup.files.read(conf_file)?;
+    let child = Ini::load_from_str(&contents).map_err(|err| Error::from(err).in_file(conf_file))?;
+    if is_root(&child) {
+        return Ok(vec![]);
+    }
+    let dir = match conf_file.parent() {
+        Some(dir) => dir,
+        None => return Ok(vec![]),
+    };
+    // Looking up the directory itself lists the config files of its parents
+    let mut parents = vec![];
+    let mut root_dir = None;
+    for path in crawl_paths(dir, lookup)? {
+        if root_dir.is_some() && path.parent() != root_dir.as_deref() {
+            break;
+        }
+        let parent = parsed_config(&path, lookup, &mut vec![], cache)?;
+        if is_root(&parent.ini) {
+            root_dir = path.parent().map(Path::to_path_buf);
+        }
+        parents.push((path, parent));
+    }
+
+    let mut result = vec![];
+    for property in property_spans(&contents)? {
+        let section = match property.section {
+            Some(ref section) if !section.contains('/') => section,
+            _ => continue,
+        };
+        let key = property.key.to_lowercase();
+        if sections_setting(&child, &key) > 1 {
+            continue;
+        }
+        let inherited = parents.iter()
+            .find_map(|(path, parent)| {
+                last_setting(&parent.ini, &key).map(|setting| (path, setting))
+            });
+        if let Some((path, (inherited_section, inherited_value))) = inherited {
+            let same_value = if lookup.property_registry.lowercases(&key) {
+                inherited_value.eq_ignore_ascii_case(&property.value)
+            } else {
+                inherited_value == property.value
+            };
+            if inherited_section == section && same_value {
+                result.push(RedundantSetting {
+                    section: section.clone(),
+                    key: property.key.clone(),
+                    value: property.value.clone(),
+                    span: property.span.clone(),
+                    inherited_from: path.clone(),
+                });
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Deletes the lines of the properties in `redundant` from `contents`, the config file
+/// they were found in.
+pub fn remove_redundant(contents: &str, redundant: &[RedundantSetting]) -> String {
+    let mut lines: Vec<Range<usize>> = redundant.iter()
+        .map(|setting| {
+            let start = contents[..setting.span.start].rfind('\n').map_or(0, |i| i + 1);
+            let end = contents[setting.span.end..]
+                .find('\n')
+                .map_or(contents.len(), |i| setting.span.end + i + 1);
+            start..end
+        })
+        .collect();
+    lines.sort_by_key(|line| line.start);
+    let mut result = String::with_capacity(contents.len());
+    let mut pos = 0;
+    for line in lines {
+        if line.start >= pos {
+            result.push_str(&contents[pos..line.start]);
+            pos = line.end;
+        }
+    }
+    result.push_str(&contents[pos..]);
+    result
+}
+
+fn is_root(ini: &Ini) -> bool {
+    ini.get_from::<String>(None, "root").is_some_and(|root| root.eq_ignore_ascii_case("true"))
+}
+
+/// The last section setting `key`, which wins over the others, and its value.
+fn last_setting<'a>(ini: &'a Ini, key: &str) -> Option<(&'a str, &'a str)> {
+    ini.iter()
+        .filter_map(|(section, properties)| {
+            let section = section.as_ref()?;
+            properties.iter()
+                .find(|&(k, _)| k.to_lowercase() == key)
+                .map(|(_, value)| (section.as_str(), value.as_str()))
+        })
+        .last()
+}
+
+fn sections_setting(ini: &Ini, key: &str) -> usize {
+    ini.iter()
+        .filter(|&(section, properties)| {
+            section.is_some() && properties.keys().any(|k| k.to_lowercase() == key)
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn finds_settings_restating_inherited_values() {
+        let conf_file = Path::new("test_files/redundant/pkg/.editorconfig");
+        let redundant = redundant_settings(conf_file, &LookupOptions::default()).unwrap();
+        let found: Vec<_> = redundant.iter()
+            .map(|setting| (setting.section.as_str(), setting.key.as_str()))
+            .collect();
+        assert_eq!(found, [("*", "end_of_line"), ("*.py", "indent_size")]);
+        assert!(redundant[0].inherited_from.ends_with("test_files/redundant/.editorconfig"));
+
+        let contents = fs::read_to_string(conf_file).unwrap();
+        assert_eq!(remove_redundant(&contents, &redundant),
+                   "[*]\nindent_style = tab\n\n[*.py]\n\n[lib/*.js]\ncharset = utf-8\n");
+    }
+}
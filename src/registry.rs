@@ -0,0 +1,342 @@
+//! The properties lookups and checks know the values of, those of the specification by
+//! default, which hosts can extend with their own, like the `ij_*` ones of IntelliJ.
+
+use std::fmt;
+
+use ordermap::OrderMap;
+
+use spec_version::SpecVersion;
+
+/// What values of a property look like.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    /// One of the keywords of the property.
+    Keyword,
+    /// A positive number, or one of the keywords of the property, like `tab` for
+    /// `indent_size`.
+    PositiveInteger,
+    /// `true` or `false`.
+    Boolean,
+    /// Any text, like comma-separated lists.
+    Text,
+}
+
+/// A property and the values it allows.
+///
+/// ```
+/// use editorconfig::{PropertyRegistry, PropertySpec, ValueType};
+///
+/// let mut registry = PropertyRegistry::standard();
+/// registry.register(PropertySpec::new("quote_type", ValueType::Keyword)
+///     .keywords(&["single", "double", "auto"]));
+/// registry.register(PropertySpec::new("ij_*", ValueType::Text).case_sensitive());
+/// assert_eq!(registry.check_value("quote_type", "backtick").unwrap(),
+///            "single, double or auto");
+/// assert!(registry.get("ij_java_imports_layout").is_some());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropertySpec {
+    /// The key, lowercase. A name ending with `*` stands for every key starting with the
+    /// rest of it.
+    pub name: String,
+    pub value_type: ValueType,
+    /// The values allowed besides those of `value_type`, lowercase.
+    pub keywords: Vec<String>,
+    /// Whether lookups lowercase the values, which checks compare ignoring case either way.
+    pub lowercase: bool,
+    /// The version of the spec adding the property, 0.0.0 for those it always had. Editors
+    /// whose core is older ignore it, see `LookupOptions::min_spec_version`.
+    pub since: SpecVersion,
+    /// Keywords added by a later version of the spec than the property, with that version.
+    pub keywords_since: Vec<(String, SpecVersion)>,
+}
+
+impl PropertySpec {
+    /// A property with no keywords, whose values lookups lowercase.
+    pub fn new(name: &str, value_type: ValueType) -> PropertySpec {
+        PropertySpec {
+            name: name.to_lowercase(),
+            value_type,
+            keywords: vec![],
+            lowercase: true,
+            since: SpecVersion(0, 0, 0),
+            keywords_since: vec![],
+        }
+    }
+
+    /// Allows `keywords` besides the values of the type.
+    pub fn keywords(mut self, keywords: &[&str]) -> PropertySpec {
+        self.keywords = keywords.iter().map(|keyword| keyword.to_lowercase()).collect();
+        self
+    }
+
+    /// Keeps the case of values in lookups, e.g. for paths or names.
+    pub fn case_sensitive(mut self) -> PropertySpec {
+        self.lowercase = false;
+        self
+    }
+
+    /// Marks the property as added by `version` of the spec.
+    pub fn since(mut self, version: SpecVersion) -> PropertySpec {
+        self.since = version;
+        self
+    }
+
+    /// Allows `keywords` too, marking them as added by `version` of the spec. It comes after
+    /// `keywords`, which replaces the keywords allowed.
+    pub fn keywords_since(mut self, version: SpecVersion, keywords: &[&str]) -> PropertySpec {
+        for keyword in keywords {
+            self.keywords.push(keyword.to_lowercase());
+            self.keywords_since.push((keyword.to_lowercase(), version));
+        }
+        self
+    }
+
+    /// The version of the spec adding `value` of the property, ignoring case: the one adding
+    /// the keyword if it's newer than the property, else `since`.
+    pub fn since_for(&self, value: &str) -> SpecVersion {
+        let value = value.to_lowercase();
+        self.keywords_since
+            .iter()
+            .filter(|(keyword, _)| *keyword == value)
+            .map(|&(_, version)| version)
+            .fold(self.since, SpecVersion::max)
+    }
+
+    /// Whether `value` is one the property allows, ignoring case. `unset` always is.
+    pub fn allows(&self, value: &str) -> bool {
+        let value = value.to_lowercase();
+        if value == "unset" || self.keywords.contains(&value) {
+            return true;
+        }
+        match self.value_type {
+            ValueType::Keyword => false,
+            ValueType::PositiveInteger => value.parse::<u32>().is_ok_and(|n| n > 0),
+            ValueType::Boolean => value == "true" || value == "false",
+            ValueType::Text => true,
+        }
+    }
+
+    /// Describes the values allowed, e.g. `a positive number or tab`.
+    pub fn expected(&self) -> String {
+        let mut alternatives = match self.value_type {
+            ValueType::Keyword | ValueType::Text => vec![],
+            ValueType::PositiveInteger => vec!["a positive number".to_string()],
+            ValueType::Boolean => vec!["true".to_string(), "false".to_string()],
+        };
+        alternatives.extend(self.keywords.iter().cloned());
+        match alternatives.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            None => "any value".to_string(),
+        }
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        match self.name.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == self.name,
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ValueType::Keyword => "keyword",
+            ValueType::PositiveInteger => "positive integer",
+            ValueType::Boolean => "boolean",
+            ValueType::Text => "text",
+        })
+    }
+}
+
+/// The known properties: lookups lowercase their values unless they're case sensitive, and
+/// checks report values they don't allow. See `LookupOptions::property_registry`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PropertyRegistry {
+    properties: OrderMap<String, PropertySpec>,
+}
+
+impl PropertyRegistry {
+    /// A registry knowing no property, so that values are kept as they're written.
+    pub fn new() -> PropertyRegistry {
+        PropertyRegistry { properties: OrderMap::new() }
+    }
+
+    /// The properties of the specification, `max_line_length` and `spelling_language`, whose
+    /// values lookups keep as written, and `root`.
+    pub fn standard() -> PropertyRegistry {
+        let mut registry = PropertyRegistry::new();
+        let specs = [PropertySpec::new("indent_style", ValueType::Keyword)
+                         .keywords(&["tab", "space"]),
+                     PropertySpec::new("indent_size", ValueType::PositiveInteger)
+                         .keywords(&["tab"]),
+                     PropertySpec::new("tab_width", ValueType::PositiveInteger),
+                     PropertySpec::new("end_of_line", ValueType::Keyword)
+                         .keywords(&["lf", "crlf", "cr"]),
+                     PropertySpec::new("charset", ValueType::Keyword)
+                         .keywords(&["latin1", "utf-8", "utf-8-bom", "utf-16be", "utf-16le"]),
+                     PropertySpec::new("trim_trailing_whitespace", ValueType::Boolean),
+                     PropertySpec::new("insert_final_newline", ValueType::Boolean),
+                     PropertySpec::new("max_line_length", ValueType::PositiveInteger)
+                         .keywords(&["off"])
+                         .case_sensitive(),
+                     PropertySpec::new("spelling_language", ValueType::Text)
+                         .case_sensitive()
+                         .since(SpecVersion(0, 16, 0)),
+                     PropertySpec::new("root", ValueType::Boolean)];
+        for spec in specs {
+            registry.register(spec);
+        }
+        registry
+    }
+
+    /// Adds `spec`, replacing the property of the same name.
+    pub fn register(&mut self, spec: PropertySpec) {
+        self.properties.insert(spec.name.clone(), spec);
+    }
+
+    /// The property `key` is, ignoring case: the one of that name, or else the one whose
+    /// name ending with `*` matches the longest part of it.
+    pub fn get(&self, key: &str) -> Option<&PropertySpec> {
+        let key = key.to_lowercase();
+        self.properties.get(&key).or_else(|| {
+            self.properties
+                .values()
+                .filter(|spec| spec.name.ends_with('*') && spec.matches(&key))
+                .max_by_key(|spec| spec.name.len())
+        })
+    }
+
+    /// The properties, in the order they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = &PropertySpec> {
+        self.properties.values()
+    }
+
+    /// Whether lookups lowercase the values of `key`.
+    pub fn lowercases(&self, key: &str) -> bool {
+        self.get(key).is_some_and(|spec| spec.lowercase)
+    }
+
+    /// Returns the values `key` allows if `value` isn't one of them, `None` if it is or if
+    /// the property isn't known.
+    pub fn check_value(&self, key: &str, value: &str) -> Option<String> {
+        self.get(key).filter(|spec| !spec.allows(value)).map(PropertySpec::expected)
+    }
+
+    /// The property `key` is likely a misspelling of, if it isn't known: the closest one by
+    /// edit distance, ignoring case, at most 2 edits away and fewer than a third of its
+    /// length, so that short keys don't all look alike.
+    ///
+    /// ```
+    /// use editorconfig::PropertyRegistry;
+    ///
+    /// let registry = PropertyRegistry::standard();
+    /// assert_eq!(registry.suggest("indent_stile"), Some("indent_style"));
+    /// assert_eq!(registry.suggest("indent_style"), None);
+    /// assert_eq!(registry.suggest("quote_type"), None);
+    /// ```
+    pub fn suggest(&self, key: &str) -> Option<&str> {
+        if self.get(key).is_some() {
+            return None;
+        }
+        let key = key.to_lowercase();
+        self.properties
+            .values()
+            .filter(|spec| !spec.name.ends_with('*'))
+            .map(|spec| (edit_distance(&key, &spec.name), spec.name.as_str()))
+            .filter(|&(distance, name)| distance <= 2 && distance * 3 < name.chars().count())
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, name)| name)
+    }
+}
+
+/// The number of characters to insert, delete or replace to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..b.len() + 1).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &b) in b.iter().enumerate() {
+            let replaced = previous[j] + if a == b { 0 } else { 1 };
+            current.push(replaced.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+impl Default for PropertyRegistry {
+    /// `PropertyRegistry::standard`.
+    fn default() -> PropertyRegistry {
+        PropertyRegistry::standard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_and_checks_standard_and_added_properties() {
+        let mut registry = PropertyRegistry::standard();
+        assert_eq!(registry.check_value("indent_style", "spaces").unwrap(), "tab or space");
+        assert_eq!(registry.check_value("Indent_Style", "Space"), None);
+        assert_eq!(registry.check_value("tab_width", "-4").unwrap(), "a positive number");
+        assert_eq!(registry.check_value("indent_size", "0").unwrap(),
+                   "a positive number or tab");
+        assert_eq!(registry.check_value("root", "yes").unwrap(), "true or false");
+        assert_eq!(registry.check_value("charset", "unset"), None);
+        assert_eq!(registry.check_value("quote_type", "anything"), None);
+        assert!(registry.lowercases("charset") && !registry.lowercases("max_line_length"));
+
+        registry.register(PropertySpec::new("ij_*", ValueType::Text).case_sensitive());
+        registry.register(PropertySpec::new("ij_continuation_indent_size",
+                                            ValueType::PositiveInteger));
+        registry.register(PropertySpec::new("csharp_prefer_braces", ValueType::Boolean)
+            .keywords(&["when_multiline"]));
+        assert_eq!(registry.get("ij_continuation_indent_size").unwrap().value_type,
+                   ValueType::PositiveInteger);
+        assert_eq!(registry.get("IJ_java_names").unwrap().name, "ij_*");
+        assert!(!registry.lowercases("ij_java_names"));
+        assert_eq!(registry.check_value("csharp_prefer_braces", "sometimes").unwrap(),
+                   "true, false or when_multiline");
+        assert_eq!(registry.iter().count(), 13);
+        assert!(PropertyRegistry::new().get("charset").is_none());
+    }
+
+    #[test]
+    fn knows_the_version_adding_properties_and_keywords() {
+        let registry = PropertyRegistry::standard();
+        assert_eq!(registry.get("charset").unwrap().since_for("utf-8"), SpecVersion(0, 0, 0));
+        assert_eq!(registry.get("spelling_language").unwrap().since_for("en-US"),
+                   SpecVersion(0, 16, 0));
+        let spec = PropertySpec::new("quote_type", ValueType::Keyword)
+            .since(SpecVersion(0, 12, 0))
+            .keywords(&["single", "double"])
+            .keywords_since(SpecVersion(0, 15, 0), &["Auto"]);
+        assert_eq!(spec.since_for("single"), SpecVersion(0, 12, 0));
+        assert_eq!(spec.since_for("AUTO"), SpecVersion(0, 15, 0));
+        assert_eq!(spec.expected(), "single, double or auto");
+    }
+
+    #[test]
+    fn suggests_the_closest_known_property() {
+        let mut registry = PropertyRegistry::standard();
+        assert_eq!(registry.suggest("insert_final_newlines"), Some("insert_final_newline"));
+        assert_eq!(registry.suggest("Indent_Stile"), Some("indent_style"));
+        assert_eq!(registry.suggest("tabwidth"), Some("tab_width"));
+        assert_eq!(registry.suggest("trim_trailing_whitespace"), None);
+        assert_eq!(registry.suggest("indent_sz"), Some("indent_size"));
+        assert_eq!(registry.suggest("rot"), Some("root"));
+        assert_eq!(registry.suggest("indent"), None);
+        assert_eq!(registry.suggest("ro"), None);
+        assert_eq!(registry.suggest("dotnet_sort_system_directives_first"), None);
+        registry.register(PropertySpec::new("ij_*", ValueType::Text));
+        registry.register(PropertySpec::new("quote_type", ValueType::Keyword));
+        assert_eq!(registry.suggest("quote_typ"), Some("quote_type"));
+        assert_eq!(registry.suggest("ij_java_imports"), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,108 @@
+//! Best-effort reading of malformed config files, for editors that would rather apply most
+//! settings with a warning than none at all.
+
+/// Something `repair` changed in a config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Repair {
+    /// The line that was changed, from 1.
+    pub line: usize,
+    /// What was assumed, e.g. ``section header `[*.rs` isn't closed, read as `[*.rs]` ``.
+    pub message: String,
+}
+
+/// Rewrites the lines of `contents` that would make parsing fail or silently read something
+/// else than intended, returning the repaired contents and what was changed:
+///
+/// - a section header without `]` is closed, before its comment if it has one,
+/// - a property without a key (`= 4`) is dropped,
+/// - a line that is neither a section header, a property nor a comment is dropped, instead
+///   of becoming part of the key of the next property.
+///
+/// Lines are never added or removed, so line numbers still refer to the original file.
+pub fn repair(contents: &str) -> (String, Vec<Repair>) {
+    let mut repaired = String::with_capacity(contents.len() + 1);
+    let mut repairs = vec![];
+    // The quote of a value spanning lines, whose next lines are left alone
+    let mut open_quote = None;
+    for (i, line) in contents.split_inclusive('\n').enumerate() {
+        let body = line.trim_end_matches(['\n', '\r']);
+        let ending = &line[body.len()..];
+        let trimmed = body.trim();
+        if let Some(quote) = open_quote {
+            if body.contains(quote) {
+                open_quote = None;
+            }
+            repaired.push_str(line);
+            continue;
+        }
+        let mut repair = |message: String, replacement: &str| {
+            repairs.push(Repair {
+                line: i + 1,
+                message,
+            });
+            repaired.push_str(replacement);
+            repaired.push_str(ending);
+        };
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            repaired.push_str(line);
+        } else if trimmed.starts_with('[') {
+            if trimmed.contains(']') {
+                repaired.push_str(line);
+            } else {
+                let (name, comment) = trimmed.split_at(trimmed.find(['#', ';'])
+                    .unwrap_or(trimmed.len()));
+                let name = name.trim_end();
+                let closed = if comment.is_empty() {
+                    format!("{}]", name)
+                } else {
+                    format!("{}] {}", name, comment)
+                };
+                repair(format!("section header `{}` isn't closed, read as `{}]`", name, name),
+                       &closed);
+            }
+        } else if trimmed.starts_with('=') || trimmed.starts_with(':') {
+            repair(format!("`{}` has no key, ignored", trimmed), "");
+        } else if let Some(separator) = trimmed.find(['=', ':']) {
+            let value = trimmed[separator + 1..].trim_start();
+            open_quote = value.chars()
+                .next()
+                .filter(|&c| c == '"' || c == '\'')
+                .filter(|&quote| !value[1..].contains(quote));
+            repaired.push_str(line);
+        } else {
+            repair(format!("`{}` has no `=` and no value, ignored", trimmed), "");
+        }
+    }
+    (repaired, repairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ini::Ini;
+
+    #[test]
+    fn repairs_unclosed_headers_and_stray_lines() {
+        let (repaired, repairs) = repair("root = true\r\n\
+                                          [*.rs # Rust\r\n\
+                                          indent_size = 4\r\n\
+                                          = 2\r\n\
+                                          tab_width\r\n\
+                                          charset = \"utf-8\n\
+                                          [not a header\"\n\
+                                          [*.md\n\
+                                          indent_size = 2");
+        assert_eq!(repaired,
+                   "root = true\r\n[*.rs] # Rust\r\nindent_size = 4\r\n\r\n\r\ncharset = \"utf-8\n\
+                    [not a header\"\n[*.md]\nindent_size = 2");
+        let lines: Vec<_> = repairs.iter().map(|repair| repair.line).collect();
+        assert_eq!(lines, [2, 4, 5, 8]);
+        assert_eq!(repairs[0].message, "section header `[*.rs` isn't closed, read as `[*.rs]`");
+        assert_eq!(repairs[1].message, "`= 2` has no key, ignored");
+
+        let ini = Ini::load_from_str(&repaired).unwrap();
+        assert_eq!(ini.get_from(Some("*.rs"), "indent_size"), Some("4"));
+        assert_eq!(ini.get_from(Some("*.md"), "indent_size"), Some("2"));
+        assert_eq!(repair("[*]\nkey = value\n"), ("[*]\nkey = value\n".to_string(), vec![]));
+    }
+}
@@ -0,0 +1,671 @@
+//! Rendering the diagnostics of checks, as text for people, JSON, SARIF, JUnit or rdjson
+//! for tools, or however a host wants them, e.g. in the problems panel of an editor.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use manifest::json_string;
+use {explain, group_diagnostics, CodeOwners, Diagnostic, DiagnosticGroup, GroupBy,
+     MessageCatalog, PathStyle, Severity, RULES};
+
+/// Receives the diagnostics of a check, one by one or aggregated, see `write_report`.
+pub trait Reporter {
+    /// A diagnostic, when they're reported one by one, in the order they were found.
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) -> io::Result<()>;
+
+    /// Diagnostics aggregated according to `by`, largest groups first.
+    fn group(&mut self, group: &DiagnosticGroup, by: GroupBy) -> io::Result<()>;
+
+    /// Called after the last diagnostic or group, e.g. to close a list. Does nothing by
+    /// default.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Gives `diagnostics` to `reporter`, one by one for `GroupBy::Ungrouped`, or else
+/// aggregated with `group_diagnostics`, then finishes the report.
+pub fn write_report(reporter: &mut dyn Reporter,
+                    diagnostics: &[Diagnostic],
+                    by: GroupBy)
+                    -> io::Result<()> {
+    if by == GroupBy::Ungrouped {
+        for diagnostic in diagnostics {
+            reporter.diagnostic(diagnostic)?;
+        }
+    } else {
+        for group in group_diagnostics(diagnostics, by) {
+            reporter.group(&group, by)?;
+        }
+    }
+    reporter.finish()
+}
+
+/// What the reporters share: where paths are shown from, how, and in which language.
+#[derive(Clone, Debug)]
+pub struct ReportOptions<'a> {
+    /// The directory relative paths are shown from.
+    cwd: &'a Path,
+    path_style: PathStyle,
+    /// The language of the messages.
+    catalog: MessageCatalog,
+}
+
+impl<'a> ReportOptions<'a> {
+    /// Shows paths relative to `cwd` as `PathStyle::default` does, in English.
+    pub fn new(cwd: &'a Path) -> ReportOptions<'a> {
+        ReportOptions {
+            cwd,
+            path_style: PathStyle::default(),
+            catalog: MessageCatalog::english(),
+        }
+    }
+
+    /// Shows paths as `path_style` says.
+    pub fn path_style(mut self, path_style: PathStyle) -> ReportOptions<'a> {
+        self.path_style = path_style;
+        self
+    }
+
+    /// Writes messages in the language of `catalog`.
+    pub fn catalog(mut self, catalog: MessageCatalog) -> ReportOptions<'a> {
+        self.catalog = catalog;
+        self
+    }
+
+    fn display(&self, path: &Path) -> String {
+        self.path_style.display(path, self.cwd)
+    }
+
+    fn message(&self, diagnostic: &Diagnostic) -> String {
+        diagnostic.message(&self.catalog)
+    }
+}
+
+/// Writes a line per diagnostic or group, like `check` and `lint` do by default.
+pub struct TextReporter<'a> {
+    out: &'a mut dyn Write,
+    options: ReportOptions<'a>,
+    /// Who to show as owner of each file, if anyone.
+    owners: Option<&'a CodeOwners>,
+}
+
+impl<'a> TextReporter<'a> {
+    /// Writes to `out` as `options` say.
+    pub fn new(out: &'a mut dyn Write, options: ReportOptions<'a>) -> TextReporter<'a> {
+        TextReporter {
+            out,
+            options,
+            owners: None,
+        }
+    }
+
+    /// Shows who owns each file according to `owners`.
+    pub fn owners(mut self, owners: &'a CodeOwners) -> TextReporter<'a> {
+        self.owners = Some(owners);
+        self
+    }
+
+    fn owners_suffix(&self, file: &Path) -> String {
+        match self.owners.map(|owners| owners.owners_of(&self.options.cwd.join(file))) {
+            None => String::new(),
+            Some(&[]) => " (no owner)".to_string(),
+            Some(owners) => format!(" (owned by {})", owners.join(" ")),
+        }
+    }
+}
+
+impl<'a> Reporter for TextReporter<'a> {
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) -> io::Result<()> {
+        let code = diagnostic.code().map(|code| format!("[{}] ", code)).unwrap_or_default();
+        let suffix = self.owners_suffix(&diagnostic.file);
+        writeln!(self.out,
+                 "{}: {}{}{}",
+                 self.options.display(&diagnostic.file),
+                 code,
+                 self.options.message(diagnostic),
+                 suffix)
+    }
+
+    fn group(&mut self, group: &DiagnosticGroup, by: GroupBy) -> io::Result<()> {
+        let violations = plural(group.count, "violation");
+        let files = plural(group.files, "file");
+        match (group.rule, group.path.as_ref()) {
+            (Some(rule), _) => {
+                writeln!(self.out,
+                         "{} {} {} across {} {}",
+                         group.count,
+                         rule,
+                         violations,
+                         group.files,
+                         files)
+            }
+            (None, Some(path)) if by == GroupBy::Dir => {
+                let dir = if path.as_os_str().is_empty() { Path::new(".") } else { path };
+                writeln!(self.out,
+                         "{} {} across {} {} in {}/",
+                         group.count,
+                         violations,
+                         group.files,
+                         files,
+                         self.options.display(dir))
+            }
+            (None, Some(path)) => {
+                let suffix = self.owners_suffix(path);
+                writeln!(self.out,
+                         "{}: {} {}{}",
+                         self.options.display(path),
+                         group.count,
+                         violations,
+                         suffix)
+            }
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Writes a JSON array with an object per diagnostic, holding its file, code, rule,
+/// severity, message and the arguments of the message, or per group, holding its rule or
+/// path and counts.
+pub struct JsonReporter<'a> {
+    out: &'a mut dyn Write,
+    options: ReportOptions<'a>,
+    /// Whether an object was written already.
+    started: bool,
+}
+
+impl<'a> JsonReporter<'a> {
+    /// Writes to `out` as `options` say.
+    pub fn new(out: &'a mut dyn Write, options: ReportOptions<'a>) -> JsonReporter<'a> {
+        JsonReporter {
+            out,
+            options,
+            started: false,
+        }
+    }
+
+    fn object(&mut self, fields: &[(&str, String)]) -> io::Result<()> {
+        let separator = if self.started { ",\n" } else { "[\n" };
+        self.started = true;
+        let fields: Vec<_> = fields.iter()
+            .map(|&(name, ref value)| format!("{}: {}", json_string(name), value))
+            .collect();
+        write!(self.out, "{}  {{{}}}", separator, fields.join(", "))
+    }
+
+    fn path(&self, path: &Path) -> String {
+        json_string(&self.options.display(path))
+    }
+}
+
+impl<'a> Reporter for JsonReporter<'a> {
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) -> io::Result<()> {
+        let args: Vec<_> = diagnostic.args
+            .iter()
+            .map(|&(name, ref value)| format!("{}: {}", json_string(name), json_string(value)))
+            .collect();
+        let code = diagnostic.code().map_or("null".to_string(), json_string);
+        let fields = [("file", self.path(&diagnostic.file)),
+                      ("code", code),
+                      ("rule", json_string(diagnostic.rule)),
+                      ("severity", json_string(&diagnostic.severity().to_string())),
+                      ("message", json_string(&self.options.message(diagnostic))),
+                      ("message_id", json_string(diagnostic.message_id)),
+                      ("args", format!("{{{}}}", args.join(", ")))];
+        self.object(&fields)
+    }
+
+    fn group(&mut self, group: &DiagnosticGroup, _by: GroupBy) -> io::Result<()> {
+        let mut fields = vec![];
+        if let Some(rule) = group.rule {
+            fields.push(("rule", json_string(rule)));
+        }
+        if let Some(ref path) = group.path {
+            fields.push(("path", self.path(path)));
+        }
+        fields.push(("count", group.count.to_string()));
+        fields.push(("files", group.files.to_string()));
+        self.object(&fields)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.started {
+            writeln!(self.out, "\n]")
+        } else {
+            writeln!(self.out, "[]")
+        }
+    }
+}
+
+/// Writes a SARIF 2.1.0 log, listing every rule and a result per diagnostic, located at
+/// its line when it has one. Groups are results too, about their rule or path.
+pub struct SarifReporter<'a> {
+    out: &'a mut dyn Write,
+    options: ReportOptions<'a>,
+    results: Vec<String>,
+}
+
+impl<'a> SarifReporter<'a> {
+    /// Writes to `out` as `options` say.
+    pub fn new(out: &'a mut dyn Write, options: ReportOptions<'a>) -> SarifReporter<'a> {
+        SarifReporter {
+            out,
+            options,
+            results: vec![],
+        }
+    }
+
+    fn result(&mut self,
+              rule: &str,
+              severity: Severity,
+              message: &str,
+              path: Option<&Path>,
+              line: Option<usize>) {
+        let mut result = format!("{{\"ruleId\": {}, \"level\": {}, \"message\": {{\"text\": {}}}",
+                                 json_string(rule),
+                                 json_string(sarif_level(severity)),
+                                 json_string(message));
+        if let Some(path) = path {
+            let region = line.map_or(String::new(),
+                                     |line| format!(", \"region\": {{\"startLine\": {}}}", line));
+            result.push_str(&format!(", \"locations\": [{{\"physicalLocation\": \
+                                      {{\"artifactLocation\": {{\"uri\": {}}}{}}}}}]",
+                                     json_string(&self.options.display(path)
+                                         .to_string()),
+                                     region));
+        }
+        result.push('}');
+        self.results.push(result);
+    }
+}
+
+impl<'a> Reporter for SarifReporter<'a> {
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) -> io::Result<()> {
+        let message = self.options.message(diagnostic);
+        self.result(diagnostic.code().unwrap_or(diagnostic.rule),
+                    diagnostic.severity(),
+                    &message,
+                    Some(&diagnostic.file),
+                    line_of(diagnostic));
+        Ok(())
+    }
+
+    fn group(&mut self, group: &DiagnosticGroup, _by: GroupBy) -> io::Result<()> {
+        let rule = group.rule.map(|rule| explain(rule).map_or(rule, |info| info.code));
+        let severity = group.rule.and_then(explain).map_or(Severity::Medium, |info| info.severity);
+        self.result(rule.unwrap_or("editorconfig"),
+                    severity,
+                    &group_summary(group),
+                    group.path.as_deref(),
+                    None);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let rules: Vec<_> = RULES.iter()
+            .map(|rule| {
+                format!("{{\"id\": {}, \"name\": {}, \"shortDescription\": {{\"text\": {}}}, \
+                         \"defaultConfiguration\": {{\"level\": {}}}}}",
+                        json_string(rule.code),
+                        json_string(rule.name),
+                        json_string(&summary_of(rule.explanation)),
+                        json_string(sarif_level(rule.severity)))
+            })
+            .collect();
+        writeln!(self.out, "{{")?;
+        writeln!(self.out, "  \"version\": \"2.1.0\",")?;
+        writeln!(self.out,
+                 "  \"$schema\": \"https://json.schemastore.org/sarif-2.1.0.json\",")?;
+        writeln!(self.out, "  \"runs\": [{{")?;
+        writeln!(self.out,
+                 "    \"tool\": {{\"driver\": {{\"name\": \"editorconfig\", \"version\": {}, \
+                  \"rules\": [",
+                 json_string(env!("CARGO_PKG_VERSION")))?;
+        writeln!(self.out, "      {}", rules.join(",\n      "))?;
+        writeln!(self.out, "    ]}}}},")?;
+        if self.results.is_empty() {
+            writeln!(self.out, "    \"results\": []")?;
+        } else {
+            writeln!(self.out, "    \"results\": [")?;
+            writeln!(self.out, "      {}", self.results.join(",\n      "))?;
+            writeln!(self.out, "    ]")?;
+        }
+        writeln!(self.out, "  }}]")?;
+        writeln!(self.out, "}}")
+    }
+}
+
+/// Writes a JUnit XML report with a failed test case per diagnostic, named after its rule
+/// and classed under its file, or per group.
+pub struct JunitReporter<'a> {
+    out: &'a mut dyn Write,
+    options: ReportOptions<'a>,
+    test_cases: Vec<String>,
+}
+
+impl<'a> JunitReporter<'a> {
+    /// Writes to `out` as `options` say.
+    pub fn new(out: &'a mut dyn Write, options: ReportOptions<'a>) -> JunitReporter<'a> {
+        JunitReporter {
+            out,
+            options,
+            test_cases: vec![],
+        }
+    }
+
+    fn test_case(&mut self, class_name: &str, name: &str, failure_type: &str, message: &str) {
+        self.test_cases.push(format!("<testcase classname=\"{}\" name=\"{}\">\
+                                      <failure type=\"{}\" message=\"{}\"/></testcase>",
+                                     xml_escape(class_name),
+                                     xml_escape(name),
+                                     xml_escape(failure_type),
+                                     xml_escape(message)));
+    }
+}
+
+impl<'a> Reporter for JunitReporter<'a> {
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) -> io::Result<()> {
+        let file = self.options.display(&diagnostic.file);
+        let name = match diagnostic.code() {
+            Some(code) => format!("{} {}", code, diagnostic.rule),
+            None => diagnostic.rule.to_string(),
+        };
+        let message = self.options.message(diagnostic);
+        self.test_case(&file, &name, diagnostic.rule, &message);
+        Ok(())
+    }
+
+    fn group(&mut self, group: &DiagnosticGroup, _by: GroupBy) -> io::Result<()> {
+        let path = group.path
+            .as_ref()
+            .map(|path| self.options.display(path));
+        let name = group.rule.map(str::to_string).or(path).unwrap_or_default();
+        self.test_case("editorconfig", &name, group.rule.unwrap_or("violations"),
+                       &group_summary(group));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let count = self.test_cases.len();
+        writeln!(self.out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(self.out,
+                 "<testsuites name=\"editorconfig\" tests=\"{}\" failures=\"{}\">",
+                 count,
+                 count)?;
+        writeln!(self.out,
+                 "  <testsuite name=\"editorconfig\" tests=\"{}\" failures=\"{}\">",
+                 count,
+                 count)?;
+        for test_case in &self.test_cases {
+            writeln!(self.out, "    {}", test_case)?;
+        }
+        writeln!(self.out, "  </testsuite>")?;
+        writeln!(self.out, "</testsuites>")
+    }
+}
+
+/// Writes the Reviewdog Diagnostic Format, with a diagnostic per diagnostic or group,
+/// located at its line when it has one.
+pub struct RdjsonReporter<'a> {
+    out: &'a mut dyn Write,
+    options: ReportOptions<'a>,
+    diagnostics: Vec<String>,
+}
+
+impl<'a> RdjsonReporter<'a> {
+    /// Writes to `out` as `options` say.
+    pub fn new(out: &'a mut dyn Write, options: ReportOptions<'a>) -> RdjsonReporter<'a> {
+        RdjsonReporter {
+            out,
+            options,
+            diagnostics: vec![],
+        }
+    }
+
+    fn push(&mut self,
+            message: &str,
+            path: Option<&Path>,
+            line: Option<usize>,
+            severity: Severity,
+            code: Option<&str>) {
+        let mut fields = vec![format!("\"message\": {}", json_string(message))];
+        if let Some(path) = path {
+            let range = line.map_or(String::new(), |line| {
+                format!(", \"range\": {{\"start\": {{\"line\": {}}}}}", line)
+            });
+            fields.push(format!("\"location\": {{\"path\": {}{}}}",
+                                json_string(&self.options.display(path)),
+                                range));
+        }
+        let severity = match severity {
+            Severity::High => "ERROR",
+            Severity::Medium => "WARNING",
+            Severity::Low => "INFO",
+        };
+        fields.push(format!("\"severity\": {}", json_string(severity)));
+        if let Some(code) = code {
+            fields.push(format!("\"code\": {{\"value\": {}}}", json_string(code)));
+        }
+        self.diagnostics.push(format!("{{{}}}", fields.join(", ")));
+    }
+}
+
+impl<'a> Reporter for RdjsonReporter<'a> {
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) -> io::Result<()> {
+        let message = self.options.message(diagnostic);
+        self.push(&message,
+                  Some(&diagnostic.file),
+                  line_of(diagnostic),
+                  diagnostic.severity(),
+                  Some(diagnostic.code().unwrap_or(diagnostic.rule)));
+        Ok(())
+    }
+
+    fn group(&mut self, group: &DiagnosticGroup, _by: GroupBy) -> io::Result<()> {
+        let rule = group.rule.and_then(explain);
+        self.push(&group_summary(group),
+                  group.path.as_deref(),
+                  None,
+                  rule.map_or(Severity::Medium, |info| info.severity),
+                  rule.map(|info| info.code).or(group.rule));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        writeln!(self.out, "{{")?;
+        writeln!(self.out, "  \"source\": {{\"name\": \"editorconfig\"}},")?;
+        if self.diagnostics.is_empty() {
+            writeln!(self.out, "  \"diagnostics\": []")?;
+        } else {
+            writeln!(self.out, "  \"diagnostics\": [")?;
+            writeln!(self.out, "    {}", self.diagnostics.join(",\n    "))?;
+            writeln!(self.out, "  ]")?;
+        }
+        writeln!(self.out, "}}")
+    }
+}
+
+/// The line a diagnostic is about, from its `line` argument.
+fn line_of(diagnostic: &Diagnostic) -> Option<usize> {
+    diagnostic.args
+        .iter()
+        .find(|&&(name, _)| name == "line")
+        .and_then(|(_, line)| line.parse().ok())
+}
+
+/// The first paragraph of an explanation, on one line.
+fn summary_of(explanation: &str) -> String {
+    explanation.lines().take_while(|line| !line.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+/// E.g. `3 violations across 2 files`.
+fn group_summary(group: &DiagnosticGroup) -> String {
+    format!("{} {} across {} {}",
+            group.count,
+            plural(group.count, "violation"),
+            group.files,
+            plural(group.files, "file"))
+}
+
+/// `noun` for a count of 1, its plural otherwise.
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 { noun.to_string() } else { format!("{}s", noun) }
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "note",
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Collects what it's given, like an editor would to fill its problems panel.
+    #[derive(Default)]
+    struct Collecting {
+        rules: Vec<&'static str>,
+        groups: Vec<usize>,
+        finished: bool,
+    }
+
+    impl Reporter for Collecting {
+        fn diagnostic(&mut self, diagnostic: &Diagnostic) -> io::Result<()> {
+            self.rules.push(diagnostic.rule);
+            Ok(())
+        }
+
+        fn group(&mut self, group: &DiagnosticGroup, _by: GroupBy) -> io::Result<()> {
+            self.groups.push(group.count);
+            Ok(())
+        }
+
+        fn finish(&mut self) -> io::Result<()> {
+            self.finished = true;
+            Ok(())
+        }
+    }
+
+    fn diagnostic(file: &str, rule: &'static str) -> Diagnostic {
+        Diagnostic {
+            file: PathBuf::from(file),
+            rule,
+            message_id: "broken-config.unusable",
+            args: vec![("error", "line 2: \"=\"".to_string())],
+        }
+    }
+
+    #[test]
+    fn renders_diagnostics_one_by_one_or_grouped() {
+        let diagnostics = [diagnostic("a/.editorconfig", "broken-config"),
+                           diagnostic("b/.editorconfig", "broken-config"),
+                           diagnostic("b/.editorconfig", "custom")];
+        let mut collecting = Collecting::default();
+        write_report(&mut collecting, &diagnostics, GroupBy::Ungrouped).unwrap();
+        assert_eq!(collecting.rules, ["broken-config", "broken-config", "custom"]);
+        write_report(&mut collecting, &diagnostics, GroupBy::Rule).unwrap();
+        assert_eq!(collecting.groups, [2, 1]);
+        assert!(collecting.finished);
+
+        let options = ReportOptions::new(Path::new("/repo"));
+        let mut out = vec![];
+        let mut reporter = TextReporter::new(&mut out, options.clone());
+        write_report(&mut reporter, &diagnostics[..1], GroupBy::Ungrouped).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "a/.editorconfig: [EC203] can't be used: line 2: \"=\"\n");
+
+        let mut out = vec![];
+        let mut reporter = JsonReporter::new(&mut out, options.clone());
+        write_report(&mut reporter, &diagnostics[2..], GroupBy::Ungrouped).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "[\n  {\"file\": \"b/.editorconfig\", \"code\": null, \"rule\": \"custom\", \
+                    \"severity\": \"medium\", \"message\": \"can't be used: line 2: \\\"=\\\"\", \
+                    \"message_id\": \"broken-config.unusable\", \
+                    \"args\": {\"error\": \"line 2: \\\"=\\\"\"}}\n]\n");
+        let mut out = vec![];
+        let mut reporter = JsonReporter::new(&mut out, options.clone());
+        write_report(&mut reporter, &diagnostics, GroupBy::File).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "[\n  {\"path\": \"b/.editorconfig\", \"count\": 2, \"files\": 1},\n  \
+                    {\"path\": \"a/.editorconfig\", \"count\": 1, \"files\": 1}\n]\n");
+        let mut out = vec![];
+        let mut reporter = JsonReporter::new(&mut out, options.clone());
+        write_report(&mut reporter, &[], GroupBy::Ungrouped).unwrap();
+        assert_eq!(out, b"[]\n");
+    }
+
+    #[test]
+    fn renders_diagnostics_for_ci_tools() {
+        let invalid = Diagnostic {
+            file: PathBuf::from("a/.editorconfig"),
+            rule: "invalid-value",
+            message_id: "invalid-value.not-allowed",
+            args: vec![("key", "indent_style".to_string()),
+                       ("value", "<tab>".to_string()),
+                       ("line", "2".to_string()),
+                       ("expected", "tab or space".to_string())],
+        };
+        let diagnostics = [invalid, diagnostic("b/.editorconfig", "broken-config")];
+        let options = ReportOptions::new(Path::new("/repo"));
+
+        let mut out = vec![];
+        let mut reporter = SarifReporter::new(&mut out, options.clone());
+        write_report(&mut reporter, &diagnostics, GroupBy::Ungrouped).unwrap();
+        let sarif = String::from_utf8(out).unwrap();
+        assert!(sarif.starts_with("{\n  \"version\": \"2.1.0\",\n"));
+        assert!(sarif.contains("{\"id\": \"EC206\", \"name\": \"invalid-value\", "));
+        assert!(sarif.contains("\n      {\"ruleId\": \"EC206\", \"level\": \"error\", \"message\": \
+                                {\"text\": \"indent_style = <tab> on line 2 isn't allowed, \
+                                expected tab or space\"}, \"locations\": [{\"physicalLocation\": \
+                                {\"artifactLocation\": {\"uri\": \"a/.editorconfig\"}, \
+                                \"region\": {\"startLine\": 2}}}]},\n"));
+        assert!(sarif.contains("{\"ruleId\": \"EC203\", \"level\": \"error\""));
+
+        let mut out = vec![];
+        let mut reporter = JunitReporter::new(&mut out, options.clone());
+        write_report(&mut reporter, &diagnostics[..1], GroupBy::Ungrouped).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                    <testsuites name=\"editorconfig\" tests=\"1\" failures=\"1\">\n  \
+                    <testsuite name=\"editorconfig\" tests=\"1\" failures=\"1\">\n    \
+                    <testcase classname=\"a/.editorconfig\" name=\"EC206 invalid-value\">\
+                    <failure type=\"invalid-value\" message=\"indent_style = &lt;tab&gt; on line \
+                    2 isn&apos;t allowed, expected tab or space\"/></testcase>\n  \
+                    </testsuite>\n</testsuites>\n");
+
+        let mut out = vec![];
+        let mut reporter = RdjsonReporter::new(&mut out, options.clone());
+        write_report(&mut reporter, &diagnostics, GroupBy::Rule).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "{\n  \"source\": {\"name\": \"editorconfig\"},\n  \"diagnostics\": [\n    \
+                    {\"message\": \"1 violation across 1 file\", \"severity\": \"ERROR\", \
+                    \"code\": {\"value\": \"EC206\"}},\n    \
+                    {\"message\": \"1 violation across 1 file\", \"severity\": \"ERROR\", \
+                    \"code\": {\"value\": \"EC203\"}}\n  ]\n}\n");
+        let mut out = vec![];
+        let mut reporter = RdjsonReporter::new(&mut out, options.clone());
+        write_report(&mut reporter, &diagnostics[..1], GroupBy::Ungrouped).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("\"location\": {\"path\": \
+                                                          \"a/.editorconfig\", \"range\": \
+                                                          {\"start\": {\"line\": 2}}}"));
+    }
+}
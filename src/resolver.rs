@@ -0,0 +1,522 @@
+//! A reusable lookup of the properties of files, configured once with a builder.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ordermap::OrderMap;
+
+use cache::ConfigCache;
+use provenance::explain_cached;
+use {has_config_cached, resolve_cached, EnvOverrides, Error, Explanation, InlineComments,
+     LookupOptions, OverlayFiles, PreprocessingProfile, Properties, PropertySpec, Warning};
+
+/// Looks up the properties of files with the same options every time.
+///
+/// Config files are parsed once and kept for the next lookups, which parse them again when
+/// their modification time or size changed. Directories without config files are skipped
+/// until their own stamp changes. The properties of each file are kept too, and looked up
+/// again once the stamp of a config file read for them or of one of their directories
+/// changes. Call `invalidate` for changes these don't show, and for files whose
+/// `FileProvider` doesn't stamp them: it only drops the properties of the files the config
+/// file applies to, e.g. when a `Watcher` reports it changed. Overlays take effect right
+/// away.
+///
+/// ```
+/// use std::path::Path;
+///
+/// let resolver = editorconfig::Resolver::builder()
+///     .conf_filename(".editorconfig")
+///     .case_sensitive(false)
+///     .build();
+/// let path = Path::new("./test_files/simple/file.txt").canonicalize().unwrap();
+/// let properties = resolver.resolve(&path).unwrap();
+/// assert!(!properties.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Resolver {
+    lookup: LookupOptions,
+    /// Where `lookup` reads files from, shadowing the files of the options it was built with.
+    overlay: OverlayFiles,
+    /// The config files parsed so far, shared by clones.
+    cache: Arc<ConfigCache>,
+}
+
+impl Resolver {
+    /// A builder starting from the default `LookupOptions`.
+    pub fn builder() -> ResolverBuilder {
+        ResolverBuilder { lookup: LookupOptions::default() }
+    }
+
+    /// Reads `contents` instead of the config file at `conf_file` from now on, e.g. the
+    /// unsaved buffer of an editor, so lookups show the effect of edits before they're
+    /// saved. The file doesn't have to exist. Clones of the resolver share overlays.
+    pub fn set_overlay<P: AsRef<Path>>(&self, conf_file: P, contents: &str) {
+        let path = self.overlay_path(conf_file.as_ref());
+        self.cache.remove(&path);
+        self.overlay.insert(path, contents);
+    }
+
+    /// Reads the config file at `conf_file` again, returning the overlay it had.
+    pub fn remove_overlay<P: AsRef<Path>>(&self, conf_file: P) -> Option<String> {
+        let path = self.overlay_path(conf_file.as_ref());
+        self.cache.remove(&path);
+        self.overlay.remove(&path)
+    }
+
+    /// Reads every config file again.
+    pub fn clear_overlays(&self) {
+        self.overlay.clear();
+        self.cache.clear();
+    }
+
+    /// Parses the config file at `conf_file` again on the next lookup, e.g. after it changed
+    /// on disk, and looks up again the files it was read for or which are below its
+    /// directory. The properties of other files are kept. Returns whether it had been parsed.
+    pub fn invalidate<P: AsRef<Path>>(&self, conf_file: P) -> bool {
+        self.cache.remove(&self.overlay_path(conf_file.as_ref()))
+    }
+
+    /// Parses every config file again on the next lookups.
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    /// The config files parsed and kept for the next lookups, sorted.
+    pub fn cached_config_files(&self) -> Vec<PathBuf> {
+        self.cache.paths()
+    }
+
+    /// The files whose properties are kept for the next lookups, sorted.
+    pub fn cached_files(&self) -> Vec<PathBuf> {
+        self.cache.resolved_paths()
+    }
+
+    /// `conf_file` as lookups find it when they canonicalize, below the canonical path of its
+    /// closest existing directory.
+    fn overlay_path(&self, conf_file: &Path) -> PathBuf {
+        if !self.lookup.canonicalize {
+            return conf_file.to_path_buf();
+        }
+        for dir in conf_file.ancestors().skip(1) {
+            if let Ok(canonical) = self.lookup.files.canonicalize(dir) {
+                return canonical.join(conf_file.strip_prefix(dir).unwrap());
+            }
+        }
+        conf_file.to_path_buf()
+    }
+
+    /// The options every lookup uses.
+    pub fn lookup_options(&self) -> &LookupOptions {
+        &self.lookup
+    }
+
+    /// Returns the properties of `path`, like `get_config_with`, dropping warnings.
+    pub fn resolve(&self, path: &Path) -> Result<OrderMap<String, String>, Error> {
+        self.resolve_with(path, &mut vec![])
+    }
+
+    /// Returns the properties of `path`, appending problems which didn't stop the lookup
+    /// to `warnings`.
+    pub fn resolve_with(&self,
+                        path: &Path,
+                        warnings: &mut Vec<Warning>)
+                        -> Result<OrderMap<String, String>, Error> {
+        self.resolve_properties(path, warnings).map(Properties::into_map)
+    }
+
+    /// Returns the properties of `path` like `resolve_with`, along with the config files
+    /// they come from.
+    pub fn resolve_properties(&self,
+                              path: &Path,
+                              warnings: &mut Vec<Warning>)
+                              -> Result<Properties, Error> {
+        resolve_cached(path, &self.lookup, warnings, &self.cache)
+    }
+
+    /// Explains how the properties of `path` were found, like `explain_lookup`: the config
+    /// files read, the sections which matched or didn't, and the values each property was
+    /// given, dropping warnings.
+    pub fn explain(&self, path: &Path) -> Result<Explanation, Error> {
+        explain_cached(path, &self.lookup, &mut vec![], &self.cache)
+    }
+
+    /// Whether a config file would be read for `path`, found without parsing any, e.g. for
+    /// an editor to decide whether to enable EditorConfig for a workspace at all. Stops at
+    /// the first config file found, and like lookups doesn't look above the stop directory
+    /// or the home directory with `stop_at_home`, and skips untrusted config files. Fallback
+    /// configs like `global_config` don't count.
+    ///
+    /// Directories found without config files are remembered like for lookups, so asking
+    /// again for paths below them only checks the directories not seen yet.
+    pub fn has_config(&self, path: &Path) -> bool {
+        has_config_cached(path, &self.lookup, &self.cache)
+    }
+}
+
+/// Sets the options of a `Resolver`, see `LookupOptions` for what each one does.
+#[derive(Clone, Debug)]
+pub struct ResolverBuilder {
+    lookup: LookupOptions,
+}
+
+impl ResolverBuilder {
+    /// Starts from `lookup` instead of the default options, for the ones without a setter.
+    pub fn lookup_options(mut self, lookup: LookupOptions) -> ResolverBuilder {
+        self.lookup = lookup;
+        self
+    }
+
+    /// Name of the config files to look for, `.editorconfig` by default.
+    pub fn conf_filename(mut self, name: &str) -> ResolverBuilder {
+        self.lookup.conffile = name.to_string();
+        self
+    }
+
+    /// Names of files read after the config file of each directory and overriding it, e.g.
+    /// `.editorconfig.user`, see `LookupOptions::conffile_overrides`. None by default.
+    pub fn conf_overrides(mut self, names: &[&str]) -> ResolverBuilder {
+        self.lookup.conffile_overrides = names.iter().map(|name| name.to_string()).collect();
+        self
+    }
+
+    /// Don't read config files above `dir`.
+    pub fn stop_dir<P: Into<PathBuf>>(mut self, dir: P) -> ResolverBuilder {
+        self.lookup.stop_dir = Some(dir.into());
+        self
+    }
+
+    /// Don't read config files above the home directory of the user, `false` by default.
+    pub fn stop_at_home(mut self, stop_at_home: bool) -> ResolverBuilder {
+        self.lookup.stop_at_home = stop_at_home;
+        self
+    }
+
+    /// Takes `dir` as the home directory of the user instead of reading it from the
+    /// environment.
+    pub fn home_dir<P: Into<PathBuf>>(mut self, dir: P) -> ResolverBuilder {
+        self.lookup.home_dir = Some(dir.into());
+        self
+    }
+
+    /// Knows the values of `spec` too: lookups lowercase them unless it's case sensitive,
+    /// and `lint` reports those it doesn't allow.
+    pub fn property(mut self, spec: PropertySpec) -> ResolverBuilder {
+        self.lookup.property_registry.register(spec);
+        self
+    }
+
+    /// Which properties are filled in from the others, like the reference cores by default.
+    pub fn preprocessing(mut self, profile: PreprocessingProfile) -> ResolverBuilder {
+        self.lookup.preprocessing = profile;
+        self
+    }
+
+    /// Whether `;` and `#` after a value start a comment, whatever `spec_version` says.
+    pub fn inline_comments(mut self, inline_comments: InlineComments) -> ResolverBuilder {
+        self.lookup.inline_comments = Some(inline_comments);
+        self
+    }
+
+    /// A config file giving the properties no other config file sets, see
+    /// `LookupOptions::global_config`.
+    pub fn global_config<P: Into<PathBuf>>(mut self, path: P) -> ResolverBuilder {
+        self.lookup.global_config = Some(path.into());
+        self
+    }
+
+    /// Also read the config file of the user after all the others, see
+    /// `LookupOptions::user_config`, `false` by default.
+    pub fn user_config(mut self, user_config: bool) -> ResolverBuilder {
+        self.lookup.user_config = user_config;
+        self
+    }
+
+    /// Applies the environment variables overriding the config files to read, see
+    /// `EnvOverrides`, on top of the options set so far.
+    pub fn env_overrides(mut self) -> ResolverBuilder {
+        EnvOverrides::from_env().apply(&mut self.lookup);
+        self
+    }
+
+    /// The version of the EditorConfig core to emulate, e.g. `0.8.0`.
+    pub fn spec_version(mut self, version: &str) -> ResolverBuilder {
+        self.lookup.spec_version = Some(version.to_string());
+        self
+    }
+
+    /// Whether section patterns match paths case sensitively, `true` by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> ResolverBuilder {
+        self.lookup.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// The length limits of keys and values, past which properties are ignored with a
+    /// warning. The spec's `MAX_KEY_LENGTH` and `MAX_VALUE_LENGTH` by default.
+    pub fn max_lengths(mut self, key: usize, value: usize) -> ResolverBuilder {
+        self.lookup.max_key_length = key;
+        self.lookup.max_value_length = value;
+        self
+    }
+
+    /// The length limit of section names, past which sections are ignored with a
+    /// warning. The spec's `MAX_SECTION_NAME_LENGTH` by default.
+    pub fn max_section_name_length(mut self, length: usize) -> ResolverBuilder {
+        self.lookup.max_section_name_length = length;
+        self
+    }
+
+    /// Whether looked up paths are canonicalized first, `true` by default.
+    pub fn canonicalize(mut self, canonicalize: bool) -> ResolverBuilder {
+        self.lookup.canonicalize = canonicalize;
+        self
+    }
+
+    pub fn build(self) -> Resolver {
+        let mut lookup = self.lookup;
+        let overlay = OverlayFiles::new(lookup.files.clone());
+        lookup.files = Arc::new(overlay.clone());
+        Resolver {
+            lookup,
+            overlay,
+            cache: Arc::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use test_support::FixtureBuilder;
+    use {ValueType, WarningKind};
+
+    #[test]
+    fn resolves_with_the_options_built() {
+        let path = Path::new("./test_files/overrides/foo/file.txt").canonicalize().unwrap();
+        let all = Resolver::builder().build().resolve(&path).unwrap();
+        assert_eq!(all.get("end_of_line").unwrap(), "lf");
+        let resolver = Resolver::builder()
+            .stop_dir("./test_files/overrides/foo")
+            .spec_version("0.8.0")
+            .build();
+        let stopped = resolver.resolve(&path).unwrap();
+        assert_eq!(stopped.get("end_of_line"), None);
+        assert_eq!(stopped.get("indent_style").unwrap(), "space");
+        assert_eq!(stopped.get("indent_size").unwrap(), "4");
+
+        let path = env::current_dir().unwrap().join("test_files/simple/MAKEFILE");
+        let sensitive = Resolver::builder().build();
+        let insensitive = Resolver::builder().case_sensitive(false).canonicalize(false).build();
+        assert_eq!(sensitive.resolve(&path).unwrap().get("indent_style"), None);
+        assert_eq!(insensitive.resolve(&path).unwrap().get("indent_style").unwrap(), "tab");
+    }
+
+    #[test]
+    fn reads_the_override_files_set() {
+        let path = Path::new("./test_files/overrides/foo/file.txt").canonicalize().unwrap();
+        let resolver = Resolver::builder().conf_overrides(&[".editorconfig.user"]).build();
+        let properties = resolver.resolve(&path).unwrap();
+        assert_eq!(resolver.lookup_options().conffile_overrides, [".editorconfig.user"]);
+        assert_eq!(properties.get("indent_style").unwrap(), "tab");
+        assert_eq!(properties.get("charset").unwrap(), "latin1");
+        // The override file of the parent directory says `root = true`
+        assert_eq!(properties.get("end_of_line").unwrap(), "crlf");
+        assert_eq!(properties.get("insert_final_newline").unwrap(), "true");
+    }
+
+    #[test]
+    fn warns_about_properties_past_the_length_limits() {
+        let key = "dotnet_style_prefer_conditional_expression_over_assignment_always";
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig",
+                    &format!("root = true\n[*.cs]\n{} = none\nindent_style = space\n\
+                              [{}]\ncharset = utf-8\n",
+                             key,
+                             "a".repeat(20)))
+            .file("x.cs")
+            .build()
+            .unwrap();
+        let strict = Resolver::builder().lookup_options(fixture.lookup_options()).build();
+        let mut warnings = vec![];
+        let properties = strict.resolve_with(&fixture.path("x.cs"), &mut warnings).unwrap();
+        assert_eq!(properties.get(key), None);
+        assert_eq!(properties.get("indent_style").unwrap(), "space");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::TooLong);
+        assert_eq!(warnings[0].message,
+                   format!("ignored `{}`, keys are limited to 50 characters", key));
+
+        let relaxed = Resolver::builder()
+            .lookup_options(fixture.lookup_options())
+            .max_lengths(100, 4)
+            .max_section_name_length(10)
+            .build();
+        let mut warnings = vec![];
+        let properties = relaxed.resolve_with(&fixture.path("x.cs"), &mut warnings).unwrap();
+        assert_eq!(properties.get(key).unwrap(), "none");
+        assert_eq!(properties.get("indent_style"), None);
+        let messages: Vec<_> = warnings.iter().map(|warning| warning.message.as_str()).collect();
+        assert_eq!(messages,
+                   ["ignored a section whose name is longer than 10 characters",
+                    "ignored the value of `indent_style`, values are limited to 4 characters"]);
+    }
+
+    #[test]
+    fn stops_at_the_home_directory_when_asked() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig", "[*]\ncharset = latin1\n")
+            .config("home/.editorconfig", "[*]\nindent_size = 2\n")
+            .file("home/project/x.rs")
+            .file("elsewhere/y.rs")
+            .build()
+            .unwrap();
+        let path = fixture.path("home/project/x.rs");
+        let builder = Resolver::builder().stop_dir(fixture.root()).home_dir(fixture.path("home"));
+        let properties = builder.clone().build().resolve(&path).unwrap();
+        assert_eq!(properties.get("charset").unwrap(), "latin1");
+
+        let resolver = builder.stop_at_home(true).build();
+        let properties = resolver.resolve(&path).unwrap();
+        assert_eq!(properties.get("indent_size").unwrap(), "2");
+        assert_eq!(properties.get("charset"), None);
+        let properties = resolver.resolve(&fixture.path("elsewhere/y.rs")).unwrap();
+        assert_eq!(properties.get("charset").unwrap(), "latin1");
+    }
+
+    #[test]
+    fn parses_config_files_again_when_they_change() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig", "root = true\n[*]\nindent_size = 2\n")
+            .file("a/x.rs")
+            .file("a/y.rs")
+            .build()
+            .unwrap();
+        let resolver = Resolver::builder().build();
+        let conf_file = fixture.path(".editorconfig");
+        assert_eq!(resolver.resolve(&fixture.path("a/x.rs")).unwrap()["indent_size"], "2");
+        assert_eq!(resolver.cached_config_files(), vec![conf_file.clone()]);
+
+        // Changes the size, in case the modification time doesn't
+        fs::write(&conf_file, "root = true\n[*]\nindent_size = 4\ntab_width = 8\n").unwrap();
+        let properties = resolver.clone().resolve(&fixture.path("a/y.rs")).unwrap();
+        assert_eq!(properties["indent_size"], "4");
+        assert!(resolver.invalidate(&conf_file));
+        assert!(!resolver.invalidate(&conf_file));
+        assert!(resolver.cached_config_files().is_empty());
+        assert_eq!(resolver.resolve(&fixture.path("a/x.rs")).unwrap()["tab_width"], "8");
+        resolver.clear_cache();
+        assert!(resolver.cached_config_files().is_empty());
+    }
+
+    #[test]
+    fn looks_up_again_only_the_files_a_changed_config_file_affects() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig", "root = true\n[*]\nindent_size = 2\n")
+            .config("a/.editorconfig", "[*]\ncharset = utf-8\n")
+            .file("a/x.rs")
+            .file("b/y.rs")
+            .build()
+            .unwrap();
+        let resolver = Resolver::builder().lookup_options(fixture.lookup_options()).build();
+        let (x, y) = (fixture.path("a/x.rs"), fixture.path("b/y.rs"));
+        assert_eq!(resolver.resolve(&x).unwrap()["charset"], "utf-8");
+        assert_eq!(resolver.resolve(&y).unwrap()["indent_size"], "2");
+        assert_eq!(resolver.cached_files(), [x.clone(), y.clone()]);
+
+        let conf_file = fixture.path("a/.editorconfig");
+        fs::write(&conf_file, "[*]\ncharset = latin1\n").unwrap();
+        assert!(resolver.invalidate(&conf_file));
+        assert_eq!(resolver.cached_files(), vec![y.clone()]);
+        assert_eq!(resolver.resolve(&x).unwrap()["charset"], "latin1");
+        // A config file that doesn't exist yet applies to the files below it
+        assert!(!resolver.invalidate(fixture.path("b/.editorconfig")));
+        assert_eq!(resolver.cached_files(), vec![x.clone()]);
+        // The root config file applies to both
+        resolver.resolve(&y).unwrap();
+        resolver.invalidate(fixture.path(".editorconfig"));
+        assert!(resolver.cached_files().is_empty());
+
+        // Without invalidating, changed stamps are noticed
+        resolver.resolve(&x).unwrap();
+        fs::write(&conf_file, "[*]\ncharset = utf-8\nindent_style = tab\n").unwrap();
+        assert_eq!(resolver.resolve(&x).unwrap()["indent_style"], "tab");
+        fs::write(fixture.path("b/.editorconfig"), "[*]\nindent_size = 8\n").unwrap();
+        assert_eq!(resolver.resolve(&y).unwrap()["indent_size"], "8");
+    }
+
+    #[test]
+    fn resolves_unsaved_config_files() {
+        let path = Path::new("./test_files/simple/file.txt").canonicalize().unwrap();
+        let resolver = Resolver::builder().build();
+        let conf_file = "./test_files/simple/.editorconfig";
+        resolver.set_overlay(conf_file, "root = true\n[*.txt]\nindent_style = tab\n");
+        // A config file that isn't saved yet
+        resolver.clone()
+            .set_overlay("./test_files/simple/nested/.editorconfig", "[*]\ncharset = utf-8\n");
+        let properties = resolver.resolve(&path).unwrap();
+        assert_eq!(properties.get("indent_style").unwrap(), "tab");
+        assert_eq!(properties.get("end_of_line"), None);
+        let nested = path.with_file_name("nested/new.txt");
+        assert_eq!(resolver.resolve(&nested).unwrap().get("charset").unwrap(), "utf-8");
+
+        assert!(resolver.remove_overlay(conf_file).is_some());
+        assert_eq!(resolver.resolve(&path).unwrap().get("end_of_line").unwrap(), "lf");
+        resolver.clear_overlays();
+        assert_eq!(resolver.resolve(&nested).unwrap().get("charset"), None);
+    }
+
+    #[test]
+    fn tells_whether_a_config_file_applies_without_parsing() {
+        let fixture = FixtureBuilder::new()
+            .config("a/.editorconfig", "[*]\n= 2\n")
+            .file("a/b/x.rs")
+            .file("c/y.rs")
+            .build()
+            .unwrap();
+        let resolver = Resolver::builder().lookup_options(fixture.lookup_options()).build();
+        assert!(resolver.has_config(&fixture.path("a/b/x.rs")));
+        assert!(!resolver.has_config(&fixture.path("c/y.rs")));
+        assert!(resolver.cached_config_files().is_empty());
+
+        let conf_file = fixture.path("c/.editorconfig");
+        resolver.set_overlay(&conf_file, "[*]\nindent_size = 2\n");
+        assert!(resolver.has_config(&fixture.path("c/y.rs")));
+        resolver.remove_overlay(&conf_file);
+        assert!(!resolver.has_config(&fixture.path("c/y.rs")));
+        fs::write(&conf_file, "").unwrap();
+        resolver.invalidate(&conf_file);
+        assert!(resolver.has_config(&fixture.path("c/y.rs")));
+        assert_eq!(resolver.resolve(&fixture.path("c/y.rs")).unwrap().get("indent_size"), None);
+
+        let outside = Resolver::builder().stop_dir(fixture.path("a/b")).build();
+        assert!(!outside.has_config(&fixture.path("a/b/x.rs")));
+    }
+
+    #[test]
+    fn lowercases_the_values_of_registered_properties() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig",
+                    "[*.cs]\ncsharp_prefer_braces = When_Multiline\nij_any = Mixed\n\
+                     max_line_length = OFF\n")
+            .file("x.cs")
+            .build()
+            .unwrap();
+        let path = fixture.path("x.cs");
+        let builder = Resolver::builder().lookup_options(fixture.lookup_options());
+        let properties = builder.clone().build().resolve(&path).unwrap();
+        assert_eq!(properties["csharp_prefer_braces"], "When_Multiline");
+        assert_eq!(properties["max_line_length"], "OFF");
+
+        let resolver = builder.property(PropertySpec::new("csharp_*", ValueType::Boolean)
+                .keywords(&["when_multiline"]))
+            .property(PropertySpec::new("ij_*", ValueType::Text).case_sensitive())
+            .build();
+        let properties = resolver.resolve(&path).unwrap();
+        assert_eq!(properties["csharp_prefer_braces"], "when_multiline");
+        assert_eq!(properties["ij_any"], "Mixed");
+        let registry = &resolver.lookup_options().property_registry;
+        assert_eq!(registry.check_value("csharp_style_var", "maybe").unwrap(),
+                   "true, false or when_multiline");
+    }
+}
@@ -0,0 +1,413 @@
+//! Rewriting sections to simpler ones giving the same properties to the same files, like
+//! `[*.{md,md}]` to `[*.md]`, so config files grown by hand can be tidied by `lint --fix`.
+//!
+//! Patterns are only rewritten when `patterns_equivalent` proves the rewrite matches the
+//! same paths. `[*.{md}]` is left alone: braces around a single option match literally.
+
+use std::collections::BTreeSet;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use glob::Glob;
+use ini::Document;
+use Error;
+
+/// Expansions `patterns_equivalent` compares at most per pattern, beyond which it gives up.
+const MAX_EXPANSIONS: usize = 1024;
+
+/// A part of a pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Node {
+    /// Written as is: literal characters, escapes, wildcards, `[...]` classes, numeric
+    /// ranges and braces around a single option.
+    Text(String),
+    /// `{a,b}`, each option being a sequence of nodes.
+    Alternation(Vec<Vec<Node>>),
+}
+
+/// Parses `pattern`, `None` if it has braces whose meaning isn't plain alternation: unmatched
+/// ones, empty options, options separated with `|`, nested in braces around one option or
+/// in `[...]` classes.
+fn parse(pattern: &str) -> Option<Vec<Node>> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let (nodes, end) = parse_seq(&chars, 0, false)?;
+    if end == chars.len() { Some(nodes) } else { None }
+}
+
+/// Parses from `pos` up to the end, or to the `,` or `}` ending an option when `in_braces`.
+fn parse_seq(chars: &[char], mut pos: usize, in_braces: bool) -> Option<(Vec<Node>, usize)> {
+    let mut nodes = vec![];
+    let mut text = String::new();
+    while pos < chars.len() {
+        match chars[pos] {
+            '\\' => {
+                text.extend(&chars[pos..(pos + 2).min(chars.len())]);
+                pos += 2;
+            }
+            '[' => {
+                let end = class_end(chars, pos).unwrap_or(pos);
+                if chars[pos..=end].iter().any(|&c| "{},|".contains(c)) {
+                    return None;
+                }
+                text.extend(&chars[pos..=end]);
+                pos = end + 1;
+            }
+            ',' | '}' if in_braces => break,
+            '}' | '|' => return None,
+            '{' => {
+                let end = brace_end(chars, pos)?;
+                let inner: String = chars[pos + 1..end].iter().collect();
+                if is_numeric_range(&inner) || !has_top_level_comma(&chars[pos + 1..end]) {
+                    if inner.contains(['{', '}', '|']) {
+                        return None;
+                    }
+                    text.push_str(&format!("{{{}}}", inner));
+                    pos = end + 1;
+                    continue;
+                }
+                let mut options = vec![];
+                pos += 1;
+                loop {
+                    let (option, option_end) = parse_seq(chars, pos, true)?;
+                    if option.is_empty() {
+                        return None;
+                    }
+                    options.push(option);
+                    pos = option_end + 1;
+                    if chars[option_end] == '}' {
+                        break;
+                    }
+                }
+                if !text.is_empty() {
+                    nodes.push(Node::Text(text.split_off(0)));
+                }
+                nodes.push(Node::Alternation(options));
+            }
+            c => {
+                text.push(c);
+                pos += 1;
+            }
+        }
+    }
+    if in_braces && pos >= chars.len() {
+        return None;
+    }
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+    Some((nodes, pos))
+}
+
+/// The `]` closing the class opened at `start`, like the glob translation finds it.
+fn class_end(chars: &[char], start: usize) -> Option<usize> {
+    chars[start + 1..].iter().position(|&c| c == ']').map(|i| start + 1 + i)
+}
+
+/// The `}` closing the brace opened at `start`, skipping escapes and nested braces.
+fn brace_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut pos = start;
+    while pos < chars.len() {
+        match chars[pos] {
+            '\\' => pos += 1,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+            _ => {}
+        }
+        pos += 1;
+    }
+    None
+}
+
+fn has_top_level_comma(chars: &[char]) -> bool {
+    let mut depth = 0;
+    let mut pos = 0;
+    while pos < chars.len() {
+        match chars[pos] {
+            '\\' => pos += 1,
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => return true,
+            _ => {}
+        }
+        pos += 1;
+    }
+    false
+}
+
+fn is_numeric_range(inner: &str) -> bool {
+    static NUMERIC_RANGE: OnceLock<Regex> = OnceLock::new();
+    let numeric_range_regex =
+        NUMERIC_RANGE.get_or_init(|| Regex::new(r"^-?\d+\.\.-?\d+$").unwrap());
+    numeric_range_regex.is_match(inner)
+}
+
+fn write(nodes: &[Node]) -> String {
+    let mut pattern = String::new();
+    for node in nodes {
+        match *node {
+            Node::Text(ref text) => pattern.push_str(text),
+            Node::Alternation(ref options) => {
+                let options: Vec<_> = options.iter().map(|option| write(option)).collect();
+                pattern.push_str(&format!("{{{}}}", options.join(",")));
+            }
+        }
+    }
+    pattern
+}
+
+/// Where an option of braces starts or ends in an expansion.
+const BOUNDARY: char = '\u{0}';
+
+/// The patterns without braces `nodes` stands for, with a `BOUNDARY` around the options of
+/// braces, `None` past `MAX_EXPANSIONS`.
+fn expand(nodes: &[Node]) -> Option<BTreeSet<String>> {
+    let mut expansions: BTreeSet<String> = Some(String::new()).into_iter().collect();
+    for node in nodes {
+        let suffixes = match *node {
+            Node::Text(ref text) => Some(text.clone()).into_iter().collect(),
+            Node::Alternation(ref options) => {
+                let mut suffixes = BTreeSet::new();
+                for option in options {
+                    suffixes.extend(expand(option)?
+                        .into_iter()
+                        .map(|expansion| format!("{}{}{}", BOUNDARY, expansion, BOUNDARY)));
+                }
+                suffixes
+            }
+        };
+        if expansions.len() * suffixes.len() > MAX_EXPANSIONS {
+            return None;
+        }
+        expansions = expansions.iter()
+            .flat_map(|prefix| suffixes.iter().map(move |suffix| format!("{}{}", prefix, suffix)))
+            .collect();
+    }
+    Some(expansions.iter().map(|expansion| join_parts(expansion)).collect())
+}
+
+/// `expansion` with the boundaries left out where joining the options with what surrounds
+/// them reads the same. The translation to a regex treats `**`, `*` next to `/` and a
+/// leading `/` or `*` apart, whereas `*{*,*}` matches like `*` twice.
+fn join_parts(expansion: &str) -> String {
+    let chars: Vec<char> = expansion.chars().filter(|&c| c != BOUNDARY).collect();
+    let mut joined = String::new();
+    let mut pos = 0;
+    for c in expansion.chars() {
+        if c != BOUNDARY {
+            joined.push(c);
+            pos += 1;
+            continue;
+        }
+        let before = if pos == 0 { None } else { Some(chars[pos - 1]) };
+        let after = chars.get(pos).cloned();
+        let significant = matches!((before, after),
+                                   (Some('*'), Some('*')) | (Some('*'), Some('/')) |
+                                   (Some('/'), Some('*')) | (None, Some('/')) |
+                                   (None, Some('*')));
+        if significant && !joined.ends_with(BOUNDARY) {
+            joined.push(BOUNDARY);
+        }
+    }
+    joined
+}
+
+/// Removes repeated options of alternations, and the braces of those left with one.
+fn simplify_nodes(nodes: Vec<Node>) -> Vec<Node> {
+    let mut simplified = vec![];
+    for node in nodes {
+        match node {
+            Node::Alternation(options) => {
+                let mut seen = BTreeSet::new();
+                let mut kept: Vec<Vec<Node>> = options.into_iter()
+                    .map(simplify_nodes)
+                    .filter(|option| seen.insert(write(option)))
+                    .collect();
+                if kept.len() == 1 {
+                    simplified.append(&mut kept[0]);
+                } else {
+                    simplified.push(Node::Alternation(kept));
+                }
+            }
+            text => simplified.push(text),
+        }
+    }
+    // Neighbouring texts read the same joined
+    let mut joined: Vec<Node> = vec![];
+    for node in simplified {
+        match (joined.last_mut(), node) {
+            (Some(&mut Node::Text(ref mut last)), Node::Text(ref text)) => last.push_str(text),
+            (_, node) => joined.push(node),
+        }
+    }
+    joined
+}
+
+/// Whether `a` and `b` match the same paths: both expand to the same patterns without
+/// braces, and both or none have a `/`, which decides whether they match in subdirectories.
+/// `false` when that can't be told, e.g. for patterns with thousands of expansions or with
+/// several braces one after the other, which the translation to regexes reads as one.
+pub fn patterns_equivalent(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    if a.contains('/') != b.contains('/') {
+        return false;
+    }
+    let expansions = |pattern| {
+        let nodes = parse(pattern)?;
+        let alternations = nodes.iter().filter(|node| matches!(**node, Node::Alternation(_)));
+        if alternations.count() > 1 { None } else { expand(&nodes) }
+    };
+    match (expansions(a), expansions(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Returns a simpler pattern matching the same paths as `pattern`, if there is one:
+/// repeated options of braces are removed, and braces left with one option with them.
+///
+/// ```
+/// assert_eq!(editorconfig::simplify_pattern("*.{md,md}").unwrap(), "*.md");
+/// assert_eq!(editorconfig::simplify_pattern("{a,b,a}.txt").unwrap(), "{a,b}.txt");
+/// assert_eq!(editorconfig::simplify_pattern("*.{md}"), None);
+/// ```
+pub fn simplify_pattern(pattern: &str) -> Option<String> {
+    // What invalid patterns match is better fixed by hand
+    Glob::new(pattern).ok()?;
+    let simplified = write(&simplify_nodes(parse(pattern)?));
+    if simplified != pattern && patterns_equivalent(pattern, &simplified) {
+        Some(simplified)
+    } else {
+        None
+    }
+}
+
+/// A section `simplify_config` rewrote.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Simplification {
+    /// The pattern of the section on `line` is written `simplified` instead.
+    Pattern {
+        line: usize,
+        pattern: String,
+        simplified: String,
+    },
+    /// The section on `line` was merged into the section right before it, which has the
+    /// same pattern once simplified.
+    Merged { line: usize, pattern: String },
+}
+
+/// Simplifies the patterns of the config file `contents` with `simplify_pattern`, then
+/// merges sections into the one before them when they have the same pattern. Returns the
+/// new contents, the same but for these sections, and what was done in the order of the
+/// lines of `contents`.
+pub fn simplify_config(contents: &str) -> Result<(String, Vec<Simplification>), Error> {
+    let mut document = Document::parse(contents)?;
+    let lines = document.header_lines();
+    let mut simplifications = vec![];
+    for (section, &line) in document.sections_mut().zip(&lines) {
+        let pattern = match section.name() {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        if let Some(simplified) = simplify_pattern(&pattern) {
+            section.rename(&simplified);
+            simplifications.push(Simplification::Pattern {
+                line,
+                pattern,
+                simplified,
+            });
+        }
+    }
+    let names: Vec<_> = document.sections().map(|s| s.name().unwrap_or("").to_string()).collect();
+    for index in document.merge_adjacent_sections() {
+        simplifications.push(Simplification::Merged {
+            line: lines[index],
+            pattern: names[index].clone(),
+        });
+    }
+    simplifications.sort_by_key(|simplification| match *simplification {
+        Simplification::Pattern { line, .. } | Simplification::Merged { line, .. } => line,
+    });
+    Ok((document.to_string(), simplifications))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glob::matches;
+
+    #[test]
+    fn simplifies_only_to_equivalent_patterns() {
+        let cases = [("*.{md,md}", Some("*.md")),
+                     ("{a,b,a}.txt", Some("{a,b}.txt")),
+                     ("src/{lib,lib}/**.rs", Some("src/lib/**.rs")),
+                     ("{x,{y,y}}", Some("{x,y}")),
+                     ("*.{md}", None),
+                     ("{1..3,1..3}", Some("1..3")),
+                     ("{1..3}.log", None),
+                     ("*.{js,ts}", None),
+                     ("\\{a,a\\}", None),
+                     ("[{,]{a,a}", None),
+                     ("[ab]{a,a}", Some("[ab]a")),
+                     ("{a,a", None),
+                     ("{,a}", None),
+                     ("{a,b}x{c,c}", None),
+                     ("x{c,{c,c}}", Some("xc")),
+                     ("*{*,*}", None),
+                     ("{/a,/a}", None),
+                     ("{a/,a/}**/b", None),
+                     ("{**,**}/x", None)];
+        for &(pattern, simplified) in &cases {
+            assert_eq!(simplify_pattern(pattern).as_deref(), simplified, "{}", pattern);
+        }
+        let paths = ["a.md", "docs/a.md", "{md}", "a.txt", "b.txt", "c.txt", "src/lib/x.rs",
+                     "src/lib/y/x.rs", "x", "y", "1..3", "[a", "ba", "axc", "bxc", "a/b", "a/c/b"];
+        for &(pattern, simplified) in &cases {
+            if let Some(simplified) = simplified {
+                for path in &paths {
+                    assert_eq!(matches(pattern, path).unwrap(),
+                               matches(simplified, path).unwrap(),
+                               "{} and {} on {}",
+                               pattern,
+                               simplified,
+                               path);
+                }
+            }
+        }
+
+        assert!(patterns_equivalent("{a,b}.txt", "{b,a}.txt"));
+        assert!(!patterns_equivalent("{a,b}", "a"));
+        assert!(!patterns_equivalent("{a,b/c}", "{b/c,a}/"));
+        assert!(!patterns_equivalent("{1..3}", "{1..4}"));
+        let many = "{a,b,c,d}".repeat(6);
+        assert!(!patterns_equivalent(&many, &format!("{}x", many)));
+    }
+
+    #[test]
+    fn simplifies_and_merges_sections() {
+        let contents = "root = true\n\n[*.{md,md}]\nindent_size = 2\n\n[*.md]\ncharset = utf-8\n\
+                        [*.{md}]\nx = 1\n";
+        let (simplified, simplifications) = simplify_config(contents).unwrap();
+        assert_eq!(simplified,
+                   "root = true\n\n[*.md]\nindent_size = 2\n\ncharset = utf-8\n[*.{md}]\nx = 1\n");
+        assert_eq!(simplifications,
+                   [Simplification::Pattern {
+                        line: 3,
+                        pattern: "*.{md,md}".to_string(),
+                        simplified: "*.md".to_string(),
+                    },
+                    Simplification::Merged {
+                        line: 6,
+                        pattern: "*.md".to_string(),
+                    }]);
+        assert_eq!(simplify_config(&simplified).unwrap(), (simplified.clone(), vec![]));
+    }
+}
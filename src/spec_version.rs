@@ -0,0 +1,53 @@
+//! Versions of the EditorConfig spec, so lookups given one with `-b` return what the
+//! reference core of that version would, for scripts and core tests written against it.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A version of the spec, `major.minor.patch`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpecVersion(pub u32, pub u32, pub u32);
+
+impl SpecVersion {
+    /// The first version giving `indent_size = tab` to files with `indent_style = tab`, see
+    /// `PreprocessingProfile::for_lookup`.
+    pub const INDENT_SIZE_TAB: SpecVersion = SpecVersion(0, 10, 0);
+}
+
+impl FromStr for SpecVersion {
+    type Err = String;
+
+    /// Reads `major.minor.patch`, missing parts being 0.
+    fn from_str(s: &str) -> Result<SpecVersion, String> {
+        let invalid = || format!("invalid version `{}`, expected e.g. 0.12.0", s);
+        let mut parts = s.split('.').map(str::parse::<u32>);
+        let major = parts.next().and_then(Result::ok).ok_or_else(invalid)?;
+        let minor = parts.next().unwrap_or(Ok(0)).map_err(|_| invalid())?;
+        let patch = parts.next().unwrap_or(Ok(0)).map_err(|_| invalid())?;
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(SpecVersion(major, minor, patch))
+    }
+}
+
+impl fmt::Display for SpecVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.0, self.1, self.2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_versions_with_missing_parts() {
+        assert_eq!("0.9".parse(), Ok(SpecVersion(0, 9, 0)));
+        assert_eq!("0.12.1".parse(), Ok(SpecVersion(0, 12, 1)));
+        assert!("0.9.x".parse::<SpecVersion>().is_err());
+        assert!("1.2.3.4".parse::<SpecVersion>().is_err());
+        assert!(SpecVersion(0, 9, 9) < SpecVersion::INDENT_SIZE_TAB);
+        assert_eq!(SpecVersion(0, 10, 0).to_string(), "0.10.0");
+    }
+}
@@ -0,0 +1,141 @@
+//! Temporary directory trees with config files, for crates depending on this one to test how
+//! their files are resolved. Enabled by the `test-support` feature.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ordermap::OrderMap;
+
+use {get_config_with, Error, LookupOptions, Warning};
+
+/// Lists the files of a fixture, to create them all at once with `build`.
+///
+/// ```text
+/// let fixture = FixtureBuilder::new()
+///     .config("a/.editorconfig", "[*.rs]\nindent_size = 4\n")
+///     .file("a/b/x.rs")
+///     .build()?;
+/// assert_eq!(fixture.config_of("a/b/x.rs")?.get("indent_size").unwrap(), "4");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FixtureBuilder {
+    files: Vec<(PathBuf, String)>,
+}
+
+impl FixtureBuilder {
+    pub fn new() -> FixtureBuilder {
+        FixtureBuilder::default()
+    }
+
+    /// Adds a config file at `path`, relative to the root of the fixture.
+    pub fn config<P: AsRef<Path>>(self, path: P, contents: &str) -> FixtureBuilder {
+        self.file_with(path, contents)
+    }
+
+    /// Adds an empty file at `path`, relative to the root of the fixture.
+    pub fn file<P: AsRef<Path>>(self, path: P) -> FixtureBuilder {
+        self.file_with(path, "")
+    }
+
+    /// Adds a file with `contents` at `path`, relative to the root of the fixture.
+    pub fn file_with<P: AsRef<Path>>(mut self, path: P, contents: &str) -> FixtureBuilder {
+        self.files.push((path.as_ref().to_path_buf(), contents.to_string()));
+        self
+    }
+
+    /// Creates the files, and the directories containing them, in a new directory of the
+    /// system's temporary directory.
+    pub fn build(self) -> io::Result<Fixture> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let name = format!("editorconfig-fixture-{}-{}",
+                           process::id(),
+                           NEXT_ID.fetch_add(1, Ordering::SeqCst));
+        let root = env::temp_dir().join(name);
+        fs::create_dir_all(&root)?;
+        // Dropped on errors too, removing what was created
+        let fixture = Fixture { root: root.canonicalize()? };
+        for (path, contents) in &self.files {
+            let path = fixture.path(path);
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&path, contents)?;
+        }
+        Ok(fixture)
+    }
+}
+
+/// A directory tree created by `FixtureBuilder`, removed when dropped.
+#[derive(Debug)]
+pub struct Fixture {
+    root: PathBuf,
+}
+
+impl Fixture {
+    /// The canonical path of the root directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The absolute path of `path`, relative to the root.
+    pub fn path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// The lookup options reading no config file outside of the fixture, whatever the
+    /// temporary directory contains.
+    pub fn lookup_options(&self) -> LookupOptions {
+        LookupOptions {
+            stop_dir: Some(self.root.clone()),
+            ..LookupOptions::default()
+        }
+    }
+
+    /// Returns the properties of `path`, relative to the root, with `lookup_options`.
+    pub fn config_of<P: AsRef<Path>>(&self, path: P) -> Result<OrderMap<String, String>, Error> {
+        self.config_of_with(path, &mut vec![])
+    }
+
+    /// Returns the properties of `path` like `config_of`, appending warnings to `warnings`.
+    pub fn config_of_with<P: AsRef<Path>>(&self,
+                                         path: P,
+                                         warnings: &mut Vec<Warning>)
+                                         -> Result<OrderMap<String, String>, Error> {
+        get_config_with(&self.path(path), &self.lookup_options(), warnings)
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_trees_and_removes_them() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig", "root = true\n[*]\nindent_style = tab\n")
+            .config("a/.editorconfig", "[*.rs]\nindent_size = 4\n")
+            .file("a/b/x.rs")
+            .file_with("README.md", "# Fixture\n")
+            .build()
+            .unwrap();
+        let properties = fixture.config_of("a/b/x.rs").unwrap();
+        assert_eq!(properties.get("indent_style").unwrap(), "tab");
+        assert_eq!(properties.get("indent_size").unwrap(), "4");
+        assert_eq!(fs::read_to_string(fixture.path("README.md")).unwrap(), "# Fixture\n");
+
+        let root = fixture.root().to_path_buf();
+        assert!(root.is_dir());
+        drop(fixture);
+        assert!(!root.exists());
+    }
+}
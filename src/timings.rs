@@ -0,0 +1,121 @@
+//! Where lookups spend their time, to track down pathological configs and patterns.
+
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use ordermap::OrderMap;
+
+use complexity::PatternCost;
+
+/// What was being timed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Phase {
+    /// Resolving the whole configuration of a target file.
+    Resolve,
+    /// Reading and parsing a config file.
+    Parse,
+    /// Matching the sections of a config file against a target.
+    Match,
+}
+
+/// The total time spent in one phase for one file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimingEntry {
+    /// The target file for `Phase::Resolve`, the config file otherwise.
+    pub path: PathBuf,
+    pub phase: Phase,
+    /// Sum of all recorded durations.
+    pub total: Duration,
+    /// Number of recorded durations.
+    pub count: usize,
+}
+
+/// Accumulates durations per (file, phase), and the expensive section patterns of the config
+/// files parsed meanwhile. Safe to share between threads.
+#[derive(Debug, Default)]
+pub struct Timings {
+    entries: Mutex<OrderMap<(PathBuf, Phase), (Duration, usize)>>,
+    expensive_patterns: Mutex<OrderMap<PathBuf, Vec<PatternCost>>>,
+}
+
+impl Timings {
+    pub fn new() -> Timings {
+        Timings::default()
+    }
+
+    /// Adds `duration` to the time spent in `phase` for `path`.
+    pub fn record(&self, path: &Path, phase: Phase, duration: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.entry((path.to_path_buf(), phase)).or_insert((Duration::new(0, 0), 0));
+        entry.0 += duration;
+        entry.1 += 1;
+    }
+
+    /// Remembers the expensive section patterns of the config file at `conf_file`, found
+    /// while parsing it.
+    pub(crate) fn record_expensive_patterns(&self, conf_file: &Path, costs: Vec<PatternCost>) {
+        if !costs.is_empty() {
+            self.expensive_patterns.lock().unwrap().insert(conf_file.to_path_buf(), costs);
+        }
+    }
+
+    /// Returns the expensive section patterns of the config file at `conf_file`, if it was
+    /// parsed.
+    pub fn expensive_patterns(&self, conf_file: &Path) -> Vec<PatternCost> {
+        self.expensive_patterns.lock().unwrap().get(conf_file).cloned().unwrap_or_default()
+    }
+
+    /// Returns the `n` entries of `phase` with the highest total time, slowest first.
+    pub fn slowest(&self, phase: Phase, n: usize) -> Vec<TimingEntry> {
+        let entries = self.entries.lock().unwrap();
+        let mut result: Vec<_> = entries.iter()
+            .filter(|&(&(_, p), _)| p == phase)
+            .map(|(&(ref path, phase), &(total, count))| {
+                TimingEntry {
+                    path: path.clone(),
+                    phase,
+                    total,
+                    count,
+                }
+            })
+            .collect();
+        result.sort_by_key(|entry| Reverse(entry.total));
+        result.truncate(n);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_slowest_entries_per_phase() {
+        let timings = Timings::new();
+        timings.record(Path::new("a"), Phase::Parse, Duration::from_millis(1));
+        timings.record(Path::new("b"), Phase::Parse, Duration::from_millis(3));
+        timings.record(Path::new("a"), Phase::Parse, Duration::from_millis(4));
+        timings.record(Path::new("c"), Phase::Parse, Duration::from_millis(2));
+        timings.record(Path::new("a"), Phase::Match, Duration::from_millis(9));
+
+        let slowest = timings.slowest(Phase::Parse, 2);
+        assert_eq!(slowest.len(), 2);
+        assert_eq!(slowest[0].path, PathBuf::from("a"));
+        assert_eq!(slowest[0].total, Duration::from_millis(5));
+        assert_eq!(slowest[0].count, 2);
+        assert_eq!(slowest[1].path, PathBuf::from("b"));
+    }
+
+    #[test]
+    fn keeps_the_expensive_patterns_of_parsed_files() {
+        use complexity::pattern_cost;
+
+        let timings = Timings::new();
+        timings.record_expensive_patterns(Path::new("a"), vec![pattern_cost("**/a/**/b/**")]);
+        timings.record_expensive_patterns(Path::new("b"), vec![]);
+        assert_eq!(timings.expensive_patterns(Path::new("a"))[0].pattern, "**/a/**/b/**");
+        assert!(timings.expensive_patterns(Path::new("b")).is_empty());
+    }
+}
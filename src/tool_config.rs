@@ -0,0 +1,268 @@
+//! Repository level defaults for the command line tool, read from `editorconfig-tool.ini`.
+//!
+//! The file uses the same layout `editorconfig --dump-config` prints, so a dump can be
+//! checked in and trimmed down. Sections that only describe the tool (`[tool]`, `[limits]`,
+//! `[rules]`, `[features]`) are ignored.
+//!
+//! It's read with the parser of config files, so it's an INI file, not TOML, although the
+//! values look alike:
+//!
+//! ```ini
+//! [lookup]
+//! conf_overrides = [".editorconfig.user"]
+//! strict_io = true
+//!
+//! [output]
+//! paths = "relative"
+//! ```
+//!
+//! Strings may be quoted, and quoted ones may escape characters like values of config files
+//! do. Booleans are `true` or `false`, and lists are written on one line, between brackets,
+//! with every item quoted. Comments start with `#` or `;`.
+
+use std::path::{Path, PathBuf};
+
+use ini::Ini;
+use {load_ini, Error};
+use classify::FileClass;
+use diagnostics::GroupBy;
+use output_format::OutputFormat;
+use path_style::PathStyle;
+
+/// Name of the tool config file.
+pub const TOOL_CONFIG_FILE_NAME: &str = "editorconfig-tool.ini";
+
+/// Default CLI options. Every field is optional; flags given on the command line win.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ToolConfig {
+    /// `[lookup] conf_filename`, like `-f`.
+    pub conf_filename: Option<String>,
+    /// `[lookup] conf_overrides`, like `--override`.
+    pub conf_overrides: Option<Vec<String>>,
+    /// `[lookup] spec_version`, like `-b`.
+    pub spec_version: Option<String>,
+    /// `[lookup] min_spec_version`, like `--min-version`.
+    pub min_spec_version: Option<String>,
+    /// `[lookup] strict_io`, like `--strict-io`.
+    pub strict_io: Option<bool>,
+    /// `[lookup] trust_root`, like `--trust-root`.
+    pub trust_root: Option<String>,
+    /// `[lookup] stop_dir`, like `--stop-dir`.
+    pub stop_dir: Option<String>,
+    /// `[lookup] global_config`, like `--global-config`.
+    pub global_config: Option<String>,
+    /// `[lookup] user_config`, like `--user-config`.
+    pub user_config: Option<bool>,
+    /// `[lookup] trust_same_owner`, like `--trust-owner`.
+    pub trust_same_owner: Option<bool>,
+    /// `[lookup] normalize_unicode`, like `--normalize-unicode`.
+    pub normalize_unicode: Option<bool>,
+    /// `[lookup] lenient_values`, like `--lenient-values`.
+    pub lenient_values: Option<bool>,
+    /// `[lookup] repair_malformed`, like `--repair`.
+    pub repair_malformed: Option<bool>,
+    /// `[lookup] require_config`, like `--fail-on-missing-config`.
+    pub require_config: Option<bool>,
+    /// `[lookup] conf_any_case`, like `--conf-any-case`.
+    pub conf_any_case: Option<bool>,
+    /// `[lookup] one_file_system`, like `--one-file-system`.
+    pub one_file_system: Option<bool>,
+    /// `[walk] follow_symlinks`, like `--follow-symlinks`.
+    pub follow_symlinks: Option<bool>,
+    /// `[walk] dedup_visited`, like `--no-dedup-dirs`.
+    pub dedup_visited: Option<bool>,
+    /// `[walk] sparse_checkout`, like `--sparse`.
+    pub sparse_checkout: Option<bool>,
+    /// `[output] paths`, like `--paths`.
+    pub paths: Option<PathStyle>,
+    /// `[output] group_by`, like `--group-by`.
+    pub group_by: Option<GroupBy>,
+    /// `[output] owners`, like `--owners`.
+    pub owners: Option<bool>,
+    /// `[output] format`, like `--format`.
+    pub format: Option<OutputFormat>,
+    /// `[classify]`, patterns of the files of each class, e.g. `generated = ["**/gen/**"]`.
+    pub classify: Option<Vec<(FileClass, Vec<String>)>>,
+}
+
+impl ToolConfig {
+    /// Looks for `editorconfig-tool.ini` in `start` and then in every parent directory.
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start.ancestors()
+            .map(|dir| dir.join(TOOL_CONFIG_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Reads a tool config file.
+    pub fn load(path: &Path) -> Result<ToolConfig, Error> {
+        ToolConfig::from_ini(&load_ini(path)?).map_err(|err| err.in_file(path))
+    }
+
+    /// Parses the contents of a tool config file.
+    pub fn parse(contents: &str) -> Result<ToolConfig, Error> {
+        ToolConfig::from_ini(&Ini::load_from_str(contents)?)
+    }
+
+    fn from_ini(ini: &Ini) -> Result<ToolConfig, Error> {
+        ToolConfig::from_settings(ini).map_err(|message| {
+            Error::InvalidSetting {
+                file: None,
+                message,
+            }
+        })
+    }
+
+    fn from_settings(ini: &Ini) -> Result<ToolConfig, String> {
+        let mut config = ToolConfig::default();
+        for (section, properties) in ini.iter() {
+            let section = match *section {
+                Some(ref section) => section.as_str(),
+                None => "",
+            };
+            for (key, value) in properties.iter() {
+                match (section, key.as_str()) {
+                    ("lookup", "conf_filename") => config.conf_filename = Some(value.clone()),
+                    ("lookup", "conf_overrides") => {
+                        config.conf_overrides = Some(parse_list(key, value)?)
+                    }
+                    ("lookup", "spec_version") => config.spec_version = Some(value.clone()),
+                    ("lookup", "min_spec_version") => {
+                        config.min_spec_version = Some(value.clone())
+                    }
+                    ("lookup", "strict_io") => config.strict_io = Some(parse_bool(key, value)?),
+                    ("lookup", "trust_root") => config.trust_root = Some(value.clone()),
+                    ("lookup", "stop_dir") => config.stop_dir = Some(value.clone()),
+                    ("lookup", "global_config") => config.global_config = Some(value.clone()),
+                    ("lookup", "user_config") => {
+                        config.user_config = Some(parse_bool(key, value)?)
+                    }
+                    ("lookup", "trust_same_owner") => {
+                        config.trust_same_owner = Some(parse_bool(key, value)?)
+                    }
+                    ("lookup", "normalize_unicode") => {
+                        config.normalize_unicode = Some(parse_bool(key, value)?)
+                    }
+                    ("lookup", "lenient_values") => {
+                        config.lenient_values = Some(parse_bool(key, value)?)
+                    }
+                    ("lookup", "repair_malformed") => {
+                        config.repair_malformed = Some(parse_bool(key, value)?)
+                    }
+                    ("lookup", "require_config") => {
+                        config.require_config = Some(parse_bool(key, value)?)
+                    }
+                    ("lookup", "conf_any_case") => {
+                        config.conf_any_case = Some(parse_bool(key, value)?)
+                    }
+                    ("lookup", "one_file_system") => {
+                        config.one_file_system = Some(parse_bool(key, value)?)
+                    }
+                    ("walk", "follow_symlinks") => {
+                        config.follow_symlinks = Some(parse_bool(key, value)?)
+                    }
+                    ("walk", "dedup_visited") => {
+                        config.dedup_visited = Some(parse_bool(key, value)?)
+                    }
+                    ("walk", "sparse_checkout") => {
+                        config.sparse_checkout = Some(parse_bool(key, value)?)
+                    }
+                    ("output", "paths") => config.paths = Some(value.parse()?),
+                    ("output", "group_by") => config.group_by = Some(value.parse()?),
+                    ("output", "owners") => config.owners = Some(parse_bool(key, value)?),
+                    ("output", "format") => config.format = Some(value.parse()?),
+                    ("classify", class) => {
+                        let patterns = parse_list(key, value)?;
+                        let classes = config.classify.get_or_insert_with(Vec::new);
+                        classes.push((class.parse()?, patterns))
+                    }
+                    ("tool", _) | ("limits", _) | ("rules", _) | ("features", _) => {}
+                    _ => return Err(format!("unknown setting `{}` in section [{}]", key, section)),
+                }
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("`{}` must be true or false, not `{}`", key, value)),
+    }
+}
+
+/// Parses a list of strings, like `[".a", ".b"]`.
+fn parse_list(key: &str, value: &str) -> Result<Vec<String>, String> {
+    let value = value.trim();
+    if !value.starts_with('[') || !value.ends_with(']') {
+        return Err(format!("`{}` must be a list of strings, not `{}`", key, value));
+    }
+    value[1..value.len() - 1]
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            if item.len() >= 2 && item.starts_with('"') && item.ends_with('"') {
+                Ok(item[1..item.len() - 1].to_string())
+            } else {
+                Err(format!("`{}` must be a list of strings, not `{}`", key, value))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dump_config_output() {
+        let config = ToolConfig::parse("[tool]\nversion = \"1.0.0\"\n\n\
+                                        [lookup]\nconf_filename = \".editorconfig\"\n\
+                                        conf_overrides = [\".editorconfig.user\"]\n\
+                                        spec_version = \"0.8.0\"\n\
+                                        min_spec_version = \"0.15.0\"\nstrict_io = true\n\n\
+                                        [walk]\nfollow_symlinks = true\n\n\
+                                        [output]\npaths = \"relative\"\n\n\
+                                        [classify]\ngenerated = [\"**/gen/**\"]\n\n\
+                                        [limits]\nmax_key_length = 50\n")
+            .unwrap();
+        assert_eq!(config,
+                   ToolConfig {
+                       conf_filename: Some(".editorconfig".to_string()),
+                       conf_overrides: Some(vec![".editorconfig.user".to_string()]),
+                       spec_version: Some("0.8.0".to_string()),
+                       min_spec_version: Some("0.15.0".to_string()),
+                       strict_io: Some(true),
+                       trust_root: None,
+                       stop_dir: None,
+                       global_config: None,
+                       user_config: None,
+                       trust_same_owner: None,
+                       normalize_unicode: None,
+                       lenient_values: None,
+                       repair_malformed: None,
+                       require_config: None,
+                       conf_any_case: None,
+                       one_file_system: None,
+                       follow_symlinks: Some(true),
+                       dedup_visited: None,
+                       sparse_checkout: None,
+                       paths: Some(PathStyle::Relative),
+                       group_by: None,
+                       owners: None,
+                       format: None,
+                       classify: Some(vec![(FileClass::Generated, vec!["**/gen/**".to_string()])]),
+                   });
+    }
+
+    #[test]
+    fn rejects_unknown_and_invalid_settings() {
+        assert!(ToolConfig::parse("[lookup]\nconffile = \"x\"\n").is_err());
+        assert!(ToolConfig::parse("[lookup]\nstrict_io = yes\n").is_err());
+        assert!(ToolConfig::parse("[lookup]\nconf_overrides = \".user\"\n").is_err());
+        assert!(ToolConfig::parse("[output]\npaths = \"canonical\"\n").is_err());
+        assert!(ToolConfig::parse("[classify]\nhandwritten = [\"*.rs\"]\n").is_err());
+    }
+}
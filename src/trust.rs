@@ -0,0 +1,139 @@
+//! Deciding which config files may influence a lookup.
+//!
+//! A checkout in `/tmp` shouldn't pick up a `/tmp/.editorconfig` planted by another user,
+//! so lookups can be restricted to a workspace root and to files owned by the workspace
+//! owner. See `LookupOptions::trust_root` and `LookupOptions::trust_same_owner`.
+//!
+//! Paths and owners are those of `LookupOptions::files`, so files of providers without
+//! owners, like `MemoryFiles`, are never rejected for their owner.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use {FileProvider, LookupOptions};
+
+/// The trust settings of a lookup, resolved once for all the config files it reads.
+pub struct TrustBoundary {
+    root: Option<PathBuf>,
+    owner: Option<u32>,
+    files: Arc<dyn FileProvider>,
+}
+
+impl TrustBoundary {
+    pub fn new(lookup: &LookupOptions, target: &Path) -> TrustBoundary {
+        let files = lookup.files.clone();
+        let root = lookup.trust_root
+            .as_ref()
+            .map(|root| files.canonicalize(root).unwrap_or_else(|_| root.clone()));
+        let owner = if lookup.trust_same_owner {
+            // The workspace owner is whoever owns the trust root, or else the target
+            let reference = root.as_deref().unwrap_or(target);
+            owner_of(&*files, reference)
+        } else {
+            None
+        };
+        TrustBoundary { root, owner, files }
+    }
+
+    /// Returns why `conf_file` must be ignored, or `None` if it can be read.
+    pub fn reject_reason(&self, conf_file: &Path) -> Option<String> {
+        if let Some(ref root) = self.root {
+            let dir = conf_file.parent().unwrap_or(conf_file);
+            let dir = self.files.canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+            if !dir.starts_with(root) {
+                return Some(format!("ignored, outside the trusted root {}", root.display()));
+            }
+        }
+        if let Some(owner) = self.owner {
+            if owner_of(&*self.files, conf_file).is_some_and(|file_owner| file_owner != owner) {
+                return Some("ignored, owned by another user than the workspace".to_string());
+            }
+        }
+        None
+    }
+}
+
+/// The user owning `path` or its closest existing ancestor.
+fn owner_of(files: &dyn FileProvider, path: &Path) -> Option<u32> {
+    path.ancestors().filter_map(|p| files.owner(p)).next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io;
+    use {get_config_with, MemoryFiles, WarningKind};
+
+    /// Files in memory, owned by the user of the closest directory given an owner.
+    struct OwnedFiles {
+        files: MemoryFiles,
+        owners: HashMap<PathBuf, u32>,
+    }
+
+    impl FileProvider for OwnedFiles {
+        fn exists(&self, path: &Path) -> bool {
+            self.files.exists(path)
+        }
+
+        fn read(&self, path: &Path) -> io::Result<String> {
+            self.files.read(path)
+        }
+
+        fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+            self.files.canonicalize(path)
+        }
+
+        fn owner(&self, path: &Path) -> Option<u32> {
+            self.owners.get(path).cloned()
+        }
+    }
+
+    fn lookup() -> LookupOptions {
+        let mut files = MemoryFiles::new();
+        files.insert("/.editorconfig", "[*]\ncharset = latin1\n");
+        files.insert("/tmp/.editorconfig", "[*]\nindent_style = tab\n");
+        files.insert("/tmp/me/.editorconfig", "[*]\nindent_size = 2\n");
+        files.insert("/tmp/me/project/.editorconfig", "[*]\nend_of_line = lf\n");
+        let owners = [("/", 0), ("/tmp", 0), ("/tmp/.editorconfig", 666), ("/tmp/me", 1000)];
+        let owners = owners.iter().map(|&(path, uid)| (PathBuf::from(path), uid)).collect();
+        LookupOptions { files: Arc::new(OwnedFiles { files, owners }), ..LookupOptions::default() }
+    }
+
+    #[test]
+    fn skips_config_files_of_other_users() {
+        let lookup = LookupOptions { trust_same_owner: true, ..lookup() };
+        let mut warnings = vec![];
+        let properties = get_config_with(Path::new("/tmp/me/project/x.rs"), &lookup, &mut warnings)
+            .unwrap();
+        assert_eq!(properties.get("end_of_line").unwrap(), "lf");
+        assert_eq!(properties.get("indent_size").unwrap(), "2");
+        assert_eq!(properties.get("indent_style"), None);
+        assert_eq!(properties.get("charset"), None);
+        assert!(warnings.iter().all(|warning| warning.kind == WarningKind::Untrusted));
+        assert_eq!(warnings.len(), 2);
+
+        let boundary = TrustBoundary::new(&lookup, Path::new("/tmp/me/project/x.rs"));
+        assert_eq!(boundary.reject_reason(Path::new("/tmp/.editorconfig")).unwrap(),
+                   "ignored, owned by another user than the workspace");
+        assert_eq!(boundary.reject_reason(Path::new("/tmp/me/project/.editorconfig")), None);
+        // Without owners, nothing is rejected for its owner
+        let lookup = LookupOptions { trust_same_owner: true, ..LookupOptions::default() };
+        let lookup = LookupOptions { files: Arc::new(MemoryFiles::new()), ..lookup };
+        let boundary = TrustBoundary::new(&lookup, Path::new("/tmp/me/project/x.rs"));
+        assert_eq!(boundary.reject_reason(Path::new("/tmp/.editorconfig")), None);
+    }
+
+    #[test]
+    fn reads_no_config_file_above_the_trust_root() {
+        let lookup = LookupOptions { trust_root: Some(PathBuf::from("/tmp/me")), ..lookup() };
+        let properties = get_config_with(Path::new("/tmp/me/project/x.rs"), &lookup, &mut vec![])
+            .unwrap();
+        assert_eq!(properties.get("end_of_line").unwrap(), "lf");
+        assert_eq!(properties.get("indent_size").unwrap(), "2");
+        assert_eq!(properties.get("indent_style"), None);
+        let boundary = TrustBoundary::new(&lookup, Path::new("/tmp/me/project/x.rs"));
+        assert_eq!(boundary.reject_reason(Path::new("/tmp/.editorconfig")).unwrap(),
+                   "ignored, outside the trusted root /tmp/me");
+    }
+}
@@ -0,0 +1,120 @@
+//! Resolving documents known by URI, like the unsaved (`untitled:`) or remote
+//! (`vscode-vfs://`) documents of language servers, by giving them a nominal path.
+
+use std::path::PathBuf;
+
+use ordermap::OrderMap;
+
+use {get_config_with, Error, LookupOptions, Warning};
+
+/// Maps URIs to the paths their properties are looked up for.
+///
+/// `file:` URIs are mapped to the file they name. Other URIs are mapped by the longest
+/// registered prefix they start with: the rest of the URI is taken as a path relative to
+/// the directory of the prefix. E.g. with `untitled:` mapped to the workspace,
+/// `untitled:notes.md` gets the properties of `notes.md` at the root of the workspace.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UriMap {
+    prefixes: Vec<(String, PathBuf)>,
+}
+
+impl UriMap {
+    /// A map of no prefix, only mapping `file:` URIs.
+    pub fn new() -> UriMap {
+        UriMap::default()
+    }
+
+    /// Maps the URIs starting with `prefix` below `dir`.
+    pub fn insert(&mut self, prefix: &str, dir: PathBuf) {
+        self.prefixes.retain(|(existing, _)| existing != prefix);
+        self.prefixes.push((prefix.to_string(), dir));
+    }
+
+    /// Returns the nominal path of `uri`, or `None` if no prefix matches.
+    pub fn path_for(&self, uri: &str) -> Option<PathBuf> {
+        if let Some(path) = uri.strip_prefix("file://") {
+            // Skip the authority, usually empty or `localhost`
+            let path = &path[path.find('/')?..];
+            return Some(PathBuf::from(percent_decode(path)));
+        }
+        let (prefix, dir) = self.prefixes
+            .iter()
+            .filter(|&(prefix, _)| uri.starts_with(prefix.as_str()))
+            .max_by_key(|&(prefix, _)| prefix.len())?;
+        let rest = percent_decode(&uri[prefix.len()..]);
+        Some(dir.join(rest.trim_start_matches('/')))
+    }
+}
+
+/// Decodes `%XX` escapes, leaving invalid ones as they are.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = s.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) if bytes[i] == b'%' => {
+                decoded.push(byte);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Returns the properties of the document at `uri`, like `get_config_with` for its path
+/// in `map`.
+pub fn get_config_for_uri(uri: &str,
+                          map: &UriMap,
+                          lookup: &LookupOptions,
+                          warnings: &mut Vec<Warning>)
+                          -> Result<OrderMap<String, String>, Error> {
+    match map.path_for(uri) {
+        Some(path) => get_config_with(&path, lookup, warnings),
+        None => Err(Error::UnmappedUri(uri.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::Path;
+
+    #[test]
+    fn maps_uris_to_nominal_paths() {
+        let mut map = UriMap::new();
+        map.insert("untitled:", PathBuf::from("/work"));
+        map.insert("vscode-vfs://github/", PathBuf::from("/remote"));
+        map.insert("vscode-vfs://github/org/repo/", PathBuf::from("/repo"));
+        assert_eq!(map.path_for("file:///home/me/a%20b.rs"),
+                   Some(PathBuf::from("/home/me/a b.rs")));
+        assert_eq!(map.path_for("file://localhost/etc/x"), Some(PathBuf::from("/etc/x")));
+        assert_eq!(map.path_for("untitled:Untitled-1"), Some(PathBuf::from("/work/Untitled-1")));
+        assert_eq!(map.path_for("vscode-vfs://github/org/repo/src/%E2%9C%93.rs"),
+                   Some(Path::new("/repo/src").join("✓.rs")));
+        assert_eq!(map.path_for("vscode-vfs://github/other/x.md"),
+                   Some(PathBuf::from("/remote/other/x.md")));
+        assert_eq!(map.path_for("git:/x.rs"), None);
+        assert_eq!(percent_decode("100%%zz"), "100%%zz");
+    }
+
+    #[test]
+    fn resolves_untitled_documents() {
+        let mut map = UriMap::new();
+        map.insert("untitled:", env::current_dir().unwrap().join("test_files/lenient"));
+        let properties = get_config_for_uri("untitled:draft.txt",
+                                            &map,
+                                            &LookupOptions::default(),
+                                            &mut vec![])
+            .unwrap();
+        assert_eq!(properties.get("indent_style").unwrap(), "space");
+        assert!(get_config_for_uri("untitled-2:x", &map, &LookupOptions::default(), &mut vec![])
+            .is_err());
+    }
+}
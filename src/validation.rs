@@ -0,0 +1,227 @@
+//! Values of known properties that aren't allowed, like `indent_style = spaces`,
+//! misspelled keys, like `indent_stile`, and properties newer than the core of the editors,
+//! which editors ignore, leaving files without the setting.
+
+use error::Error;
+use ini::Ini;
+use registry::PropertyRegistry;
+use spec_version::SpecVersion;
+
+/// A known property whose value isn't one the specification allows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidValue {
+    pub section: Option<String>,
+    pub key: String,
+    pub value: String,
+    /// The values allowed, e.g. `tab or space`.
+    pub expected: String,
+    /// The line of the property, from 1.
+    pub line: usize,
+}
+
+/// An unknown key close to the name of a known property, see `PropertyRegistry::suggest`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MisspelledKey {
+    pub section: Option<String>,
+    pub key: String,
+    /// The known property meant, most likely.
+    pub suggestion: String,
+    /// The line of the property, from 1.
+    pub line: usize,
+}
+
+/// A known property, or value of one, added by a later version of the spec than the oldest
+/// core the editors of the project use.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NewerSetting {
+    pub section: Option<String>,
+    pub key: String,
+    pub value: String,
+    /// The version of the spec adding the property or value, see `PropertySpec::since_for`.
+    pub since: SpecVersion,
+    /// The line of the property, from 1.
+    pub line: usize,
+}
+
+/// Returns the properties of the config file `contents` whose value `registry` doesn't
+/// allow, see `PropertyRegistry::check_value`, in the order of the file.
+///
+/// ```
+/// use editorconfig::{find_invalid_values, PropertyRegistry};
+///
+/// let contents = "[*]\nindent_style = spaces\n";
+/// let invalid = find_invalid_values(contents, &PropertyRegistry::standard()).unwrap();
+/// assert_eq!((invalid[0].line, invalid[0].expected.as_str()), (2, "tab or space"));
+/// ```
+pub fn find_invalid_values(contents: &str,
+                           registry: &PropertyRegistry)
+                           -> Result<Vec<InvalidValue>, Error> {
+    let ini = Ini::load_from_str(contents)?;
+    let mut result = vec![];
+    for (section, properties) in ini.iter() {
+        for (key, value) in properties.iter() {
+            let expected = match registry.check_value(key, value) {
+                Some(expected) => expected,
+                None => continue,
+            };
+            result.push(InvalidValue {
+                section: section.clone(),
+                key: key.clone(),
+                value: value.clone(),
+                expected,
+                line: line_of(&ini, contents, section, key),
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the keys of the config file `contents` which `registry` doesn't know but which
+/// are close to a property it does, in the order of the file. Other unknown keys are left
+/// alone, as tools define their own.
+///
+/// ```
+/// use editorconfig::{find_misspelled_keys, PropertyRegistry};
+///
+/// let contents = "[*]\nindent_stile = space\nquote_type = single\n";
+/// let misspelled = find_misspelled_keys(contents, &PropertyRegistry::standard()).unwrap();
+/// assert_eq!((misspelled[0].line, misspelled[0].suggestion.as_str()), (2, "indent_style"));
+/// assert_eq!(misspelled.len(), 1);
+/// ```
+pub fn find_misspelled_keys(contents: &str,
+                            registry: &PropertyRegistry)
+                            -> Result<Vec<MisspelledKey>, Error> {
+    let ini = Ini::load_from_str(contents)?;
+    let mut result = vec![];
+    for (section, properties) in ini.iter() {
+        for (key, _) in properties.iter() {
+            if let Some(suggestion) = registry.suggest(key) {
+                result.push(MisspelledKey {
+                    section: section.clone(),
+                    key: key.clone(),
+                    suggestion: suggestion.to_string(),
+                    line: line_of(&ini, contents, section, key),
+                });
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the properties of the config file `contents`, or their values, which `registry`
+/// says a later version of the spec than `minimum` added, in the order of the file. Cores
+/// older than that ignore them. `unset` is left alone, as it only removes a value.
+///
+/// ```
+/// use editorconfig::{find_newer_settings, PropertyRegistry, SpecVersion};
+///
+/// let contents = "[*.md]\nspelling_language = en-US\nindent_style = space\n";
+/// let registry = PropertyRegistry::standard();
+/// let newer = find_newer_settings(contents, &registry, SpecVersion(0, 15, 0)).unwrap();
+/// assert_eq!((newer[0].line, newer[0].since), (2, SpecVersion(0, 16, 0)));
+/// assert_eq!(newer.len(), 1);
+/// ```
+pub fn find_newer_settings(contents: &str,
+                           registry: &PropertyRegistry,
+                           minimum: SpecVersion)
+                           -> Result<Vec<NewerSetting>, Error> {
+    let ini = Ini::load_from_str(contents)?;
+    let mut result = vec![];
+    for (section, properties) in ini.iter() {
+        for (key, value) in properties.iter() {
+            let since = match registry.get(key) {
+                Some(spec) if !value.eq_ignore_ascii_case("unset") => spec.since_for(value),
+                _ => continue,
+            };
+            if since <= minimum {
+                continue;
+            }
+            result.push(NewerSetting {
+                section: section.clone(),
+                key: key.clone(),
+                value: value.clone(),
+                since,
+                line: line_of(&ini, contents, section, key),
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// The line of the value of `key` in `section`, from 1, or 0 if it isn't there.
+fn line_of(ini: &Ini, contents: &str, section: &Option<String>, key: &str) -> usize {
+    ini.value_span(section.clone(), key)
+        .map_or(0, |span| contents[..span.start].matches('\n').count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use registry::{PropertySpec, ValueType};
+
+    #[test]
+    fn finds_values_the_spec_does_not_allow() {
+        let contents = "root = yes\n[*]\nindent_style = spaces\nindent_size = 0\n\
+                        tab_width = -4\ncharset = UTF-8\nend_of_line = unset\n\
+                        [*.md]\ncharset = utf-32\ninsert_final_newline = TRUE\n\
+                        max_line_length = off\ntrim_trailing_whitespace = 1\nx = y\n";
+        let found: Vec<_> = find_invalid_values(contents, &PropertyRegistry::standard())
+            .unwrap()
+            .into_iter()
+            .map(|invalid| (invalid.section, invalid.key, invalid.value, invalid.line))
+            .collect();
+        let section = |name: &str| Some(name.to_string());
+        assert_eq!(found,
+                   [(None, "root".to_string(), "yes".to_string(), 1),
+                    (section("*"), "indent_style".to_string(), "spaces".to_string(), 3),
+                    (section("*"), "indent_size".to_string(), "0".to_string(), 4),
+                    (section("*"), "tab_width".to_string(), "-4".to_string(), 5),
+                    (section("*.md"), "charset".to_string(), "utf-32".to_string(), 9),
+                    (section("*.md"),
+                     "trim_trailing_whitespace".to_string(),
+                     "1".to_string(),
+                     12)]);
+        assert!(find_invalid_values("[*]\n= 2\n", &PropertyRegistry::new()).is_err());
+    }
+
+    #[test]
+    fn finds_keys_close_to_known_properties() {
+        let contents = "insert_final_newlines = true\n[*]\nIndent_Stile = space\n\
+                        indent_style = space\nij_java_names = x\n";
+        let found: Vec<_> = find_misspelled_keys(contents, &PropertyRegistry::standard())
+            .unwrap()
+            .into_iter()
+            .map(|misspelled| (misspelled.key, misspelled.suggestion, misspelled.line))
+            .collect();
+        assert_eq!(found,
+                   [("insert_final_newlines".to_string(), "insert_final_newline".to_string(), 1),
+                    ("Indent_Stile".to_string(), "indent_style".to_string(), 3)]);
+        assert_eq!(find_misspelled_keys(contents, &PropertyRegistry::new()).unwrap(), []);
+    }
+
+    #[test]
+    fn finds_settings_newer_than_the_minimum_version() {
+        let mut registry = PropertyRegistry::standard();
+        registry.register(PropertySpec::new("quote_type", ValueType::Keyword)
+            .since(SpecVersion(0, 12, 0))
+            .keywords(&["single", "double"])
+            .keywords_since(SpecVersion(0, 14, 0), &["auto"]));
+        let contents = "[*]\nquote_type = single\nspelling_language = unset\n\
+                        [*.md]\nquote_type = Auto\nspelling_language = en-US\n";
+        let found = |minimum| {
+            find_newer_settings(contents, &registry, minimum)
+                .unwrap()
+                .into_iter()
+                .map(|newer| (newer.key, newer.value, newer.since, newer.line))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(found(SpecVersion(0, 12, 0)),
+                   [("quote_type".to_string(), "Auto".to_string(), SpecVersion(0, 14, 0), 5),
+                    ("spelling_language".to_string(),
+                     "en-US".to_string(),
+                     SpecVersion(0, 16, 0),
+                     6)]);
+        assert_eq!(found(SpecVersion(0, 11, 0)).len(), 3);
+        assert_eq!(found(SpecVersion(0, 16, 0)), []);
+    }
+}
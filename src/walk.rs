@@ -0,0 +1,282 @@
+//! Recursive directory walking for operations spanning a whole tree.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use environment::GitSparseCheckout;
+use {Warning, WarningKind};
+
+/// Names of the directories version control systems keep their data in, which `walk_files`
+/// never enters.
+pub const VCS_DIRS: &[&str] = &[".git", ".hg", ".svn", ".bzr", "_darcs", "CVS"];
+
+/// Options controlling how `walk_files` traverses a tree.
+#[derive(Clone, Debug)]
+pub struct WalkOptions {
+    /// Descend into symlinked directories. Symlinked files are always listed.
+    pub follow_symlinks: bool,
+    /// Visit every directory at most once, even when it's reachable through several
+    /// paths (symlinks, bind mounts). Symlink loops are cut regardless of this setting.
+    pub dedup_visited: bool,
+    /// Skip the directories below the root which can't be listed instead of failing, like
+    /// `LookupOptions::skip_unreadable` does for config files. Every skipped directory is
+    /// reported as a `WarningKind::Unreadable` warning.
+    pub skip_unreadable: bool,
+    /// Leave out the files of a git sparse checkout its patterns exclude, which can be on
+    /// disk without being checked out or staged, see `GitSparseCheckout`. Outside of cone
+    /// mode, untracked files are kept, as only the index says what the patterns exclude.
+    pub sparse_checkout: bool,
+}
+
+impl Default for WalkOptions {
+    fn default() -> WalkOptions {
+        WalkOptions {
+            follow_symlinks: false,
+            dedup_visited: true,
+            skip_unreadable: false,
+            sparse_checkout: false,
+        }
+    }
+}
+
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+#[cfg(unix)]
+fn dir_id(path: &Path) -> io::Result<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = fs::metadata(path)?;
+    Ok((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_id(path: &Path) -> io::Result<DirId> {
+    path.canonicalize()
+}
+
+/// Lists every file below `root`, sorted by path.
+///
+/// Only files present on disk are listed, so paths left out of a git sparse checkout
+/// (or not yet fetched in a partial clone) are never reported, and with
+/// `options.sparse_checkout` neither are those left behind on disk. The directories of
+/// `VCS_DIRS` are left out too.
+///
+/// A directory is never entered again from inside itself, so looping symlinks are
+/// safe to follow; `options.dedup_visited` extends this to the whole walk.
+///
+/// Failing to list `root` is an error. Directories below it which can't be listed are
+/// too, unless `options.skip_unreadable` is set: they're appended to `warnings` instead.
+pub fn walk_files(root: &Path,
+                  options: &WalkOptions,
+                  warnings: &mut Vec<Warning>)
+                  -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut visited = HashSet::new();
+    walk_dir(root, options, &mut visited, &mut vec![], &mut files, warnings)?;
+    if options.sparse_checkout {
+        if let Some(sparse) = GitSparseCheckout::read(root) {
+            files.retain(|file| !sparse.excludes(file));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Walks `dir`, a directory below the root, skipping it with a warning if it can't be
+/// listed and `options` allow it.
+fn walk_subdir(dir: &Path,
+               options: &WalkOptions,
+               visited: &mut HashSet<DirId>,
+               ancestors: &mut Vec<DirId>,
+               files: &mut Vec<PathBuf>,
+               warnings: &mut Vec<Warning>)
+               -> io::Result<()> {
+    match walk_dir(dir, options, visited, ancestors, files, warnings) {
+        Err(ref err) if options.skip_unreadable => {
+            warnings.push(Warning::new(dir, WarningKind::Unreadable, format!("skipped, {}", err)));
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn walk_dir(dir: &Path,
+            options: &WalkOptions,
+            visited: &mut HashSet<DirId>,
+            ancestors: &mut Vec<DirId>,
+            files: &mut Vec<PathBuf>,
+            warnings: &mut Vec<Warning>)
+            -> io::Result<()> {
+    let id = dir_id(dir)?;
+    if ancestors.contains(&id) {
+        return Ok(());
+    }
+    if options.dedup_visited && visited.contains(&id) {
+        return Ok(());
+    }
+    // Listed before entering it, so that a directory which can't be listed leaves no trace
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    ancestors.push(id);
+    // Sort so that the first path reaching a directory is the same on every run
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        let is_vcs_dir = entry.file_name().to_str().is_some_and(|name| VCS_DIRS.contains(&name));
+        if file_type.is_dir() {
+            if !is_vcs_dir {
+                walk_subdir(&path, options, visited, ancestors, files, warnings)?;
+            }
+        } else if file_type.is_symlink() {
+            match fs::metadata(&path) {
+                Ok(ref meta) if meta.is_dir() => {
+                    if options.follow_symlinks && !is_vcs_dir {
+                        walk_subdir(&path, options, visited, ancestors, files, warnings)?;
+                    }
+                }
+                Ok(_) => files.push(path),
+                // Dangling symlink
+                Err(_) => {}
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    let id = ancestors.pop().unwrap();
+    if options.dedup_visited {
+        visited.insert(id);
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    fn fixture(name: &str) -> PathBuf {
+        let dir = ::std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        fs::write(dir.join("a/b/file.txt"), "").unwrap();
+        fs::write(dir.join("top.txt"), "").unwrap();
+        // a/b/loop -> a, which would recurse forever when followed naively
+        symlink(dir.join("a"), dir.join("a/b/loop")).unwrap();
+        // c -> a/b, which makes a/b reachable twice
+        symlink(dir.join("a/b"), dir.join("c")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn does_not_follow_symlinks_by_default() {
+        let dir = fixture("editorconfig-walk-nofollow");
+        let files = walk_files(&dir, &WalkOptions::default(), &mut vec![]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files, vec![dir.join("a/b/file.txt"), dir.join("top.txt")]);
+    }
+
+    #[test]
+    fn follows_looped_symlinks_without_duplicates() {
+        let dir = fixture("editorconfig-walk-dedup");
+        let options = WalkOptions { follow_symlinks: true, ..WalkOptions::default() };
+        let files = walk_files(&dir, &options, &mut vec![]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files, vec![dir.join("a/b/file.txt"), dir.join("top.txt")]);
+    }
+
+    #[test]
+    fn follows_looped_symlinks_without_dedup() {
+        let dir = fixture("editorconfig-walk-no-dedup");
+        let options = WalkOptions {
+            follow_symlinks: true,
+            dedup_visited: false,
+            ..WalkOptions::default()
+        };
+        let files = walk_files(&dir, &options, &mut vec![]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files,
+                   vec![dir.join("a/b/file.txt"), dir.join("c/file.txt"), dir.join("top.txt")]);
+    }
+
+    #[test]
+    fn skips_vcs_directories() {
+        let dir = fixture("editorconfig-walk-vcs");
+        fs::create_dir_all(dir.join("a/.git/objects")).unwrap();
+        fs::write(dir.join("a/.git/objects/pack"), "").unwrap();
+        fs::write(dir.join(".hg"), "").unwrap();
+        let files = walk_files(&dir, &WalkOptions::default(), &mut vec![]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        // Only directories are skipped
+        assert_eq!(files, vec![dir.join(".hg"), dir.join("a/b/file.txt"), dir.join("top.txt")]);
+    }
+
+    #[test]
+    fn skips_unreadable_directories_with_a_warning() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = fixture("editorconfig-walk-unreadable");
+        let locked = dir.join("locked");
+        fs::create_dir_all(&locked).unwrap();
+        fs::write(locked.join("hidden.txt"), "").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        let readable = fs::read_dir(&locked).is_ok();
+
+        let mut warnings = vec![];
+        let options = WalkOptions { skip_unreadable: true, ..WalkOptions::default() };
+        let files = walk_files(&dir, &options, &mut warnings);
+        let strict = walk_files(&dir, &WalkOptions::default(), &mut vec![]);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Permissions don't apply to root, so there is nothing to skip.
+        if readable {
+            return;
+        }
+        assert_eq!(files.unwrap(), vec![dir.join("a/b/file.txt"), dir.join("top.txt")]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::Unreadable);
+        assert_eq!(warnings[0].file, locked);
+        assert!(strict.is_err());
+    }
+
+    #[test]
+    fn leaves_out_files_a_sparse_checkout_excludes() {
+        use std::process::Command;
+
+        let dir = fixture("editorconfig-walk-sparse");
+        fs::create_dir_all(dir.join("d")).unwrap();
+        fs::write(dir.join("d/tracked.txt"), "").unwrap();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(&dir)
+                .args(args)
+                .output()
+                .is_ok_and(|output| output.status.success())
+        };
+        if !git(&["init", "-q"]) || !git(&["add", "a/b/file.txt", "d", "top.txt"]) ||
+           !git(&["sparse-checkout", "set", "--cone", "a/b"]) {
+            // No git to check against
+            fs::remove_dir_all(&dir).unwrap();
+            return;
+        }
+        // Left behind, like build outputs
+        fs::create_dir_all(dir.join("d")).unwrap();
+        fs::write(dir.join("d/tracked.txt"), "").unwrap();
+        fs::write(dir.join("d/untracked.txt"), "").unwrap();
+        fs::write(dir.join("a/new.txt"), "").unwrap();
+        let sparse = WalkOptions { sparse_checkout: true, ..WalkOptions::default() };
+        let files = walk_files(&dir, &sparse, &mut vec![]).unwrap();
+        let all = walk_files(&dir, &WalkOptions::default(), &mut vec![]).unwrap();
+        let below = walk_files(&dir.join("d"), &sparse, &mut vec![]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(files,
+                   vec![dir.join("a/b/file.txt"), dir.join("a/new.txt"), dir.join("top.txt")]);
+        assert_eq!(all.len(), 5);
+        assert_eq!(below, Vec::<PathBuf>::new());
+    }
+}
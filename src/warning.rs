@@ -0,0 +1,135 @@
+//! Non-fatal problems noticed while looking up a configuration.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use ordermap::OrderMap;
+
+/// What a `Warning` is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// A file could not be read (e.g. because of its permissions) and was skipped.
+    Unreadable,
+    /// A config file was ignored because it's outside the trust boundary of the lookup.
+    Untrusted,
+    /// A value was only understood by reading it leniently, e.g. `4ch` as `4`.
+    LenientValue,
+    /// A section name couldn't be used as a pattern and was compared to paths as is.
+    InvalidPattern,
+    /// A malformed line of a config file was repaired or dropped, see `repair`.
+    Repaired,
+    /// A config file was read although its name differs in case from the expected one.
+    MiscasedName,
+    /// A section or property was ignored for being longer than the limits of the lookup,
+    /// see `LookupOptions::max_key_length`.
+    TooLong,
+}
+
+/// A problem that didn't stop the lookup but that the user probably wants to hear about.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    /// The file the warning is about.
+    pub file: PathBuf,
+    /// What went wrong.
+    pub kind: WarningKind,
+    /// A human readable description of the problem.
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new<P: Into<PathBuf>, S: Into<String>>(file: P, kind: WarningKind, message: S) -> Warning {
+        Warning {
+            file: file.into(),
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.file.display(), self.message)
+    }
+}
+
+/// The file, kind and message of a warning, telling apart the warnings of `WarningAggregator`.
+type WarningKey = (PathBuf, WarningKind, String);
+
+/// Collects the warnings of many lookups, keeping each distinct warning only once.
+///
+/// Looking up thousands of files below the same broken config would otherwise report the
+/// same problem thousands of times. Warnings are the same when their file, kind and message
+/// are, so different problems in one file are all kept. `add` only needs a shared reference,
+/// so the aggregator can be shared between threads.
+#[derive(Debug, Default)]
+pub struct WarningAggregator {
+    warnings: Mutex<OrderMap<WarningKey, (Warning, HashSet<PathBuf>)>>,
+}
+
+impl WarningAggregator {
+    pub fn new() -> WarningAggregator {
+        WarningAggregator::default()
+    }
+
+    /// Records `warning`, noticed while looking up `target`, or adds `target` to the targets
+    /// of the identical warning seen before.
+    pub fn add(&self, target: &Path, warning: Warning) {
+        let mut warnings = self.warnings.lock().unwrap();
+        let key = (warning.file.clone(), warning.kind, warning.message.clone());
+        warnings.entry(key)
+            .or_insert_with(|| (warning, HashSet::new()))
+            .1
+            .insert(target.to_path_buf());
+    }
+
+    /// Returns every distinct warning together with the number of distinct targets it was
+    /// added for, in the order they were first seen.
+    pub fn into_counts(self) -> Vec<(Warning, usize)> {
+        let warnings = self.warnings.into_inner().unwrap();
+        warnings.into_iter().map(|(_, (warning, targets))| (warning, targets.len())).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deduplicates_by_file_kind_and_message() {
+        let aggregator = WarningAggregator::new();
+        for target in &["/a/x.txt", "/a/y.txt", "/a/z.txt"] {
+            aggregator.add(Path::new(target),
+                           Warning::new("/a/.editorconfig", WarningKind::Unreadable, "skipped"));
+        }
+        // The same target twice still counts once
+        aggregator.add(Path::new("/a/x.txt"),
+                       Warning::new("/a/.editorconfig", WarningKind::Unreadable, "skipped"));
+        aggregator.add(Path::new("/b/x.txt"),
+                       Warning::new("/b/.editorconfig", WarningKind::Unreadable, "skipped"));
+
+        let counts = aggregator.into_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].0.file, PathBuf::from("/a/.editorconfig"));
+        assert_eq!(counts[0].1, 3);
+        assert_eq!(counts[1].0.file, PathBuf::from("/b/.editorconfig"));
+        assert_eq!(counts[1].1, 1);
+    }
+
+    #[test]
+    fn keeps_different_messages_about_one_file() {
+        let aggregator = WarningAggregator::new();
+        let target = Path::new("/a/file.txt");
+        for message in &["[(] is invalid", "[{1..3}(] is invalid"] {
+            aggregator.add(target,
+                           Warning::new("/a/.editorconfig", WarningKind::InvalidPattern, *message));
+        }
+
+        let counts = aggregator.into_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].0.message, "[(] is invalid");
+        assert_eq!(counts[1].0.message, "[{1..3}(] is invalid");
+        assert!(counts.iter().all(|&(_, count)| count == 1));
+    }
+}
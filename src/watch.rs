@@ -0,0 +1,179 @@
+//! Notices when the config files lookups found change, for hosts keeping properties around,
+//! like language servers. Enabled by the `watch` feature.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use {find_all_configs_with, Error, FileProvider, FileStamp, LookupOptions, RealFiles,
+     ResolveObserver, WalkOptions};
+
+/// Watches config files on a thread of its own, checking their modification time and size
+/// every `interval`, and calls back with every file that changed, appeared or disappeared.
+/// The thread stops when the watcher is dropped.
+///
+/// Files are added with `watch` or, to watch every config file lookups find, by setting
+/// `observer()` as `LookupOptions::observer`:
+///
+/// ```text
+/// let resolver = Resolver::builder().build();
+/// let watcher = Watcher::new(Duration::from_secs(1), move |conf_file| {
+///     resolver.invalidate(conf_file);
+/// });
+/// let lookup = LookupOptions { observer: Some(watcher.observer()), ..LookupOptions::default() };
+/// ```
+#[derive(Debug)]
+pub struct Watcher {
+    state: Arc<WatchState>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, Default)]
+struct WatchState {
+    /// The stamp of each watched file when it was last checked, `None` if it didn't exist.
+    files: Mutex<HashMap<PathBuf, Option<FileStamp>>>,
+    stopped: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl WatchState {
+    fn watch(&self, conf_file: &Path) {
+        let mut files = self.files.lock().unwrap();
+        if !files.contains_key(conf_file) {
+            files.insert(conf_file.to_path_buf(), RealFiles.stamp(conf_file));
+        }
+    }
+
+    fn poll(&self) -> Vec<PathBuf> {
+        let mut changed = vec![];
+        for (path, stamp) in self.files.lock().unwrap().iter_mut() {
+            let current = RealFiles.stamp(path);
+            if current != *stamp {
+                *stamp = current;
+                changed.push(path.clone());
+            }
+        }
+        changed.sort();
+        changed
+    }
+}
+
+impl Watcher {
+    /// Starts checking the watched files, none yet, every `interval`. `on_change` runs on
+    /// the thread of the watcher.
+    pub fn new<F>(interval: Duration, on_change: F) -> Watcher
+        where F: Fn(&Path) + Send + 'static
+    {
+        let state = Arc::new(WatchState::default());
+        let thread_state = state.clone();
+        let thread = thread::spawn(move || loop {
+            let stopped = thread_state.stopped.lock().unwrap();
+            let (stopped, _) = thread_state.wake
+                .wait_timeout_while(stopped, interval, |stopped| !*stopped)
+                .unwrap();
+            if *stopped {
+                return;
+            }
+            drop(stopped);
+            for path in thread_state.poll() {
+                on_change(&path);
+            }
+        });
+        Watcher {
+            state,
+            thread: Some(thread),
+        }
+    }
+
+    /// Watches `conf_file` too, whether or not it exists.
+    pub fn watch<P: AsRef<Path>>(&self, conf_file: P) {
+        self.state.watch(conf_file.as_ref());
+    }
+
+    /// Watches the config files governing the files below `root`, see
+    /// `find_all_configs_with`. Config files added later aren't watched.
+    pub fn watch_tree(&self, root: &Path, lookup: &LookupOptions) -> Result<(), Error> {
+        for info in find_all_configs_with(root, lookup, &WalkOptions::default(), &mut vec![])? {
+            self.state.watch(&info.path);
+        }
+        Ok(())
+    }
+
+    /// Stops watching `conf_file`, returning whether it was watched.
+    pub fn unwatch<P: AsRef<Path>>(&self, conf_file: P) -> bool {
+        self.state.files.lock().unwrap().remove(conf_file.as_ref()).is_some()
+    }
+
+    /// The watched files, sorted.
+    pub fn watched(&self) -> Vec<PathBuf> {
+        let mut files: Vec<_> = self.state.files.lock().unwrap().keys().cloned().collect();
+        files.sort();
+        files
+    }
+
+    /// Checks the watched files now, returning the ones that changed since they were last
+    /// checked, without calling back.
+    pub fn poll(&self) -> Vec<PathBuf> {
+        self.state.poll()
+    }
+
+    /// An observer watching every config file lookups find.
+    pub fn observer(&self) -> Arc<dyn ResolveObserver> {
+        Arc::new(WatchObserver(self.state.clone()))
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        *self.state.stopped.lock().unwrap() = true;
+        self.state.wake.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct WatchObserver(Arc<WatchState>);
+
+impl ResolveObserver for WatchObserver {
+    fn config_discovered(&self, conf_file: &Path) {
+        self.0.watch(conf_file);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::mpsc;
+    use test_support::FixtureBuilder;
+    use {get_config_with, LookupOptions};
+
+    #[test]
+    fn calls_back_when_config_files_found_change() {
+        let fixture = FixtureBuilder::new()
+            .config(".editorconfig", "root = true\n[*]\nindent_size = 2\n")
+            .file("src/lib.rs")
+            .build()
+            .unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let watcher = Watcher::new(Duration::from_millis(10), move |conf_file| {
+            let _ = sender.send(conf_file.to_path_buf());
+        });
+        let lookup = LookupOptions {
+            observer: Some(watcher.observer()),
+            ..fixture.lookup_options()
+        };
+        get_config_with(&fixture.path("src/lib.rs"), &lookup, &mut vec![]).unwrap();
+        let conf_file = fixture.path(".editorconfig");
+        assert_eq!(watcher.watched(), vec![conf_file.clone()]);
+        assert!(watcher.poll().is_empty());
+
+        fs::write(&conf_file, "root = true\n[*]\nindent_size = 4\ntab_width = 8\n").unwrap();
+        assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), conf_file);
+        assert!(watcher.unwatch(&conf_file));
+        assert!(watcher.watched().is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+//! Runs the `editorconfig` binary against the fixtures of `test_files` and compares its exit
+//! code and output with the golden files of `tests/golden`, which make the output formats
+//! part of the contract. Run with `UPDATE_GOLDEN=1` to rewrite the golden files after a
+//! deliberate change, and review the diff.
+//!
+//! `{root}` in arguments stands for the root of the crate, and paths below it are written
+//! back as `{root}` in the golden files so that they don't depend on the checkout. The binary
+//! is run from the root, with every subcommand. The features it was compiled with are left
+//! out, so that the golden files hold whatever features the tests are run with.
+
+extern crate editorconfig;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const CASES: &[(&str, &[&str])] = &[
+    ("resolve", &["{root}/test_files/simple/file.txt"]),
+    ("resolve-many-relative",
+     &["--paths", "relative", "{root}/test_files/simple/file.txt",
+       "{root}/test_files/multi_level/foo/bar/file.txt"]),
+    ("resolve-overrides", &["--override", ".editorconfig.user",
+                            "{root}/test_files/overrides/foo/file.txt"]),
+    ("resolve-lenient", &["--lenient-values", "{root}/test_files/lenient/file.txt"]),
+    ("resolve-malformed", &["{root}/test_files/malformed/file.md"]),
+    ("resolve-repair", &["--repair", "{root}/test_files/malformed/file.md"]),
+    ("resolve-partial-failure",
+     &["--paths", "relative", "{root}/test_files/simple/file.txt",
+       "{root}/test_files/malformed/file.md", "{root}/test_files/multi_level/foo/bar/file.txt"]),
+    ("resolve-json",
+     &["--format", "json", "--paths", "relative", "{root}/test_files/simple/file.txt",
+       "{root}/test_files/malformed/file.md"]),
+    ("resolve-json-map",
+     &["--format", "json-map", "--paths", "relative", "{root}/test_files/simple/file.txt",
+       "{root}/test_files/malformed/file.md", "{root}/test_files/multi_level/foo/bar/file.txt"]),
+    ("resolve-any-case", &["--conf-any-case", "{root}/test_files/miscased/file.txt"]),
+    ("resolve-version-0.8", &["-b", "0.8.0", "{root}/test_files/simple/file.txt"]),
+    ("version", &["-v"]),
+    ("explain", &["--explain", "EC201"]),
+    ("explain-unknown", &["--explain", "EC999"]),
+    ("bad-arguments", &["--paths", "sideways"]),
+    ("audit", &["audit", "--paths", "relative", "{root}/test_files/overrides/foo/file.txt"]),
+    ("check-needs-a-check", &["check", "test_files/simple/file.txt"]),
+    ("check-redundant",
+     &["check", "--redundant", "--paths", "relative", "test_files/redundant/pkg/main.py"]),
+    ("check-redundant-by-rule",
+     &["check", "--redundant", "--group-by", "rule", "test_files/redundant/pkg/main.py"]),
+    ("check-redundant-by-file",
+     &["check", "--redundant", "--group-by", "file", "--paths", "relative",
+       "test_files/redundant/pkg/main.py"]),
+    ("lint-all", &["lint", "--all", "--paths", "relative", "test_files/redundant"]),
+    ("lint-sarif",
+     &["lint", "--format", "sarif", "--paths", "relative", "test_files/inline_comments/file.txt"]),
+    ("lint-inline-comments",
+     &["lint", "--paths", "relative", "test_files/inline_comments/file.txt"]),
+    ("doctor", &["doctor", "--paths", "relative", "test_files/doctor"]),
+    ("export", &["export", "--paths", "relative", "--for", "test_files/overrides/foo/file.txt"]),
+    ("export-needs-for", &["export"]),
+    ("manifest", &["manifest", "test_files/lenient"]),
+    ("init-needs-a-mode", &["init"]),
+];
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).canonicalize().unwrap()
+}
+
+/// Runs the binary and renders what it did like the golden files.
+fn run(args: &[&str]) -> String {
+    let root = root();
+    let root = root.to_str().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_editorconfig"))
+        .args(args.iter().map(|arg| arg.replace("{root}", root)))
+        .current_dir(root)
+        .output()
+        .unwrap();
+    let rendered = format!("exit: {}\n--- stdout\n{}--- stderr\n{}",
+                           output.status.code().unwrap_or(-1),
+                           String::from_utf8_lossy(&output.stdout),
+                           String::from_utf8_lossy(&output.stderr));
+    let features: String = editorconfig::active_features()
+        .iter()
+        .map(|feature| format!("  {}\n", feature))
+        .collect();
+    // Argument errors show the binary as started, which depends on the target directory
+    rendered.replace(env!("CARGO_BIN_EXE_editorconfig"), "editorconfig")
+        .replace(root, "{root}")
+        .replace(&format!("compiled-in features:\n{}", features), "compiled-in features:\n")
+}
+
+#[test]
+fn matches_golden_output() {
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+    let dir = root().join("tests/golden");
+    let mut mismatches = vec![];
+    for &(name, args) in CASES {
+        let actual = run(args);
+        let path = dir.join(format!("{}.txt", name));
+        if update {
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(&path, &actual).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_default();
+        if actual != expected {
+            mismatches.push(format!("{} ({:?}):\n--- expected\n{}--- actual\n{}",
+                                    name,
+                                    args,
+                                    expected,
+                                    actual));
+        }
+    }
+    assert!(mismatches.is_empty(),
+            "output differs from tests/golden, rerun with UPDATE_GOLDEN=1 if that's \
+             intended:\n\n{}",
+            mismatches.join("\n"));
+}
+
+#[test]
+fn every_golden_file_has_a_case() {
+    let dir = root().join("tests/golden");
+    for entry in fs::read_dir(dir).unwrap() {
+        let name = entry.unwrap().file_name().into_string().unwrap();
+        assert!(CASES.iter().any(|&(case, _)| format!("{}.txt", case) == name),
+                "tests/golden/{} has no case",
+                name);
+    }
+}
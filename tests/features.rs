@@ -0,0 +1,90 @@
+//! Checks that the crate builds under every meaningful combination of its features: none,
+//! each on its own and all together, so code behind a feature that the default test run
+//! doesn't enable can't rot unnoticed. The features are read from `Cargo.toml`, new ones are
+//! picked up without changing this test.
+//!
+//! Each combination runs `cargo check` on all targets in `target/feature-matrix`, which takes
+//! a while the first time, so the test is ignored by default and CI runs it in a step of its
+//! own, see `.travis.yml`. Run it with `cargo test --test features -- --ignored`.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// The names of the features declared in the `[features]` table of `manifest`.
+fn features(manifest: &str) -> Vec<String> {
+    let mut features = vec![];
+    let mut in_features = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_features = line == "[features]";
+            continue;
+        }
+        if !in_features || line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.split('=').next() {
+            let name = name.trim();
+            if name != "default" {
+                features.push(name.to_string());
+            }
+        }
+    }
+    features
+}
+
+/// No feature, each feature alone, then all of them.
+fn combinations(features: &[String]) -> Vec<Vec<String>> {
+    let mut combinations = vec![vec![]];
+    combinations.extend(features.iter().map(|feature| vec![feature.clone()]));
+    if features.len() > 1 {
+        combinations.push(features.to_vec());
+    }
+    combinations
+}
+
+fn check(root: &Path, features: &[String]) -> Result<(), String> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let output = Command::new(cargo)
+        .current_dir(root)
+        .args(["check", "--quiet", "--all-targets", "--no-default-features"])
+        .args(["--features", &features.join(",")])
+        .arg("--target-dir")
+        .arg(root.join("target/feature-matrix"))
+        .output()
+        .map_err(|err| err.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+}
+
+#[test]
+#[ignore]
+fn builds_under_every_feature_combination() {
+    let root = root();
+    let manifest = fs::read_to_string(root.join("Cargo.toml")).unwrap();
+    let mut failures = vec![];
+    for features in combinations(&features(&manifest)) {
+        if let Err(message) = check(&root, &features) {
+            failures.push(format!("features [{}]:\n{}", features.join(", "), message));
+        }
+    }
+    assert!(failures.is_empty(), "the crate doesn't build with\n\n{}", failures.join("\n\n"));
+}
+
+#[test]
+fn reads_features_from_the_manifest() {
+    let manifest = "[dependencies]\nregex = \"0.2\"\n\n[features]\ndefault = [\"watch\"]\n\
+                    # A comment\nembed = [\"macros\"]\nwatch = []\n\n[workspace]\nmembers = []\n";
+    let features = features(manifest);
+    assert_eq!(features, ["embed", "watch"]);
+    assert_eq!(combinations(&features),
+               [vec![], vec!["embed"], vec!["watch"], vec!["embed", "watch"]]);
+}
@@ -0,0 +1,258 @@
+//! Compares the public items of the crate with the snapshot in `tests/public_api.txt`, so
+//! changes breaking downstream users between minor versions don't go unnoticed. Run with
+//! `UPDATE_PUBLIC_API=1` to rewrite the snapshot after a deliberate change, and review the
+//! diff: removed or changed lines break users, added ones only grow the API.
+//!
+//! Items are read from the sources rather than from rustdoc JSON, which needs a nightly
+//! toolchain: every `pub` item, field, enum variant and trait method outside of tests, with
+//! the `impl`, struct, enum or trait it's in. Which items users can reach is taken from
+//! `lib.rs`: all those of `lib.rs` and of its `pub mod`s, and of the other modules only the
+//! items its `pub use`s re-export, along with their fields, variants and methods.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Whitespace collapsed to single spaces.
+fn normalize(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    text.replace("( ", "(").replace(" )", ")").replace("< ", "<").replace(", >", ">")
+}
+
+/// The statement starting at `lines[start]`, up to its body, value or end.
+fn statement(lines: &[&str], start: usize) -> (String, usize) {
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        // Comments after the item aren't part of it
+        let line = line.split(" //").next().unwrap_or("");
+        text.push_str(line);
+        text.push('\n');
+        // Re-exports list what they export between braces
+        if text.trim_start().starts_with("pub use ") {
+            if let Some(end) = text.find(';') {
+                return (normalize(&text[..end]), i);
+            }
+            continue;
+        }
+        let end = [" {", "\n{", " =", ";"].iter().filter_map(|end| text.find(end)).min();
+        if let Some(end) = end {
+            return (normalize(&text[..end]), i);
+        }
+        if line.trim_end().ends_with(',') && !text.contains('(') {
+            return (normalize(text.trim_end().trim_end_matches(',')), i);
+        }
+        if text.contains('(') && text.matches('(').count() == text.matches(')').count() &&
+           line.trim_end().ends_with(',') {
+            return (normalize(text.trim_end().trim_end_matches(',')), i);
+        }
+    }
+    (normalize(&text), lines.len() - 1)
+}
+
+/// The public items of the source file `contents`, one per line.
+fn public_items(contents: &str) -> Vec<String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut items = vec![];
+    // The item a block at the top level opens, and whether it's a public enum or trait
+    let mut context: Option<(String, bool, bool)> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let tests = lines.get(i + 1).is_some_and(|next| next.starts_with("mod tests"));
+        if line == "#[cfg(test)]" && tests {
+            break;
+        }
+        if line == "}" {
+            context = None;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim_start();
+        let opens_block = indent == 0 &&
+                          ["impl", "pub struct", "pub enum", "pub trait", "unsafe impl"]
+                              .iter()
+                              .any(|start| trimmed.starts_with(start));
+        if opens_block {
+            let (text, end) = statement(&lines, i);
+            if trimmed.starts_with("pub") {
+                items.push(text.clone());
+            }
+            let block = lines[i..=end].iter().any(|l| l.trim_end().ends_with('{'));
+            if block {
+                context = Some((text.clone(),
+                                trimmed.starts_with("pub enum"),
+                                trimmed.starts_with("pub trait")));
+            }
+            i = end + 1;
+            continue;
+        }
+        let item = match context {
+            Some((_, true, _)) if indent == 4 &&
+                                  trimmed.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                let (text, end) = statement(&lines, i);
+                let text = text.split(['(', '{']).next().unwrap_or("").trim().to_string();
+                Some((text, end))
+            }
+            Some((_, _, true)) if indent == 4 && trimmed.starts_with("fn ") => {
+                Some(statement(&lines, i))
+            }
+            _ if trimmed.starts_with("pub ") && (indent == 0 || context.is_some()) => {
+                Some(statement(&lines, i))
+            }
+            _ => None,
+        };
+        match item {
+            Some((text, end)) => {
+                match context {
+                    Some((ref block, _, _)) => items.push(format!("{} :: {}", block, text)),
+                    None => items.push(text),
+                }
+                i = end + 1;
+            }
+            None => i += 1,
+        }
+    }
+    items
+}
+
+/// The modules `lib_items`, the public items of `lib.rs`, declare with `pub mod`, and the
+/// names it re-exports from each of the others with `pub use`.
+fn exports(lib_items: &[String]) -> (Vec<String>, HashMap<String, Vec<String>>) {
+    let mut public_modules = vec![];
+    let mut reexports: HashMap<String, Vec<String>> = HashMap::new();
+    for item in lib_items {
+        if let Some(module) = item.strip_prefix("pub mod ") {
+            public_modules.push(module.to_string());
+        } else if let Some(path) = item.strip_prefix("pub use ") {
+            let (module, names) = path.split_once("::").unwrap_or(("", path));
+            let names = names.trim_start_matches('{').trim_end_matches('}');
+            // `a as b` makes `a` public all the same
+            let names = names.split(',').filter_map(|name| name.split_whitespace().next());
+            reexports.entry(module.to_string())
+                .or_default()
+                .extend(names.map(str::to_string));
+        }
+    }
+    (public_modules, reexports)
+}
+
+/// The name of the item `item` is, or of the type or trait it's a member of: `Options` for
+/// both `pub struct Options` and `impl Options :: pub fn new() -> Options`.
+fn owner(item: &str) -> &str {
+    let item = item.split(" :: ").next().unwrap_or(item);
+    let name = if let Some(implemented) = item.strip_prefix("impl")
+        .or_else(|| item.strip_prefix("unsafe impl")) {
+        let implemented = match implemented.split_once(" for ") {
+            Some((_, implementor)) => implementor,
+            // Skips the generics of the impl
+            None => implemented.split_once("> ").map_or(implemented, |(_, rest)| rest),
+        };
+        implemented.trim_start()
+    } else {
+        let keywords = ["pub", "unsafe", "fn", "struct", "enum", "trait", "const", "static", "type"];
+        item.split_whitespace().find(|word| !keywords.contains(word)).unwrap_or("")
+    };
+    let end = name.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(name.len());
+    &name[..end]
+}
+
+fn source_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// The snapshot of the public API: the items users can reach of every source file, by file.
+fn snapshot() -> String {
+    let root = root();
+    let lib_items = public_items(&fs::read_to_string(root.join("src/lib.rs")).unwrap());
+    let (public_modules, reexports) = exports(&lib_items);
+    let mut snapshot = String::new();
+    for path in source_files(&root.join("src")) {
+        let module = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let mut items = public_items(&fs::read_to_string(&path).unwrap());
+        if module != "lib" && !public_modules.contains(&module) {
+            let names = reexports.get(&module).cloned().unwrap_or_default();
+            items.retain(|item| names.iter().any(|name| name == owner(item)));
+        }
+        if items.is_empty() {
+            continue;
+        }
+        let name = path.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/");
+        snapshot.push_str(&format!("# {}\n", name));
+        for item in items {
+            snapshot.push_str(&item);
+            snapshot.push('\n');
+        }
+        snapshot.push('\n');
+    }
+    snapshot
+}
+
+#[test]
+fn public_api_matches_the_snapshot() {
+    let path = root().join("tests/public_api.txt");
+    let actual = snapshot();
+    if env::var_os("UPDATE_PUBLIC_API").is_some() {
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_default();
+    let missing_from = |text: &str, line: &str| !text.lines().any(|l| l == line);
+    let removed: Vec<_> = expected.lines().filter(|line| missing_from(&actual, line)).collect();
+    let added: Vec<_> = actual.lines().filter(|line| missing_from(&expected, line)).collect();
+    assert!(removed.is_empty() && added.is_empty(),
+            "the public API differs from tests/public_api.txt, rerun with UPDATE_PUBLIC_API=1 \
+             if that's intended\n\nremoved or changed:\n{}\n\nadded:\n{}",
+            removed.join("\n"),
+            added.join("\n"));
+}
+
+#[test]
+fn reads_items_with_their_context() {
+    let source = "pub struct Options {\n    pub name: String,\n    hidden: bool,\n}\n\n\
+                  impl Options {\n    pub fn new(name: &str,\n               hidden: bool)\n  \
+                  -> Options {\n        Options { name, hidden }\n    }\n\n    fn private() {}\n\
+                  }\n\npub enum Kind {\n    /// Doc\n    A,\n    B(u32),\n}\n\n\
+                  pub const LIMIT: usize = 10;\npub use a::{b,\n            c};\n\n\
+                  #[cfg(test)]\nmod tests {\n    pub fn x() {}\n}\n";
+    assert_eq!(public_items(source),
+               ["pub struct Options",
+                "pub struct Options :: pub name: String",
+                "impl Options :: pub fn new(name: &str, hidden: bool) -> Options",
+                "pub enum Kind",
+                "pub enum Kind :: A",
+                "pub enum Kind :: B",
+                "pub const LIMIT: usize",
+                "pub use a::{b, c}"]);
+}
+
+#[test]
+fn tells_what_lib_rs_exports() {
+    let lib_items: Vec<_> = ["pub mod cli", "pub use a::{b, C}", "pub use d::E", "pub fn f()"]
+        .iter()
+        .map(|item| item.to_string())
+        .collect();
+    let (public_modules, reexports) = exports(&lib_items);
+    assert_eq!(public_modules, ["cli"]);
+    assert_eq!(reexports["a"], ["b", "C"]);
+    assert_eq!(reexports["d"], ["E"]);
+    for &(item, name) in &[("pub fn classify(path: &Path) -> FileClass", "classify"),
+                           ("pub const LIMIT: usize", "LIMIT"),
+                           ("pub enum Kind :: B", "Kind"),
+                           ("impl Options :: pub fn new() -> Options", "Options"),
+                           ("impl<'a> Reporter for TextReporter<'a> :: fn x()", "TextReporter"),
+                           ("impl<T: Write> JsonReporter<T> :: pub fn new()", "JsonReporter")] {
+        assert_eq!(owner(item), name, "{}", item);
+    }
+}
@@ -0,0 +1,13 @@
+//! Checks that the copies of modules other crates of the workspace keep, so that they can be
+//! packaged on their own, are the same as the modules they were copied from.
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn macros_parse_config_files_like_the_crate() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let read = |path: &str| fs::read_to_string(root.join(path)).unwrap();
+    assert!(read("src/ini.rs") == read("editorconfig-macros/src/ini.rs"),
+            "editorconfig-macros/src/ini.rs differs from src/ini.rs, copy it over");
+}